@@ -1,4 +1,4 @@
-use crate::var_type::type_from_str;
+use crate::var_type::type_from_str_with_generics;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use std::rc::Rc;
@@ -8,17 +8,29 @@ use syn::{Attribute, Expr, ExprLit, Lit, MetaList, MetaNameValue};
 pub struct Attributes {
     pub name: Option<Rc<str>>,
     pub return_type: Option<TokenStream2>,
+    /// Names of type parameters of generic function
+    pub generics: Vec<Rc<str>>,
 }
 
 impl Attributes {
     pub fn from_function_attrs(attrs: &Vec<Attribute>) -> Self {
         let mut new = Self::default();
+        let mut return_type = None;
         for Attribute { meta, .. } in attrs {
             match meta {
                 syn::Meta::List(MetaList { path, tokens, .. })
                     if quote!(#path).to_string() == "return_type" =>
                 {
-                    new.return_type = Some(type_from_str(&tokens.to_string()));
+                    return_type = Some(tokens.to_string());
+                }
+                syn::Meta::List(MetaList { path, tokens, .. })
+                    if quote!(#path).to_string() == "generics" =>
+                {
+                    new.generics = tokens
+                        .to_string()
+                        .split(',')
+                        .map(|name| name.trim().into())
+                        .collect();
                 }
                 syn::Meta::NameValue(MetaNameValue {
                     path,
@@ -31,6 +43,8 @@ impl Attributes {
                 _ => (),
             }
         }
+        new.return_type =
+            return_type.map(|return_type| type_from_str_with_generics(&return_type, &new.generics));
         new
     }
 }
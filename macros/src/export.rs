@@ -1,12 +1,13 @@
 use crate::{
     attributes::Attributes,
     quote,
-    var_type::{type_from_str, type_quote},
+    var_type::{type_from_str, type_from_str_with_generics, type_quote},
 };
 use proc_macro2::TokenStream;
+use std::rc::Rc;
 use syn::{
-    Attribute, Ident, Item, ItemConst, ItemFn, ItemMod, ItemUse, MetaList, PatIdent, PatType,
-    ReturnType, Type, Visibility,
+    Attribute, GenericArgument, Ident, Item, ItemConst, ItemFn, ItemMod, ItemUse, MetaList,
+    PatIdent, PatType, PathArguments, ReturnType, Type, Visibility,
 };
 
 pub fn export_module(module: &mut ItemMod) -> TokenStream {
@@ -74,16 +75,19 @@ pub fn export_item_fn(function: &mut ItemFn, mod_ident: Option<&Ident>) -> Token
     let params = function_params_from_itemfn(function);
     let args = args_from_function_params(&params);
     let args_importing = args_import_from_function_params(&params);
-    let params = params_from_function_params(&params);
+    let params = params_from_function_params(&params, &attr.generics);
     let return_type = get_return_type(function, attr.return_type);
+    let call = if returns_exec_error(function) {
+        quote!(#ident(#args))
+    } else {
+        quote!(simplesl::ToResult::<_, simplesl::errors::ExecError>::to_result(#ident(#args)))
+    };
     quote!(
         simplesl::function::Function::new(
             simplesl::function::Params(std::sync::Arc::new([#params])),
             |interpreter| {
                 #args_importing
-                simplesl::ToResult::<_, simplesl::errors::ExecError>::to_result(
-                    #ident(#args)
-                ).map(|value| value.into())
+                #call.map(|value| value.into())
             },
             #return_type,
         ).into()
@@ -116,7 +120,13 @@ pub fn args_from_function_params(params: &[(Ident, Vec<Attribute>, Box<Type>)])
 pub fn args_import_from_function_params(
     params: &[(Ident, Vec<Attribute>, Box<Type>)],
 ) -> TokenStream {
-    params.iter().fold(quote!(), |acc, (ident, _, _)| {
+    params.iter().fold(quote!(), |acc, (ident, _, param_type)| {
+        if is_call_depth(param_type) {
+            return quote!(
+                #acc
+                let #ident = interpreter.call_depth();
+            );
+        }
         let ident_str = ident.to_string();
         quote!(
             #acc
@@ -125,18 +135,37 @@ pub fn args_import_from_function_params(
     })
 }
 
-pub fn params_from_function_params(params: &[(Ident, Vec<Attribute>, Box<Type>)]) -> TokenStream {
-    params.iter().fold(quote!(), |acc, param| {
-        let param = param_from_function_param(param);
-        quote!(#acc #param,)
-    })
+pub fn params_from_function_params(
+    params: &[(Ident, Vec<Attribute>, Box<Type>)],
+    generics: &[Rc<str>],
+) -> TokenStream {
+    params
+        .iter()
+        .filter(|(_, _, param_type)| !is_call_depth(param_type))
+        .fold(quote!(), |acc, param| {
+            let param = param_from_function_param(param, generics);
+            quote!(#acc #param,)
+        })
+}
+
+/// Checks if param is `CallDepth`, such param receives depth of the call
+/// instead of being `SimpleSL` param
+fn is_call_depth(param_type: &Type) -> bool {
+    let Type::Path(path) = param_type else {
+        return false;
+    };
+    path.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "CallDepth")
 }
 
 fn param_from_function_param(
     (ident, attrs, param_type): &(Ident, Vec<Attribute>, Box<Type>),
+    generics: &[Rc<str>],
 ) -> TokenStream {
     let ident = ident.to_string();
-    let param_type = get_type_from_attrs(attrs)
+    let param_type = get_type_from_attrs(attrs, generics)
         .unwrap_or_else(|| quote!(<#param_type as simplesl::variable::TypeOf>::type_of()));
     quote!(
         simplesl::function::Param {
@@ -146,17 +175,42 @@ fn param_from_function_param(
     )
 }
 
-fn get_type_from_attrs(attrs: &[Attribute]) -> Option<TokenStream> {
+fn get_type_from_attrs(attrs: &[Attribute], generics: &[Rc<str>]) -> Option<TokenStream> {
     for attr in attrs {
         if let syn::Meta::List(MetaList { path, tokens, .. }) = &attr.meta
             && quote!(#path).to_string() == "var_type"
         {
-            return Some(type_quote(&tokens.clone().into()));
+            if generics.is_empty() {
+                return Some(type_quote(tokens.clone().into()));
+            }
+            return Some(type_from_str_with_generics(&tokens.to_string(), generics));
         }
     }
     None
 }
 
+/// Checks if function returns `Result<_, ExecError>`, such errors are propagated
+/// instead of being converted into variable
+fn returns_exec_error(function: &ItemFn) -> bool {
+    let ReturnType::Type(_, syn_type) = &function.sig.output else {
+        return false;
+    };
+    let Type::Path(path) = syn_type.as_ref() else {
+        return false;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return false;
+    };
+    segment.ident == "Result"
+        && arguments.args.iter().nth(1).is_some_and(|argument| {
+            matches!(argument, GenericArgument::Type(Type::Path(error))
+                if error.path.segments.last().is_some_and(|segment| segment.ident == "ExecError"))
+        })
+}
+
 pub fn get_return_type(function: &ItemFn, return_type: Option<TokenStream>) -> TokenStream {
     let ReturnType::Type(_, syn_type) = &function.sig.output else {
         return type_from_str("()");
@@ -15,6 +15,10 @@ use syn::{Ident, ItemFn, ItemMod, parse, parse_macro_input};
 use var::quote;
 use var_type::type_quote;
 
+/// Macro simplifying declaring `SimpleSL` functions
+///
+/// # Panics
+/// Panics when input isn't valid `SimpleSL` declaration list
 #[proc_macro]
 pub fn decls(item: TokenStream) -> TokenStream {
     let str = item.to_string();
@@ -53,9 +57,11 @@ pub fn export(attr: TokenStream, module: TokenStream) -> TokenStream {
 }
 
 /// Macro simplifying creating `SimpleSL` Type
+///
+/// Type aliases registered in an interpreter can be used with `var_type!(interpreter => Alias | ())`
 #[proc_macro]
 pub fn var_type(item: TokenStream) -> TokenStream {
-    type_quote(&item).into()
+    type_quote(item).into()
 }
 
 /// Macro simplifying creating `SimpleSL` Variable
@@ -1,24 +1,55 @@
 use itertools::Itertools;
 use pest::{Parser, iterators::Pair};
-use proc_macro::TokenStream;
+use proc_macro::{Spacing, TokenStream, TokenTree};
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 use simplesl_parser::{Rule, SimpleSLParser, unexpected};
+use std::rc::Rc;
 
-pub fn type_quote(item: &TokenStream) -> TokenStream2 {
-    let item_str = item.to_string();
-    type_from_str(&item_str)
+/// Determines what idents used in type refer to
+#[derive(Clone, Copy)]
+enum Idents<'a> {
+    /// Rust variables holding types
+    Variables,
+    /// Type aliases registered in the interpreter
+    Aliases(&'a TokenStream2),
+    /// Type parameters of generic function, other idents are rust variables
+    Generics(&'a [Rc<str>]),
+}
+
+pub fn type_quote(item: TokenStream) -> TokenStream2 {
+    let tokens: Vec<TokenTree> = item.into_iter().collect();
+    let arrow = tokens.windows(2).position(|window| {
+        matches!(window, [TokenTree::Punct(eq), TokenTree::Punct(gt)]
+            if eq.as_char() == '=' && eq.spacing() == Spacing::Joint && gt.as_char() == '>')
+    });
+    let Some(arrow) = arrow else {
+        return type_from_str(&TokenStream::from_iter(tokens).to_string());
+    };
+    let interpreter: TokenStream2 = TokenStream::from_iter(tokens[..arrow].to_vec()).into();
+    let item_str = TokenStream::from_iter(tokens[arrow + 2..].to_vec()).to_string();
+    type_from_str_with_idents(&item_str, Idents::Aliases(&interpreter))
 }
 
 pub fn type_from_str(item_str: &str) -> TokenStream2 {
+    type_from_str_with_idents(item_str, Idents::Variables)
+}
+
+/// Idents listed in generics are treated as type parameters of generic function
+pub fn type_from_str_with_generics(item_str: &str, generics: &[Rc<str>]) -> TokenStream2 {
+    type_from_str_with_idents(item_str, Idents::Generics(generics))
+}
+
+fn type_from_str_with_idents(item_str: &str, idents: Idents) -> TokenStream2 {
     let pair = SimpleSLParser::parse(Rule::r#type_ident, item_str)
         .unwrap_or_else(|error| panic!("{error}"))
         .next()
         .unwrap();
-    type_token_from_pair(pair)
+    type_token_from_pair(pair, idents)
 }
 
-fn type_token_from_pair(pair: Pair<Rule>) -> TokenStream2 {
+fn type_token_from_pair(pair: Pair<Rule>, idents: Idents) -> TokenStream2 {
+    let type_token_from_pair = |pair| type_token_from_pair(pair, idents);
     match pair.as_rule() {
         Rule::bool_type => quote!(simplesl::variable::Type::Bool),
         Rule::int_type => quote!(simplesl::variable::Type::Int),
@@ -29,7 +60,7 @@ fn type_token_from_pair(pair: Pair<Rule>) -> TokenStream2 {
         Rule::never => quote!(simplesl::variable::Type::Never),
         Rule::multi_ident => pair
             .into_inner()
-            .map(|pair| type_token_from_pair(pair))
+            .map(type_token_from_pair)
             .reduce(|acc, curr| quote!(#acc | # curr))
             .unwrap(),
         Rule::array_type_ident => {
@@ -57,10 +88,7 @@ fn type_token_from_pair(pair: Pair<Rule>) -> TokenStream2 {
                 }.into()
             ))
         }
-        Rule::ident => {
-            let ident = format_ident!("{}", pair.as_str());
-            quote!(#ident)
-        }
+        Rule::ident => ident_token(pair.as_str(), idents),
         Rule::function_type_params_ident => {
             let elements = pair
                 .into_inner()
@@ -86,6 +114,39 @@ fn type_token_from_pair(pair: Pair<Rule>) -> TokenStream2 {
                 simplesl::variable::StructType::from([#fields])
             ))
         }
+        Rule::map_type_ident => {
+            let mut pairs = pair.into_inner();
+            let key = type_token_from_pair(pairs.next().unwrap());
+            let value = type_token_from_pair(pairs.next().unwrap());
+            quote!(simplesl::variable::Type::Map(
+                simplesl::variable::MapType {
+                    key: #key,
+                    value: #value
+                }.into()
+            ))
+        }
+        Rule::set_type_ident => {
+            let element_type = pair.into_inner().next().map(type_token_from_pair).unwrap();
+            quote!(simplesl::variable::Type::Set((#element_type).into()))
+        }
         rule => unexpected!(rule),
     }
 }
+
+fn ident_token(ident: &str, idents: Idents) -> TokenStream2 {
+    match idents {
+        Idents::Aliases(interpreter) => quote!(
+            (#interpreter)
+                .get_type(#ident)
+                .unwrap_or_else(|| panic!("Type {} doesn't exist", #ident))
+                .clone()
+        ),
+        Idents::Generics(generics) if generics.iter().any(|name| **name == *ident) => {
+            quote!(simplesl::variable::Type::Generic(#ident.into()))
+        }
+        Idents::Generics(_) | Idents::Variables => {
+            let ident = format_ident!("{}", ident);
+            quote!(#ident)
+        }
+    }
+}
@@ -23,6 +23,7 @@ lazy_static::lazy_static! {
             .op(Op::infix(equal, Left) | Op::infix(not_equal, Left) | Op::infix(lower, Left)
                 | Op::infix(lower_equal, Left) | Op::infix(greater, Left)
                 | Op::infix(greater_equal, Left))
+            .op(Op::infix(range, Left) | Op::infix(range_inclusive, Left) | Op::infix(range_step, Left))
             .op(Op::infix(bitwise_or, Left))
             .op(Op::infix(xor, Left))
             .op(Op::infix(bitwise_and, Left))
@@ -33,10 +34,11 @@ lazy_static::lazy_static! {
             .op(Op::infix(map, Left) | Op::infix(filter, Left) | Op::infix(partition, Left)
                 | Op::infix(reduce, Left) | Op::postfix(sum) | Op::postfix(product)
                 | Op::postfix(all) | Op::postfix(reduce_any) | Op::postfix(bitand_reduce)
-                | Op::postfix(bitor_reduce) | Op::postfix(collect) | Op::postfix(iter))
+                | Op::postfix(bitor_reduce) | Op::postfix(collect) | Op::postfix(collect_set)
+                | Op::postfix(iter) | Op::postfix(type_filter))
             .op(Op::prefix(not) | Op::prefix(unary_minus) | Op::prefix(indirection))
-            .op(Op::postfix(at) | Op::postfix(slicing) | Op::postfix(type_filter)
-                | Op::postfix(function_call) | Op::postfix(tuple_access) | Op::postfix(field_access))
+            .op(Op::postfix(at) | Op::postfix(slicing) | Op::postfix(function_call)
+                | Op::postfix(tuple_access) | Op::postfix(field_access) | Op::postfix(propagate))
     };
 }
 
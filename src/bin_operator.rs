@@ -73,6 +73,32 @@ pub enum BinOperator {
     AssignPow,
     #[display("\\")]
     Partition,
+    #[display("..")]
+    Range,
+    #[display("..=")]
+    RangeInclusive,
+}
+
+impl BinOperator {
+    /// Returns true for `=` and compound assignment operators
+    #[must_use]
+    pub fn is_assign(self) -> bool {
+        matches!(
+            self,
+            Self::Assign
+                | Self::AssignAdd
+                | Self::AssignSubtract
+                | Self::AssignMultiply
+                | Self::AssignDivide
+                | Self::AssignModulo
+                | Self::AssignLShift
+                | Self::AssignRShift
+                | Self::AssignBitwiseAnd
+                | Self::AssignBitwiseOr
+                | Self::AssignXor
+                | Self::AssignPow
+        )
+    }
 }
 
 #[doc(hidden)]
@@ -113,6 +139,8 @@ impl From<Rule> for BinOperator {
             Rule::assign_xor => Self::AssignXor,
             Rule::assign_pow => Self::AssignPow,
             Rule::partition => Self::Partition,
+            Rule::range => Self::Range,
+            Rule::range_inclusive => Self::RangeInclusive,
             _ => unreachable!(),
         }
     }
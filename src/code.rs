@@ -1,43 +1,113 @@
 use crate::{
-    Error, ExecError, Interpreter,
-    instruction::{Exec, ExecStop, InstructionWithStr, local_variable::LocalVariables},
+    Error, ExecError, Interpreter, OptLevel, Warning,
+    instruction::{
+        ExecStop, InstructionWithStr,
+        local_variable::{LocalVariables, Slot},
+    },
+    optimizer::always_stops,
     variable::{ReturnType, Type, Variable},
+    vm::Chunk,
 };
 use pest::Parser;
 use simplesl_parser::{Rule, SimpleSLParser};
-use std::sync::Arc;
+use std::{fs, sync::Arc};
 
 #[derive(Debug, Clone)]
 pub struct Code {
     pub(crate) instructions: Arc<[InstructionWithStr]>,
+    pub(crate) warnings: Arc<[Warning]>,
+    /// Variables declared at the top level with their slots
+    variables: Arc<[(Arc<str>, Slot)]>,
+    chunk: Chunk,
 }
 
 impl Code {
+    pub(crate) fn new(
+        instructions: Arc<[InstructionWithStr]>,
+        variables: Arc<[(Arc<str>, Slot)]>,
+        warnings: Arc<[Warning]>,
+    ) -> Self {
+        let chunk = Chunk::new(&instructions);
+        Self {
+            instructions,
+            warnings,
+            variables,
+            chunk,
+        }
+    }
     pub fn parse(interpreter: &Interpreter, script: &str) -> Result<Self, Error> {
-        let parse = SimpleSLParser::parse(Rule::input, script)?;
+        Self::parse_with_opt_level(interpreter, script, OptLevel::default())
+    }
+    /// Parses script optimizing it as thoroughly as opt_level allows
+    pub fn parse_with_opt_level(
+        interpreter: &Interpreter,
+        script: &str,
+        opt_level: OptLevel,
+    ) -> Result<Self, Error> {
+        Self::parse_with(LocalVariables::new(interpreter), script, opt_level)
+    }
+    /// Parses script stored in the file at given path.
+    /// Locations of errors occurring during execution of the code include the path
+    pub fn parse_file(interpreter: &Interpreter, path: &str) -> Result<Self, Error> {
+        let script = fs::read_to_string(path)?;
         let mut local_variables = LocalVariables::new(interpreter);
-        let instructions = parse
-            .map(|pair| {
-                InstructionWithStr::new(pair, &mut local_variables)
-                    .and_then(|iws| Ok(iws.recreate(&mut local_variables)?))
-            })
-            .collect::<Result<_, Error>>()?;
-        Ok(Self { instructions })
+        local_variables.set_file(path);
+        Self::parse_with(local_variables, &script, OptLevel::default())
+    }
+    fn parse_with(
+        mut local_variables: LocalVariables,
+        script: &str,
+        opt_level: OptLevel,
+    ) -> Result<Self, Error> {
+        local_variables.opt_level = opt_level;
+        let parse = SimpleSLParser::parse(Rule::input, script)?;
+        let mut instructions = Vec::new();
+        let mut reachable = true;
+        for pair in parse {
+            // Unreachable instructions are still created so errors in them are reported
+            let instruction = InstructionWithStr::new(pair, &mut local_variables)?;
+            if !reachable {
+                continue;
+            }
+            let instruction = if opt_level == OptLevel::None {
+                instruction
+            } else {
+                instruction.recreate(&mut local_variables)?
+            };
+            reachable = opt_level == OptLevel::None || !always_stops(&instruction.instruction);
+            instructions.push(instruction);
+        }
+        let warnings = local_variables.take_warnings().into();
+        let variables = local_variables.layer_slots();
+        Ok(Self::new(instructions.into(), variables, warnings))
+    }
+    /// Returns warnings reported while parsing the code
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
     }
+    /// Executes the code with the default recursion limit
     pub fn exec(&self) -> Result<Variable, ExecError> {
         let mut interpreter = Interpreter::without_stdlib();
-        self.exec_unscoped(&mut interpreter)
+        self.exec_in(&mut interpreter)
     }
+    /// Executes the code, variables declared at its top level are inserted into the interpreter.
+    /// Variables assigned before an error are inserted as well
     pub fn exec_unscoped(&self, interpreter: &mut Interpreter) -> Result<Variable, ExecError> {
-        match self
-            .instructions
-            .iter()
-            .map(|instruction| instruction.exec(interpreter))
-            .last()
-            .unwrap_or(Ok(Variable::Void))
-        {
+        self.exec_in(interpreter)
+    }
+    fn exec_in(&self, interpreter: &mut Interpreter) -> Result<Variable, ExecError> {
+        let outer_frame = interpreter.replace_frame(Vec::new());
+        let result = self.chunk.exec(interpreter);
+        let frame = interpreter.replace_frame(outer_frame);
+        for (ident, slot) in self.variables.iter() {
+            if let Some(variable) = frame.get(*slot) {
+                interpreter.insert(ident.clone(), variable.clone());
+            }
+        }
+        match result {
             Ok(var) => Ok(var),
-            Err(ExecStop::Error(err)) => Err(err),
+            Err(ExecStop::Error(err)) => Err(err.in_function(None)),
             Err(_) => unreachable!("Return statement outside of function body"),
         }
     }
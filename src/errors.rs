@@ -1,6 +1,10 @@
 mod error;
 mod exec_error;
 mod parse_type_error;
+mod stack_trace;
+mod warning;
 pub use error::Error;
 pub use exec_error::ExecError;
 pub use parse_type_error::ParseTypeError;
+pub use stack_trace::{Frame, Location, StackTrace};
+pub use warning::Warning;
@@ -1,5 +1,8 @@
 use crate::{
-    BinOperator, ExecError, function::Param, unary_operator::UnaryOperator, variable::Type,
+    BinOperator, ExecError,
+    function::Param,
+    unary_operator::UnaryOperator,
+    variable::{Type, Variable},
 };
 use derive_more::From;
 use match_any::match_any;
@@ -25,11 +28,16 @@ pub enum Error {
     CannotTupleAccess(Arc<str>, Type),
     CannotFieldAccess(Arc<str>, Type),
     CannotIndexWith(Arc<str>),
+    CannotBeKey(Type),
     CannotSlice(Arc<str>, Type),
     ZeroDivision,
     ZeroModulo,
     OverflowShift,
-    MatchNotCovered,
+    ZeroStep,
+    CannotCompare(Variable, Variable),
+    MatchNotCovered(Type),
+    VariantsNotCovered(Type, Arc<[Arc<str>]>),
+    CannotInferReturnType(Arc<str>),
     #[from]
     IO(std::io::Error),
     #[from(pest::error::Error<Rule>)]
@@ -44,6 +52,14 @@ pub enum Error {
         returned: Type,
     },
     ReturnOutsideFunction,
+    PropagateOutsideFunction,
+    YieldOutsideFunction,
+    WrongGeneratorType(Type),
+    WrongYield {
+        function_name: Option<Arc<str>>,
+        element_type: Type,
+        yielded: Type,
+    },
     MissingReturn {
         function_name: Option<Arc<str>>,
         return_type: Type,
@@ -82,6 +98,17 @@ pub enum Error {
         given: Arc<str>,
         given_type: Type,
     },
+    User(Variable),
+    StackOverflow(Arc<str>),
+    CannotPropagate(Arc<str>, Type),
+    TypeDoesntExist(Arc<str>),
+    NotAnEnum(Arc<str>),
+    EmptyEnum(Arc<str>),
+    NoVariant(Arc<str>, Arc<str>),
+    PatternNeverMatches(Arc<str>, Type),
+    PatternNotCovering(Arc<str>, Type),
+    NotMut(Arc<str>),
+    MissingKey(Variable),
 }
 
 impl PartialEq for Error {
@@ -91,19 +118,36 @@ impl PartialEq for Error {
             | (Self::CannotBeParsed(l0), Self::CannotBeParsed(r0))
             | (Self::CannotIndexInto(l0), Self::CannotIndexInto(r0))
             | (Self::CannotIndexWith(l0), Self::CannotIndexWith(r0))
+            | (Self::CannotBeKey(l0), Self::CannotBeKey(r0))
             | (Self::Parsing(l0), Self::Parsing(r0))
             | (Self::IntegerOverflow(l0), Self::IntegerOverflow(r0))
             | (Self::NotAFunction(l0), Self::NotAFunction(r0))
             | (Self::NotATuple(l0), Self::NotATuple(r0))
             | (Self::CannotDetermineParams(l0), Self::CannotDetermineParams(r0))
             | (Self::CannotDetermineLength(l0), Self::CannotDetermineLength(r0))
-            | (Self::CannotReduce(l0), Self::CannotReduce(r0)) => l0 == r0,
+            | (Self::TypeDoesntExist(l0), Self::TypeDoesntExist(r0))
+            | (Self::NotAnEnum(l0), Self::NotAnEnum(r0))
+            | (Self::EmptyEnum(l0), Self::EmptyEnum(r0))
+            | (Self::MatchNotCovered(l0), Self::MatchNotCovered(r0))
+            | (Self::CannotInferReturnType(l0), Self::CannotInferReturnType(r0))
+            | (Self::WrongGeneratorType(l0), Self::WrongGeneratorType(r0))
+            | (Self::CannotReduce(l0), Self::CannotReduce(r0))
+            | (Self::User(l0), Self::User(r0))
+            | (Self::StackOverflow(l0), Self::StackOverflow(r0))
+            | (Self::NotMut(l0), Self::NotMut(r0)) => l0 == r0,
+            (Self::MissingKey(l0), Self::MissingKey(r0)) => l0 == r0,
             (Self::WrongType(l0, l1), Self::WrongType(r0, r1))
             | (Self::WrongCondition(l0, l1), Self::WrongCondition(r0, r1))
             | (Self::WrongNumberOfArguments(l0, l1), Self::WrongNumberOfArguments(r0, r1))
             | (Self::CannotTupleAccess(l0, l1), Self::CannotTupleAccess(r0, r1))
             | (Self::CannotFieldAccess(l0, l1), Self::CannotFieldAccess(r0, r1))
             | (Self::CannotSlice(l0, l1), Self::CannotSlice(r0, r1))
+            | (Self::CannotPropagate(l0, l1), Self::CannotPropagate(r0, r1))
+            | (Self::NoVariant(l0, l1), Self::NoVariant(r0, r1))
+            | (Self::PatternNeverMatches(l0, l1), Self::PatternNeverMatches(r0, r1))
+            | (Self::PatternNotCovering(l0, l1), Self::PatternNotCovering(r0, r1))
+            | (Self::CannotCompare(l0, l1), Self::CannotCompare(r0, r1))
+            | (Self::VariantsNotCovered(l0, l1), Self::VariantsNotCovered(r0, r1))
              => l0 == r0 && l1 == r1,
             (Self::IO(l0), Self::IO(r0)) | (Self::CannotUnescapeString(l0), Self::CannotUnescapeString(r0)) => {
                 l0.to_string() == r0.to_string()
@@ -131,6 +175,22 @@ impl PartialEq for Error {
                     && function_return_type == function_return_type2
                     && returned == returned2
             },
+            (
+                Self::WrongYield {
+                    function_name,
+                    element_type,
+                    yielded,
+                },
+                Self::WrongYield {
+                    function_name: function_name2,
+                    element_type: element_type2,
+                    yielded: yielded2,
+                },
+            ) => {
+                function_name == function_name2
+                    && element_type == element_type2
+                    && yielded == yielded2
+            },
             (
                 Self::WrongArgument{ function: f, param: p, given: g, given_type: gt },
                 Self::WrongArgument{ function: f2, param: p2, given: g2, given_type: gt2 }
@@ -183,7 +243,17 @@ impl fmt::Display for Error {
                 write!(f, "Cannot index into value of type {var_type}")
             }
             Self::CannotIndexWith(var_type) => {
-                write!(f, "Cannot index with {var_type}. Index must be int")
+                write!(
+                    f,
+                    "Cannot index with {var_type}. Index must be int, iterator of ints, key of the map or element of the set"
+                )
+            }
+            Self::CannotBeKey(var_type) => {
+                write!(
+                    f,
+                    "Value of type {var_type} cannot be used as key of map or element of set. \
+                    It must be int, string, bool or tuple of them"
+                )
             }
             Self::CannotTupleAccess(ins, var_type) => {
                 write!(
@@ -222,8 +292,27 @@ impl fmt::Display for Error {
             Self::OverflowShift => {
                 write!(f, "Cannot shift with overflow")
             }
-            Self::MatchNotCovered => {
-                write!(f, "All posible values must be covered in match")
+            Self::ZeroStep => write!(f, "Step of range cannot be 0"),
+            Self::CannotCompare(lhs, rhs) => write!(f, "Cannot compare {lhs} with {rhs}"),
+            Self::MatchNotCovered(var_type) => {
+                write!(
+                    f,
+                    "All posible values must be covered in match. Values of type {var_type} are not covered"
+                )
+            }
+            Self::VariantsNotCovered(var_type, variants) => {
+                write!(
+                    f,
+                    "All posible values must be covered in match. Values of type {var_type} are not covered, missing variants: {}",
+                    variants.join(", ")
+                )
+            }
+            Self::CannotInferReturnType(name) => {
+                write!(
+                    f,
+                    "Cannot infer return type of recursive function {name}\n\
+                    declare return type of the function with -> type"
+                )
             }
             Self::IO(error) => write!(f, "{error}"),
             Self::Parsing(error) => write!(f, "{error}"),
@@ -253,6 +342,37 @@ impl fmt::Display for Error {
                     "Return statement can only be used inside of function body"
                 )
             }
+            Self::PropagateOutsideFunction => {
+                write!(f, "Operator ? can only be used inside of function body")
+            }
+            Self::YieldOutsideFunction => {
+                write!(
+                    f,
+                    "Yield statement can only be used inside of function body"
+                )
+            }
+            Self::WrongGeneratorType(return_type) => {
+                write!(
+                    f,
+                    "Function containing yield statement must return iterator of type () -> (bool, T) \
+                    but is declared to return {return_type}"
+                )
+            }
+            Self::WrongYield {
+                function_name,
+                element_type,
+                yielded,
+            } => {
+                write!(
+                    f,
+                    "Cannot yield {yielded} from generator{}\n\
+                    Generator{0} declared to yield {element_type}",
+                    function_name
+                        .as_deref()
+                        .map(|value| format!(" {value}"))
+                        .unwrap_or("".into())
+                )
+            }
             Self::MissingReturn {
                 function_name,
                 return_type,
@@ -325,6 +445,32 @@ impl fmt::Display for Error {
                 f,
                 "mut declared to contain {declared} but initialized with {given} that is {given_type}"
             ),
+            Self::User(value) => write!(f, "Uncaught error: {value}"),
+            Self::StackOverflow(function) => {
+                write!(f, "Maximum recursion depth exceeded in {function}")
+            }
+            Self::TypeDoesntExist(name) => write!(f, "Type {name} doesn't exist"),
+            Self::NotAnEnum(name) => write!(f, "Cannot match variant of {name}. It is not an enum"),
+            Self::EmptyEnum(name) => write!(f, "Enum {name} has to declare at least one variant"),
+            Self::NoVariant(name, variant) => {
+                write!(f, "Enum {name} doesn't have variant {variant}")
+            }
+            Self::PatternNeverMatches(pattern, var_type) => {
+                write!(
+                    f,
+                    "Pattern {pattern} can never match value of type {var_type}"
+                )
+            }
+            Self::PatternNotCovering(pattern, var_type) => write!(
+                f,
+                "Pattern {pattern} doesn't match all values of type {var_type}"
+            ),
+            Self::NotMut(ins) => write!(f, "Cannot assign to {ins}. It is not mut"),
+            Self::MissingKey(key) => write!(f, "Map doesn't contain key {key}"),
+            Self::CannotPropagate(ins, var_type) => write!(
+                f,
+                "Cannot use ? on {ins} which is {var_type}. Only values of type T | () or T | struct{{error_code: int, msg: string}} can be propagated"
+            ),
         }
     }
 }
@@ -338,6 +484,13 @@ impl From<ExecError> for Error {
             ExecError::ZeroDivision => Self::ZeroDivision,
             ExecError::ZeroModulo => Self::ZeroModulo,
             ExecError::OverflowShift => Self::OverflowShift,
+            ExecError::ZeroStep => Self::ZeroStep,
+            ExecError::CannotCompare(lhs, rhs) => Self::CannotCompare(lhs, rhs),
+            ExecError::MissingKey(key) => Self::MissingKey(key),
+            ExecError::CannotBeKey(var_type) => Self::CannotBeKey(var_type),
+            ExecError::User(value) => Self::User(value),
+            ExecError::StackOverflow(function) => Self::StackOverflow(function),
+            ExecError::Traced { error, .. } => Self::from(*error),
         }
     }
 }
@@ -1,4 +1,7 @@
+use super::stack_trace::{Location, StackTrace};
+use crate::variable::{Type, Variable};
 use derive_more::Display;
+use std::sync::Arc;
 
 #[derive(Debug, Display, PartialEq)]
 pub enum ExecError {
@@ -14,6 +17,58 @@ pub enum ExecError {
     ZeroModulo,
     #[display("Cannot shift with overflow")]
     OverflowShift,
+    #[display("Step of range cannot be 0")]
+    ZeroStep,
+    #[display("Cannot compare {_0} with {_1}")]
+    CannotCompare(Variable, Variable),
+    #[display("Map doesn't contain key {_0}")]
+    MissingKey(Variable),
+    #[display("Value of type {_0} cannot be used as key of map or element of set")]
+    CannotBeKey(Type),
+    #[display("Uncaught error: {_0}")]
+    User(Variable),
+    #[display("Maximum recursion depth exceeded in {_0}")]
+    StackOverflow(Arc<str>),
+    #[display("{error}\n{trace}")]
+    Traced {
+        error: Box<ExecError>,
+        trace: StackTrace,
+    },
+}
+
+impl ExecError {
+    /// Returns error without stack trace
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Traced { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// Returns stack trace collected while error was traveling up the call stack
+    pub fn trace(&self) -> Option<&StackTrace> {
+        match self {
+            Self::Traced { trace, .. } => Some(trace),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn at(self, location: &Location) -> Self {
+        self.map_trace(|trace| trace.at(location))
+    }
+
+    pub(crate) fn in_function(self, function: Option<Arc<str>>) -> Self {
+        self.map_trace(|trace| trace.push(function))
+    }
+
+    fn map_trace(self, f: impl FnOnce(&mut StackTrace)) -> Self {
+        let (error, mut trace) = match self {
+            Self::Traced { error, trace } => (error, trace),
+            error => (Box::new(error), StackTrace::default()),
+        };
+        f(&mut trace);
+        Self::Traced { error, trace }
+    }
 }
 
 impl std::error::Error for ExecError {}
@@ -0,0 +1,148 @@
+use itertools::Itertools;
+use std::{fmt, sync::Arc};
+
+/// Position of an instruction in the source code
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub file: Option<Arc<str>>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}:")?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Single entry of [`StackTrace`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// Ident of the function, None for top-level code
+    pub function: Option<Arc<str>>,
+    /// Location of the instruction being executed when error occurred
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let function = self.function.as_deref().unwrap_or("<script>");
+        match &self.location {
+            Some(location) => write!(f, "at {function} ({location})"),
+            None => write!(f, "at {function}"),
+        }
+    }
+}
+
+/// Call stack collected while error is traveling up from the place it occurred.
+/// The most recent call comes first
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StackTrace {
+    frames: Vec<Frame>,
+    location: Option<Location>,
+}
+
+impl StackTrace {
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Sets location of the current frame unless more precise one is already known
+    pub(crate) fn at(&mut self, location: &Location) {
+        if self.location.is_none() {
+            self.location = Some(location.clone());
+        }
+    }
+
+    /// Closes the current frame as one belonging to the given function
+    pub(crate) fn push(&mut self, function: Option<Arc<str>>) {
+        let location = self.location.take();
+        self.frames.push(Frame { function, location });
+    }
+}
+
+/// Consecutive equal frames, e.g. of recursion, are displayed once with their count
+impl fmt::Display for StackTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let groups = self.frames.iter().dedup_with_count();
+        for (i, (count, frame)) in groups.enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "    {frame}")?;
+            if count > 1 {
+                write!(f, " (×{count})")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Code, ExecError, Interpreter, errors::stack_trace::Frame};
+
+    #[test]
+    fn stack_trace() {
+        let script = "divide := (a: int, b: int) -> int {
+    return a / b
+}
+average := (sum: int, count: int) -> int {
+    x := 5
+    return divide(sum, count)
+}
+y := average(15, 0)";
+        let error = Code::parse(&Interpreter::without_stdlib(), script)
+            .unwrap()
+            .exec()
+            .unwrap_err();
+        assert_eq!(error.root(), &ExecError::ZeroDivision);
+        let frames: Box<[(Option<&str>, usize, usize)]> = error
+            .trace()
+            .unwrap()
+            .frames()
+            .iter()
+            .map(|Frame { function, location }| {
+                let location = location.as_ref().unwrap();
+                (function.as_deref(), location.line, location.column)
+            })
+            .collect();
+        assert_eq!(
+            frames.as_ref(),
+            [
+                (Some("divide"), 2, 5),
+                (Some("average"), 6, 5),
+                (None, 8, 6)
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            "Cannot divide by 0
+    at divide (2:5)
+    at average (6:5)
+    at <script> (8:6)"
+        );
+    }
+
+    #[test]
+    fn recursion_trace() {
+        let script = "f := (n: int) -> int {
+    return 1 + f(n)
+}
+f(0)";
+        let error = Code::parse(&Interpreter::without_stdlib(), script)
+            .unwrap()
+            .exec()
+            .unwrap_err();
+        assert_eq!(error.trace().unwrap().frames().len(), 1001);
+        assert_eq!(
+            error.to_string(),
+            "Maximum recursion depth exceeded in f
+    at f (2:5) (×1000)
+    at <script> (4:1)"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use super::Location;
+use std::{fmt, sync::Arc};
+
+/// Problem found during parsing that doesn't prevent the code from running
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Warning {
+    /// Match arm that can never be reached because values it matches are covered by previous arms
+    UnreachableArm {
+        arm: Arc<str>,
+        location: Option<Location>,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnreachableArm { arm, location } => {
+                if let Some(location) = location {
+                    write!(f, "{location}: ")?;
+                }
+                write!(
+                    f,
+                    "Unreachable match arm {arm}. Values it matches are already covered"
+                )
+            }
+        }
+    }
+}
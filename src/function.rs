@@ -1,20 +1,29 @@
 mod body;
+mod closure;
+mod generator;
+mod native_iter;
 mod param;
-pub(crate) use self::body::Body;
+use self::generator::Generator;
+pub(crate) use self::native_iter::{FunctionIter, NativeIter};
 pub use self::param::{Param, Params};
+pub(crate) use self::{body::Body, closure::Closure};
 use crate::{
     self as simplesl, Code, Error, ExecError, Interpreter,
-    instruction::{
-        ExecStop, InstructionWithStr, block::Block, function::call, unary_operation::UnaryOperation,
-    },
+    instruction::{ExecStop, InstructionWithStr, function::call, unary_operation::UnaryOperation},
+    interpreter::CallDepth,
     join,
     unary_operator::UnaryOperator,
-    variable::{FunctionType, ReturnType, Type, Typed, Variable},
+    variable::{FunctionType, ReturnType, Type, Typed, Variable, infer_generics},
 };
 use derive_more::Display;
 use simplesl_macros::var_type;
 use std::{iter::zip, sync::Arc};
 
+/// Stack space that has to be left for function call, otherwise it continues on a new stack segment
+pub(crate) const STACK_RED_ZONE: usize = 256 * 1024;
+/// Size of stack segments allocated for deeply nested calls
+pub(crate) const STACK_SIZE: usize = 4 * 1024 * 1024;
+
 #[derive(Debug, Display, Clone)]
 #[display("({params})->{return_type}")]
 pub struct Function {
@@ -22,6 +31,8 @@ pub struct Function {
     pub(crate) params: Params,
     pub(crate) body: Body,
     pub(crate) return_type: Type,
+    /// Values of variables captured by function literal
+    pub(crate) captured: Arc<[Variable]>,
 }
 
 impl Function {
@@ -35,9 +46,55 @@ impl Function {
             params,
             body: Body::Native(body),
             return_type,
+            captured: Arc::from([]),
+        }
+    }
+
+    /// Creates iterator function of type `() -> (bool, element_type)` returning given elements
+    pub(crate) fn native_iter(
+        elements: impl Iterator<Item = Result<Variable, ExecError>> + Send + 'static,
+        element_type: Type,
+    ) -> Self {
+        Self::native_adaptor(elements, element_type, CallDepth::default())
+    }
+
+    /// Creates iterator function returning elements produced by iterators called at given depth
+    pub(crate) fn native_adaptor(
+        elements: impl Iterator<Item = Result<Variable, ExecError>> + Send + 'static,
+        element_type: Type,
+        call_depth: CallDepth,
+    ) -> Self {
+        let default = Variable::of_type(&element_type).unwrap_or(Variable::Void);
+        Self {
+            ident: None,
+            params: Params(Arc::from([])),
+            body: Body::Iter(NativeIter::new(elements, default, call_depth)),
+            return_type: var_type!((bool, element_type)),
+            captured: Arc::from([]),
+        }
+    }
+
+    /// Creates function calling given one and dropping its result
+    pub(crate) fn discarding(function: Arc<Self>) -> Self {
+        Self {
+            ident: function.ident.clone(),
+            params: function.params.clone(),
+            return_type: Type::Void,
+            captured: Arc::from([]),
+            body: Body::Discard(function),
         }
     }
 
+    /// Returns Rust iterator over elements returned by iterator function called at given depth.
+    /// Iterator implemented in Rust is called deeper than iterators producing its elements
+    pub(crate) fn iter(self: Arc<Self>, call_depth: CallDepth) -> FunctionIter {
+        let call_depth = match &self.body {
+            Body::Iter(iter) => call_depth.deeper(iter.call_depth().nested()),
+            _ => call_depth,
+        };
+        FunctionIter(self, call_depth)
+    }
+
     pub fn of_type(fn_type: &FunctionType) -> Option<Self> {
         let params = fn_type
             .params
@@ -53,7 +110,7 @@ impl Function {
         Some(Self {
             ident: None,
             params,
-            body: Body::Lang(
+            body: Body::lang(
                 [InstructionWithStr {
                     str: format!("return {returned}").into(),
                     instruction: UnaryOperation {
@@ -61,51 +118,124 @@ impl Function {
                         op: UnaryOperator::Return,
                     }
                     .into(),
+                    location: None,
                 }]
                 .into(),
+                false,
             ),
             return_type: fn_type.return_type(),
+            captured: Arc::from([]),
         })
     }
 
     pub fn create_call(self: Arc<Self>, args: Vec<Variable>) -> Result<Code, Error> {
         let ident = self.ident.clone().unwrap_or_else(|| Arc::from("function"));
         let str = format!("{}({})", ident, join(args.iter(), ", ")).into();
-        let instructions = call::create_from_variables(ident, self, args)?;
-        Ok(Code {
-            instructions: Arc::new([InstructionWithStr {
-                instruction: Block { instructions }.into(),
+        let instruction = call::create_from_variables(ident, self, args)?;
+        Ok(Code::new(
+            Arc::new([InstructionWithStr {
+                instruction,
                 str,
+                location: None,
             }]),
-        })
+            Arc::from([]),
+            Arc::from([]),
+        ))
     }
 
-    pub(crate) fn exec(&self, interpreter: &mut Interpreter) -> Result<Variable, ExecError> {
-        let body = match &self.body {
-            Body::Lang(body) => body,
-            Body::Native(body) => return (body)(interpreter),
+    /// Executes function returning recursive call in tail position of its body instead of executing it
+    fn exec(&self, interpreter: &mut Interpreter<'static>) -> Result<Variable, ExecStop> {
+        let result = match &self.body {
+            Body::Lang(body) => match body.exec(interpreter) {
+                Ok(_) => Ok(Variable::Void),
+                Err(ExecStop::Return(var)) => Ok(var),
+                Err(ExecStop::Error(error)) => Err(error),
+                Err(tail_call @ ExecStop::TailCall(_)) => return Err(tail_call),
+                Err(ExecStop::Break) => unreachable!("Break outside of loop"),
+                Err(ExecStop::Continue) => unreachable!("Continue outside of loop"),
+                Err(ExecStop::Yield(_)) => unreachable!("Yield outside of generator"),
+            },
+            Body::Native(body) => (body)(interpreter),
+            Body::Iter(iter) => iter.next_tuple(),
+            Body::Discard(_) => unreachable!("Function dropping result executed with interpreter"),
+            Body::Generator(body) => {
+                let element_type = self.return_type.iter_element().unwrap();
+                // Interpreter of the call isn't used anymore so it is moved to the generator
+                let interpreter = std::mem::replace(interpreter, Interpreter::without_stdlib());
+                let generator = Generator::new(body.clone(), interpreter, self.ident.clone());
+                return Ok(Self::native_iter(generator, element_type).into());
+            }
         };
-        match interpreter.exec(body) {
-            Ok(_) => Ok(Variable::Void),
-            Err(ExecStop::Return(var)) => Ok(var),
-            Err(ExecStop::Error(error)) => Err(error),
-            Err(ExecStop::Break) => unreachable!("Break outside of loop"),
-            Err(ExecStop::Continue) => unreachable!("Continue outside of loop"),
-        }
+        result.map_err(|error| ExecStop::Error(error.in_function(Some(self.name()))))
     }
 
+    /// Executes function with given args called at given depth.
+    /// Recursive calls in tail position are executed here after the frame of the caller is released,
+    /// so tail recursion runs in constant Rust stack
     pub(crate) fn exec_with_args(
         self: &Arc<Self>,
         args: &[Variable],
+        call_depth: CallDepth,
     ) -> Result<Variable, ExecError> {
+        if let Body::Discard(function) = &self.body {
+            return function
+                .exec_with_args(args, call_depth)
+                .map(|_| Variable::Void);
+        }
+        let Some(call_depth) = call_depth.enter() else {
+            return Err(ExecError::StackOverflow(self.name()));
+        };
+        let mut args = args.to_vec();
+        loop {
+            let mut interpreter = self.interpreter_with_args(args, call_depth);
+            // Deep recursion continues on stack allocated on the heap instead of overflowing
+            let result =
+                stacker::maybe_grow(STACK_RED_ZONE, STACK_SIZE, || self.exec(&mut interpreter));
+            match result {
+                Ok(value) => return Ok(value),
+                Err(ExecStop::TailCall(next_args)) => args = next_args,
+                Err(ExecStop::Error(error)) => return Err(error),
+                Err(_) => unreachable!("Function returned break or continue"),
+            }
+        }
+    }
+
+    /// Returns ident of function used in errors
+    fn name(&self) -> Arc<str> {
+        self.ident.clone().unwrap_or_else(|| "<anonymous>".into())
+    }
+
+    /// Creates interpreter executing function with args bound to its params
+    fn interpreter_with_args(
+        self: &Arc<Self>,
+        args: Vec<Variable>,
+        call_depth: CallDepth,
+    ) -> Interpreter<'static> {
         let mut interpreter = Interpreter::without_stdlib();
-        if let Some(ident) = &self.ident {
-            interpreter.insert(ident.clone(), self.clone().into())
+        interpreter.set_call_depth(call_depth);
+        if self.params.is_generic() {
+            let generics = infer_generics(
+                self.params.iter().map(|param| &param.var_type),
+                args.iter().map(Typed::as_type),
+            );
+            for (name, var_type) in generics {
+                interpreter.insert_type(name, var_type);
+            }
         }
-        for (arg, Param { var_type: _, name }) in zip(args, self.params.iter()) {
-            interpreter.insert(name.clone(), arg.clone());
+        if let Body::Native(_) = self.body {
+            for (arg, param) in zip(args, self.params.iter()) {
+                interpreter.insert(param.name.clone(), param.bind(arg));
+            }
+        } else {
+            // Params occupy the first slots of frame followed by the function itself
+            // and captured values
+            let this = self.ident.as_ref().map(|_| self.clone().into());
+            let captured = self.captured.iter().cloned();
+            let args = zip(args, self.params.iter()).map(|(arg, param)| param.bind(arg));
+            interpreter.replace_frame(args.chain(this).chain(captured).collect());
+            interpreter.set_function(self.clone());
         }
-        self.exec(&mut interpreter)
+        interpreter
     }
 }
 
@@ -1,10 +1,28 @@
+use super::{Function, native_iter::NativeIter};
 use crate::{
     ExecError, instruction::InstructionWithStr, interpreter::Interpreter, variable::Variable,
+    vm::Chunk,
 };
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub(crate) enum Body {
-    Lang(Arc<[InstructionWithStr]>),
+    Lang(Chunk),
     Native(fn(&mut Interpreter) -> Result<Variable, ExecError>),
+    Iter(NativeIter),
+    /// Body of generator, executed lazily when elements are requested
+    Generator(Chunk),
+    /// Calls the function dropping its result
+    Discard(Arc<Function>),
+}
+
+impl Body {
+    /// Creates body of function written in SimpleSL compiling its instructions
+    pub fn lang(instructions: Arc<[InstructionWithStr]>, generator: bool) -> Self {
+        if generator {
+            Self::Generator(Chunk::new(&instructions))
+        } else {
+            Self::Lang(Chunk::function(&instructions))
+        }
+    }
 }
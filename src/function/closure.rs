@@ -0,0 +1,37 @@
+use super::{Body, Function, Params};
+use crate::{
+    Interpreter,
+    variable::{Type, Variable},
+};
+use std::sync::Arc;
+
+/// Function literal which body is compiled while parsing.
+/// Values of variables it captures are bound when the function is created
+#[derive(Debug)]
+pub(crate) struct Closure {
+    pub ident: Option<Arc<str>>,
+    pub params: Params,
+    pub body: Body,
+    pub return_type: Type,
+    /// Number of captured values, they occupy slots of frame following params and the function itself
+    pub captures: usize,
+}
+
+impl Closure {
+    /// Creates function binding captured values.
+    /// Type params are substituted with types inferred in the enclosing function
+    pub fn create(
+        &self,
+        captured: impl Into<Arc<[Variable]>>,
+        interpreter: &Interpreter,
+    ) -> Function {
+        let get_type = |name: &str| interpreter.get_type(name).cloned();
+        Function {
+            ident: self.ident.clone(),
+            params: self.params.substitute(&get_type),
+            body: self.body.clone(),
+            return_type: self.return_type.substitute(&get_type),
+            captured: captured.into(),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{
+    ExecError, Interpreter,
+    instruction::ExecStop,
+    variable::Variable,
+    vm::{Chunk, ExecState},
+};
+use std::sync::Arc;
+
+/// Rust iterator over values yielded by generator.
+/// Execution of body is suspended on every yield and resumed when the next element is requested.
+/// Interpreter of the generator carries depth of the call that created it
+pub(crate) struct Generator {
+    body: Chunk,
+    interpreter: Interpreter<'static>,
+    state: Option<ExecState>,
+    ident: Option<Arc<str>>,
+}
+
+impl Generator {
+    pub fn new(body: Chunk, interpreter: Interpreter<'static>, ident: Option<Arc<str>>) -> Self {
+        Self {
+            body,
+            interpreter,
+            state: Some(ExecState::default()),
+            ident,
+        }
+    }
+}
+
+impl Iterator for Generator {
+    type Item = Result<Variable, ExecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // State is dropped when body finishes, so the next calls return nothing
+        let mut state = self.state.take()?;
+        match self.body.resume(&mut state, &mut self.interpreter) {
+            Err(ExecStop::Yield(value)) => {
+                self.state = Some(state);
+                Some(Ok(value))
+            }
+            Err(ExecStop::Error(error)) => {
+                let ident = self.ident.clone().unwrap_or_else(|| "<anonymous>".into());
+                Some(Err(error.in_function(Some(ident))))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Body, Function, STACK_RED_ZONE, STACK_SIZE};
+use crate::{ExecError, interpreter::CallDepth, variable::Variable};
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+type Elements = Box<dyn Iterator<Item = Result<Variable, ExecError>> + Send>;
+
+/// State of iterator function implemented in Rust
+#[derive(Clone)]
+pub(crate) struct NativeIter {
+    /// None while elements are being produced
+    elements: Arc<Mutex<Option<Elements>>>,
+    default: Variable,
+    /// Depth of calls of iterators producing elements
+    call_depth: CallDepth,
+}
+
+impl NativeIter {
+    pub fn new(
+        elements: impl Iterator<Item = Result<Variable, ExecError>> + Send + 'static,
+        default: Variable,
+        call_depth: CallDepth,
+    ) -> Self {
+        Self {
+            elements: Arc::new(Mutex::new(Some(Box::new(elements)))),
+            default,
+            call_depth,
+        }
+    }
+
+    pub fn call_depth(&self) -> CallDepth {
+        self.call_depth
+    }
+
+    /// Returns next element, None if iterator is exhausted.
+    /// Elements are taken out of the lock while they are produced,
+    /// so iterator called by itself is treated as exhausted instead of deadlocking
+    pub fn next(&self) -> Option<Result<Variable, ExecError>> {
+        let mut elements = self.elements.lock().unwrap().take()?;
+        let next = elements.next();
+        *self.elements.lock().unwrap() = Some(elements);
+        next
+    }
+
+    /// Returns `(true, element)` or `(false, default)` if iterator is exhausted,
+    /// the same as iterator function written in SimpleSL would
+    pub fn next_tuple(&self) -> Result<Variable, ExecError> {
+        Ok(match self.next().transpose()? {
+            Some(element) => Variable::Tuple([true.into(), element].into()),
+            None => Variable::Tuple([false.into(), self.default.clone()].into()),
+        })
+    }
+}
+
+impl Drop for NativeIter {
+    fn drop(&mut self) {
+        // Adaptors are dropped together with iterators they consume,
+        // so long chains of them are dropped on stack grown like for nested calls
+        if let Some(elements) = Arc::get_mut(&mut self.elements) {
+            let elements = elements
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            stacker::maybe_grow(STACK_RED_ZONE, STACK_SIZE, || drop(elements));
+        }
+    }
+}
+
+impl fmt::Debug for NativeIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeIter")
+            .field("default", &self.default)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Rust iterator over elements returned by iterator function of type `() -> (bool, T)`
+/// called at given depth
+pub(crate) struct FunctionIter(pub Arc<Function>, pub CallDepth);
+
+impl FunctionIter {
+    /// Returns depth at which the iterator function is called
+    pub fn call_depth(&self) -> CallDepth {
+        self.1
+    }
+}
+
+impl Iterator for FunctionIter {
+    type Item = Result<Variable, ExecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Body::Iter(iter) = &self.0.body {
+            // Iterator implemented in Rust is advanced directly, but counts as a call
+            if self.1.enter().is_none() {
+                return Some(Err(ExecError::StackOverflow(self.0.name())));
+            }
+            return stacker::maybe_grow(STACK_RED_ZONE, STACK_SIZE, || iter.next());
+        }
+        let tuple = match self.0.exec_with_args(&[], self.1) {
+            Ok(Variable::Tuple(tuple)) => tuple,
+            Ok(_) => unreachable!("Iterator function returned value that is not tuple"),
+            Err(error) => return Some(Err(error)),
+        };
+        (tuple[0] == Variable::Bool(true)).then(|| Ok(tuple[1].clone()))
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{instruction::local_variable::LocalVariableMap, join, variable::Type};
+use super::Function;
+use crate::{
+    Error,
+    instruction::local_variable::{LocalVariableMap, LocalVariables},
+    join,
+    variable::{FunctionType, Type, Variable},
+};
 use derive_more::Display;
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -11,13 +17,48 @@ pub struct Param {
     pub var_type: Type,
 }
 
-#[doc(hidden)]
-impl From<Pair<'_, Rule>> for Param {
-    fn from(value: Pair<'_, Rule>) -> Self {
-        let mut inner = value.into_inner();
-        Self {
+impl Param {
+    pub(crate) fn create(
+        pair: Pair<'_, Rule>,
+        local_variables: &LocalVariables,
+    ) -> Result<Self, Error> {
+        let mut inner = pair.into_inner();
+        Ok(Self {
             name: inner.next().unwrap().as_str().into(),
-            var_type: Type::from(inner.next().unwrap()),
+            var_type: local_variables.create_type(inner.next().unwrap())?,
+        })
+    }
+
+    /// Checks if value of type arg_type can be passed as the param.
+    /// Function returning any value can be passed where function returning `()` is expected
+    pub(crate) fn accepts(&self, arg_type: &Type) -> bool {
+        if arg_type.matches(&self.var_type) {
+            return true;
+        }
+        let Type::Function(expected) = &self.var_type else {
+            return false;
+        };
+        expected.return_type == Type::Void
+            && arg_type.params().is_some_and(|params| {
+                let return_type = Type::Void;
+                FunctionType {
+                    params,
+                    return_type,
+                }
+                .matches(expected)
+            })
+    }
+
+    /// Converts arg passed as the param, result of function passed where function
+    /// returning `()` is expected is dropped
+    pub(crate) fn bind(&self, arg: Variable) -> Variable {
+        match (arg, &self.var_type) {
+            (Variable::Function(function), Type::Function(expected))
+                if expected.return_type == Type::Void && function.return_type != Type::Void =>
+            {
+                Function::discarding(function).into()
+            }
+            (arg, _) => arg,
         }
     }
 }
@@ -26,6 +67,35 @@ impl From<Pair<'_, Rule>> for Param {
 #[display("{}", join(self.as_ref(), ", "))]
 pub struct Params(pub Arc<[Param]>);
 
+impl Params {
+    pub(crate) fn create(
+        pair: Pair<'_, Rule>,
+        local_variables: &LocalVariables,
+    ) -> Result<Self, Error> {
+        pair.into_inner()
+            .map(|pair| Param::create(pair, local_variables))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl Params {
+    /// Replaces type parameters in types of params with types returned by `get`
+    pub(crate) fn substitute(&self, get: &impl Fn(&str) -> Option<Type>) -> Self {
+        self.iter()
+            .map(|Param { name, var_type }| Param {
+                name: name.clone(),
+                var_type: var_type.substitute(get),
+            })
+            .collect()
+    }
+
+    /// Returns true if type of any param contains type parameter
+    pub(crate) fn is_generic(&self) -> bool {
+        self.iter().any(|param| param.var_type.is_generic())
+    }
+}
+
 impl Deref for Params {
     type Target = Arc<[Param]>;
 
@@ -1,51 +1,70 @@
 mod array;
-mod array_repeat;
+pub(crate) mod array_repeat;
+pub(crate) mod assign_element;
 pub mod at;
-mod bin_op;
+pub mod bin_op;
 pub mod block;
-mod control_flow;
-mod destruct_tuple;
+pub(crate) mod control_flow;
+mod destruct;
+mod enum_declaration;
 mod field_access;
 pub mod function;
+mod r#impl;
 mod import;
 pub mod local_variable;
-mod r#loop;
+pub(crate) mod r#loop;
+pub(crate) mod map_literal;
+mod method;
 mod module;
 mod r#mut;
+pub(crate) mod pattern;
 mod prefix_op;
-mod reduce;
+pub mod range;
+pub(crate) mod reduce;
 mod r#return;
-mod set;
-mod slicing;
+pub(crate) mod set;
+pub(crate) mod set_literal;
+pub(crate) mod slicing;
 mod r#struct;
-mod tuple;
+mod throw;
+pub(crate) mod tuple;
 pub mod tuple_access;
-mod type_filter;
+mod type_declaration;
+pub(crate) mod type_filter;
 pub mod unary_operation;
+mod r#yield;
 use self::{
     array::Array,
     array_repeat::ArrayRepeat,
+    assign_element::AssignElement,
     bin_op::*,
     block::Block,
-    control_flow::{IfElse, Match, SetIfElse},
-    destruct_tuple::DestructTuple,
-    function::{AnonymousFunction, FunctionDeclaration},
-    local_variable::{LocalVariable, LocalVariables},
+    control_flow::{IfElse, Match, SetIfElse, TryCatch},
+    destruct::Destruct,
+    enum_declaration::EnumVariant,
+    function::{AnonymousFunction, FunctionDeclaration, Propagate},
+    local_variable::{LocalVariable, LocalVariables, Slot},
     set::Set,
     tuple::Tuple,
 };
 use crate::{
-    Error, ExecError,
+    Error, ExecError, Location, OptLevel,
     instruction::{field_access::FieldAccess, r#struct::Struct},
-    interpreter::Interpreter,
+    optimizer,
     variable::{ReturnType, Type, Typed, Variable},
+    vm::{Compiler, Op},
 };
 use derive_more::From;
+use r#impl::Impl;
 use r#loop::{Loop, r#for, r#while, while_set};
+use map_literal::Map;
 use match_any::match_any;
+use method::Method;
 use r#mut::Mut;
 use pest::iterators::Pair;
+use range::Range;
 use reduce::Reduce;
+use set_literal::SetLiteral;
 use simplesl_parser::{PRATT_PARSER, Rule, unexpected};
 use slicing::Slicing;
 use std::sync::Arc;
@@ -57,6 +76,7 @@ use unary_operation::UnaryOperation;
 pub struct InstructionWithStr {
     pub instruction: Instruction,
     pub str: Arc<str>,
+    pub location: Option<Location>,
 }
 
 impl InstructionWithStr {
@@ -65,8 +85,13 @@ impl InstructionWithStr {
             return Self::new_expression(pair, local_variables);
         }
         let str = pair.as_str().into();
+        let location = Some(local_variables.location(&pair));
         let instruction = Instruction::new(pair, local_variables)?;
-        Ok(Self { instruction, str })
+        Ok(Self {
+            instruction,
+            str,
+            location,
+        })
     }
 
     pub(crate) fn new_expression(
@@ -75,15 +100,21 @@ impl InstructionWithStr {
     ) -> Result<Self, Error> {
         PRATT_PARSER
             .map_primary(|pair| Self::create_primary(pair, local_variables))
-            .map_prefix(|op, rhs| Self::create_prefix(op, rhs?))
+            .map_prefix(|op, rhs| Self::create_prefix(op, rhs?, local_variables))
             .map_infix(|lhs, op, rhs| Self::create_infix(op, lhs?, rhs?, local_variables))
             .map_postfix(|lhs, op| Self::create_postfix(op, lhs?, local_variables))
             .parse(pair.into_inner())
     }
 
-    pub fn new_ident(str: Arc<str>, local_variables: &LocalVariables) -> Result<Self, Error> {
+    pub fn new_ident(pair: Pair<Rule>, local_variables: &LocalVariables) -> Result<Self, Error> {
+        let str: Arc<str> = pair.as_str().into();
+        let location = Some(local_variables.location(&pair));
         let instruction = Instruction::new_ident(&str, local_variables)?;
-        Ok(Self { instruction, str })
+        Ok(Self {
+            instruction,
+            str,
+            location,
+        })
     }
 
     fn create_primary(
@@ -95,6 +126,7 @@ impl InstructionWithStr {
             return Self::new_expression(pair, local_variables);
         }
         let str: Arc<str> = pair.as_str().into();
+        let location = Some(local_variables.location(&pair));
         let instruction = match rule {
             Rule::ident => Instruction::new_ident(&str, local_variables),
             Rule::r#true | Rule::r#false | Rule::int | Rule::float | Rule::string | Rule::void => {
@@ -107,15 +139,26 @@ impl InstructionWithStr {
             Rule::function => AnonymousFunction::create_instruction(pair, local_variables),
             Rule::r#struct => Struct::create_instruction(pair, local_variables),
             Rule::r#mod => module::create_instruction(pair, local_variables),
+            Rule::map_literal => Map::create_instruction(pair, local_variables),
+            Rule::set_literal => SetLiteral::create_instruction(pair, local_variables),
             rule => unexpected!(rule),
         }?;
-        Ok(Self { instruction, str })
+        Ok(Self {
+            instruction,
+            str,
+            location,
+        })
     }
 
     pub fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Self, ExecError> {
         let instruction = self.instruction.recreate(local_variables)?;
         let str = self.str.clone();
-        Ok(Self { instruction, str })
+        let location = self.location.clone();
+        Ok(Self {
+            instruction,
+            str,
+            location,
+        })
     }
 
     pub fn map<F>(self, f: F) -> Self
@@ -125,6 +168,7 @@ impl InstructionWithStr {
         Self {
             instruction: f(self.instruction),
             str: self.str,
+            location: self.location,
         }
     }
     pub fn try_map<F, E>(self, f: F) -> Result<Self, E>
@@ -135,16 +179,11 @@ impl InstructionWithStr {
         Ok(Self {
             instruction,
             str: self.str,
+            location: self.location,
         })
     }
 }
 
-impl Exec for InstructionWithStr {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        self.instruction.exec(interpreter)
-    }
-}
-
 impl ReturnType for InstructionWithStr {
     fn return_type(&self) -> Type {
         self.instruction.return_type()
@@ -157,6 +196,7 @@ impl From<Variable> for InstructionWithStr {
         Self {
             instruction: Instruction::from(value),
             str,
+            location: None,
         }
     }
 }
@@ -169,35 +209,53 @@ pub enum Instruction {
     Array(Arc<Array>),
     #[from(ArrayRepeat)]
     ArrayRepeat(Arc<ArrayRepeat>),
+    #[from(AssignElement)]
+    AssignElement(Arc<AssignElement>),
     #[from]
     Block(Block),
     Break,
     Continue,
-    #[from(DestructTuple)]
-    DestructTuple(Arc<DestructTuple>),
+    #[from(Destruct)]
+    Destruct(Arc<Destruct>),
+    #[from(EnumVariant)]
+    EnumVariant(Arc<EnumVariant>),
     #[from(FieldAccess)]
     FieldAccess(Arc<FieldAccess>),
     #[from(FunctionDeclaration)]
     FunctionDeclaration(Arc<FunctionDeclaration>),
     #[from(IfElse)]
     IfElse(Arc<IfElse>),
-    LocalVariable(Arc<str>, LocalVariable),
+    #[from(Impl)]
+    Impl(Arc<Impl>),
+    LocalVariable(Arc<str>, LocalVariable, Slot),
     #[from(Loop)]
     Loop(Arc<Loop>),
+    #[from(Map)]
+    Map(Arc<Map>),
     #[from(Match)]
     Match(Arc<Match>),
+    #[from(Method)]
+    Method(Arc<Method>),
     #[from(Mut)]
     Mut(Arc<Mut>),
+    #[from(Propagate)]
+    Propagate(Arc<Propagate>),
+    #[from(Range)]
+    Range(Arc<Range>),
     #[from(Reduce)]
     Reduce(Arc<Reduce>),
     #[from(Set)]
     Set(Arc<Set>),
     #[from(SetIfElse)]
     SetIfElse(Arc<SetIfElse>),
+    #[from(SetLiteral)]
+    SetLiteral(Arc<SetLiteral>),
     #[from(Slicing)]
     Slicing(Arc<Slicing>),
     #[from(Struct)]
     Struct(Arc<Struct>),
+    #[from(TryCatch)]
+    TryCatch(Arc<TryCatch>),
     #[from]
     Tuple(Tuple),
     #[from(TupleAccess)]
@@ -216,7 +274,8 @@ impl Instruction {
     pub fn new(pair: Pair<Rule>, local_variables: &mut LocalVariables) -> Result<Self, Error> {
         match pair.as_rule() {
             Rule::set => Set::create_instruction(pair, local_variables),
-            Rule::destruct_tuple => DestructTuple::create_instruction(pair, local_variables),
+            Rule::assign_element => AssignElement::create_instruction(pair, local_variables),
+            Rule::destruct => Destruct::create_instruction(pair, local_variables),
             Rule::block => Block::create_instruction(pair, local_variables),
             Rule::import => import::create_instruction(pair, local_variables),
             Rule::if_else => IfElse::create_instruction(pair, local_variables),
@@ -226,6 +285,12 @@ impl Instruction {
                 FunctionDeclaration::create_instruction(pair, local_variables)
             }
             Rule::r#return => r#return::create(pair, local_variables),
+            Rule::throw => throw::create(pair, local_variables),
+            Rule::r#yield => r#yield::create(pair, local_variables),
+            Rule::type_declaration => type_declaration::create(pair, local_variables),
+            Rule::enum_declaration => enum_declaration::create(pair, local_variables),
+            Rule::impl_block => Impl::create_instruction(pair, local_variables),
+            Rule::try_catch => TryCatch::create_instruction(pair, local_variables),
             Rule::expr => {
                 InstructionWithStr::new_expression(pair, local_variables).map(|iws| iws.instruction)
             }
@@ -245,7 +310,10 @@ impl Instruction {
         str: &Arc<str>,
         local_variables: &LocalVariables<'_>,
     ) -> Result<Instruction, Error> {
-        local_variables.get(str).map_or_else(
+        if let Some(function) = local_variables.inferred_function(str) {
+            function.mark_recursive();
+        }
+        local_variables.instruction(str).map_or_else(
             || {
                 local_variables
                     .interpreter
@@ -254,59 +322,25 @@ impl Instruction {
                     .map(Instruction::from)
                     .ok_or_else(|| Error::VariableDoesntExist(str.clone()))
             },
-            |var| match var.clone() {
-                LocalVariable::Variable(variable) => Ok(Instruction::Variable(variable)),
-                local_variable => Ok(Instruction::LocalVariable(str.clone(), local_variable)),
-            },
+            Ok,
         )
     }
 }
 
-impl Exec for Instruction {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        match_any! { self,
-            Self::Variable(var) => Ok(var.clone()),
-            Self::LocalVariable(ident, _) => interpreter
-                .get_variable(ident)
-                .cloned()
-                .ok_or_else(|| panic!("Tried to get variable {ident} that doest exist")),
-            Self::AnonymousFunction(ins) | Self::Array(ins) | Self::ArrayRepeat(ins)
-            | Self::Block(ins) | Self::DestructTuple(ins) | Self::Tuple(ins)
-            | Self::BinOperation(ins) | Self::FieldAccess(ins) | Self::FunctionDeclaration(ins)
-            | Self::IfElse(ins) | Self::Loop(ins) | Self::Match(ins) | Self::Mut(ins)
-            | Self::Reduce(ins) | Self::Set(ins) | Self::SetIfElse(ins) | Self::Slicing(ins)
-            | Self::Struct(ins) | Self::TypeFilter(ins) | Self::UnaryOperation(ins)
-            | Self::TupleAccess(ins) => ins.exec(interpreter),
-            Self::Break => Err(ExecStop::Break),
-            Self::Continue => Err(ExecStop::Continue)
-        }
-    }
-}
-
 impl Recreate for Instruction {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
         match_any! {self,
-            Self::LocalVariable(ident, _) => Ok(local_variables.get(ident).map_or_else(
-                || {
-                    local_variables.interpreter
-                        .get_variable(ident)
-                        .cloned()
-                        .map(Instruction::from)
-                        .unwrap_or_else(|| panic!("Tried to get variable {ident} that doest exist"))
-                },
-                |var| match var.clone() {
-                    LocalVariable::Variable(variable) => Self::Variable(variable),
-                    local_variable => Self::LocalVariable(ident.clone(), local_variable),
-                },
-            )),
+            Self::LocalVariable(ident, _, slot) => Ok(local_variables
+                .instruction(ident)
+                .unwrap_or_else(|| local_variables.interpreter.local(*slot).clone().into())),
             Self::Variable(variable) => Ok(Self::Variable(variable.clone())),
-            Self::AnonymousFunction(ins) | Self::Array(ins) | Self::ArrayRepeat(ins)
-            | Self::Block(ins) | Self::DestructTuple(ins) | Self::Tuple(ins)
+            Self::AnonymousFunction(ins) | Self::Array(ins) | Self::ArrayRepeat(ins) | Self::AssignElement(ins)
+            | Self::Block(ins) | Self::Destruct(ins) | Self::EnumVariant(ins) | Self::Tuple(ins)
             | Self::BinOperation(ins) | Self::FieldAccess(ins) | Self::FunctionDeclaration(ins)
-            | Self::IfElse(ins) | Self::Loop(ins) | Self::Match(ins) | Self::Mut(ins)
-            | Self::Reduce(ins) | Self::Set(ins) | Self::SetIfElse(ins) | Self::Slicing(ins)
-            | Self::Struct(ins) |  Self::TypeFilter(ins) | Self::UnaryOperation(ins)
-            | Self::TupleAccess(ins) => ins.recreate(local_variables),
+            | Self::IfElse(ins) | Self::Impl(ins) | Self::Loop(ins) | Self::Map(ins) | Self::Match(ins) | Self::Method(ins) | Self::Mut(ins)
+            | Self::Propagate(ins) | Self::Range(ins) | Self::Reduce(ins) | Self::Set(ins) | Self::SetIfElse(ins) | Self::SetLiteral(ins) | Self::Slicing(ins)
+            | Self::Struct(ins) | Self::TryCatch(ins) | Self::TypeFilter(ins)
+            | Self::UnaryOperation(ins) | Self::TupleAccess(ins) => ins.recreate(local_variables),
             _ => Ok(self.clone())
         }
     }
@@ -315,13 +349,13 @@ impl Recreate for Instruction {
 impl ReturnType for Instruction {
     fn return_type(&self) -> Type {
         match_any! { self,
-            Self::Variable(variable) | Self::LocalVariable(_, variable) => variable.as_type(),
-            Self::AnonymousFunction(ins) | Self::Array(ins) | Self::ArrayRepeat(ins)
-            | Self::Block(ins) | Self::DestructTuple(ins) | Self::Tuple(ins)
+            Self::Variable(variable) | Self::LocalVariable(_, variable, _) => variable.as_type(),
+            Self::AnonymousFunction(ins) | Self::Array(ins) | Self::ArrayRepeat(ins) | Self::AssignElement(ins)
+            | Self::Block(ins) | Self::Destruct(ins) | Self::EnumVariant(ins) | Self::Tuple(ins)
             | Self::BinOperation(ins) | Self::FieldAccess(ins) | Self::FunctionDeclaration(ins)
-            | Self::IfElse(ins) | Self::Match(ins) | Self::Mut(ins) | Self::Reduce(ins)
-            | Self::Set(ins) | Self::SetIfElse(ins) | Self::Slicing(ins) | Self::Struct(ins)
-            | Self::TypeFilter(ins) | Self::UnaryOperation(ins) | Self::TupleAccess(ins)
+            | Self::IfElse(ins) | Self::Impl(ins) | Self::Map(ins) | Self::Match(ins) | Self::Method(ins) | Self::Mut(ins) | Self::Propagate(ins) | Self::Range(ins) | Self::Reduce(ins)
+            | Self::Set(ins) | Self::SetIfElse(ins) | Self::SetLiteral(ins) | Self::Slicing(ins) | Self::Struct(ins)
+            | Self::TryCatch(ins) | Self::TypeFilter(ins) | Self::UnaryOperation(ins) | Self::TupleAccess(ins)
                 => ins.return_type(),
             Self::Loop(_) => Type::Void,
             Self::Break | Self::Continue => Type::Never
@@ -329,6 +363,32 @@ impl ReturnType for Instruction {
     }
 }
 
+impl Compile for Instruction {
+    fn compile(&self, compiler: &mut Compiler) {
+        match_any! { self,
+            Self::Variable(variable) => {
+                compiler.emit(Op::Constant(variable.clone()));
+            },
+            Self::LocalVariable(_, _, slot) => {
+                compiler.emit(Op::Load(*slot));
+            },
+            Self::AnonymousFunction(ins) | Self::Array(ins) | Self::ArrayRepeat(ins) | Self::AssignElement(ins)
+            | Self::Block(ins) | Self::Destruct(ins) | Self::EnumVariant(ins) | Self::Tuple(ins)
+            | Self::BinOperation(ins) | Self::FieldAccess(ins) | Self::FunctionDeclaration(ins)
+            | Self::IfElse(ins) | Self::Impl(ins) | Self::Loop(ins) | Self::Map(ins) | Self::Match(ins) | Self::Method(ins) | Self::Mut(ins)
+            | Self::Propagate(ins) | Self::Range(ins) | Self::Reduce(ins) | Self::Set(ins) | Self::SetIfElse(ins) | Self::SetLiteral(ins) | Self::Slicing(ins)
+            | Self::Struct(ins) | Self::TryCatch(ins) | Self::TypeFilter(ins)
+            | Self::UnaryOperation(ins) | Self::TupleAccess(ins) => ins.compile(compiler),
+            Self::Break => {
+                compiler.emit(Op::Break);
+            },
+            Self::Continue => {
+                compiler.emit(Op::Continue);
+            }
+        }
+    }
+}
+
 pub(crate) fn recreate_instructions(
     instructions: &[InstructionWithStr],
     local_variables: &mut LocalVariables,
@@ -339,12 +399,32 @@ pub(crate) fn recreate_instructions(
         .collect()
 }
 
+/// Recreates instructions executed one after another.
+/// Instructions unreachable after one that always stops execution are dropped when optimizing
+pub(crate) fn recreate_sequence(
+    instructions: &[InstructionWithStr],
+    local_variables: &mut LocalVariables,
+) -> Result<Arc<[InstructionWithStr]>, ExecError> {
+    let eliminate = local_variables.opt_level >= OptLevel::Basic;
+    let mut recreated = Vec::with_capacity(instructions.len());
+    for instruction in instructions {
+        let instruction = instruction.recreate(local_variables)?;
+        let stops = eliminate && optimizer::always_stops(&instruction.instruction);
+        recreated.push(instruction);
+        if stops {
+            break;
+        }
+    }
+    Ok(recreated.into())
+}
+
 pub trait Recreate {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError>;
 }
 
-pub trait Exec {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult;
+/// Instructions that can be executed by the vm without walking their tree
+pub(crate) trait Compile {
+    fn compile(&self, compiler: &mut Compiler);
 }
 
 pub type ExecResult = Result<Variable, ExecStop>;
@@ -352,6 +432,10 @@ pub enum ExecStop {
     Break,
     Continue,
     Return(Variable),
+    /// Args of recursive call in tail position, executed after the frame of the function is released
+    TailCall(Vec<Variable>),
+    /// Value yielded by generator, its execution is resumed when the next element is requested
+    Yield(Variable),
     Error(ExecError),
 }
 
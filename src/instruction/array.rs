@@ -1,11 +1,11 @@
 use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
     recreate_instructions,
 };
 use crate::{
     self as simplesl, Error, ExecError,
-    interpreter::Interpreter,
     variable::{ReturnType, Type},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_macros::var_type;
@@ -40,10 +40,12 @@ impl Array {
     }
 }
 
-impl Exec for Array {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let elements = interpreter.exec(&self.instructions)?;
-        Ok(elements.into())
+impl Compile for Array {
+    fn compile(&self, compiler: &mut Compiler) {
+        for instruction in self.instructions.iter() {
+            compiler.compile(instruction);
+        }
+        compiler.emit(Op::Array(self.instructions.len()));
     }
 }
 
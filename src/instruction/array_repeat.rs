@@ -1,10 +1,8 @@
-use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
-};
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
 use crate::{
     self as simplesl, Error, ExecError,
-    interpreter::Interpreter,
     variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_macros::{var, var_type};
@@ -57,15 +55,21 @@ impl ArrayRepeat {
     }
 }
 
-impl Exec for ArrayRepeat {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let value = self.value.exec(interpreter)?;
-        let len = self.len.exec(interpreter)?.into_int().unwrap();
-        if len < 0 {
-            return Err(ExecError::NegativeLength.into());
-        }
-        Ok(var!([value; len]))
+impl Compile for ArrayRepeat {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.value);
+        compiler.compile(&self.len);
+        compiler.emit(Op::ArrayRepeat);
+    }
+}
+
+/// Returns array of value repeated len times
+pub(crate) fn exec(value: Variable, len: Variable) -> Result<Variable, ExecError> {
+    let len = len.into_int().unwrap();
+    if len < 0 {
+        return Err(ExecError::NegativeLength);
     }
+    Ok(var!([value; len]))
 }
 
 impl Recreate for ArrayRepeat {
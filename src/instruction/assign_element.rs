@@ -0,0 +1,434 @@
+use super::{
+    Compile, Instruction, InstructionWithStr, Recreate,
+    at::absolute_index,
+    bin_op::{self, assign},
+    local_variable::LocalVariables,
+};
+use crate::{
+    BinOperator, Error, ExecError,
+    variable::{ReturnType, Type, Typed, Variable},
+    vm::{Compiler, Op},
+};
+use pest::iterators::Pair;
+use simplesl_parser::{Rule, unexpected};
+use std::sync::Arc;
+
+/// Assignment to element of array, map, tuple or field of struct stored inside mut
+#[derive(Debug)]
+pub struct AssignElement {
+    target: InstructionWithStr,
+    path: Arc<[Accessor]>,
+    op: BinOperator,
+    value: InstructionWithStr,
+    element_type: Type,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Accessor {
+    Index(InstructionWithStr),
+    Key(InstructionWithStr),
+    Field(Arc<str>),
+    Tuple(usize),
+}
+
+enum Step {
+    Index(i64),
+    Key(Variable),
+    Field(Arc<str>),
+    Tuple(usize),
+}
+
+impl AssignElement {
+    pub fn create_instruction(
+        pair: Pair<Rule>,
+        local_variables: &LocalVariables,
+    ) -> Result<Instruction, Error> {
+        let mut pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+        let value = pairs.pop().unwrap();
+        let op = pairs.pop().unwrap();
+        let mut pairs = pairs.into_iter();
+        let mut target = InstructionWithStr::new_ident(pairs.next().unwrap(), local_variables)?;
+        let mut accessors = pairs.by_ref().peekable();
+        // elements of values that aren't mut are accessed as usual
+        while accessors.peek().is_some() && !target.return_type().is_mut() {
+            target = InstructionWithStr::create_postfix(
+                accessors.next().unwrap(),
+                target,
+                local_variables,
+            )?;
+        }
+        let accessors: Box<[Pair<Rule>]> = accessors.collect();
+        let value = InstructionWithStr::new_expression(value, local_variables)?;
+        if accessors.is_empty() {
+            return InstructionWithStr::create_infix(op, target, value, local_variables)
+                .map(|iws| iws.instruction);
+        }
+        let op = BinOperator::from(op.as_rule());
+        let target_type = target.return_type();
+        let mut element_type = match &target_type {
+            Type::Mut(element_type) => element_type.as_ref().clone(),
+            target_type => target_type.clone(),
+        };
+        let mut element_str = target.str.to_string();
+        let mut path = Vec::new();
+        for pair in accessors {
+            let str = pair.as_str();
+            let (accessor, accessor_type) =
+                Accessor::create(pair, &element_type, &element_str, local_variables)?;
+            path.push(accessor);
+            element_type = accessor_type;
+            element_str.push_str(str);
+        }
+        let value_type = value.return_type();
+        if !bin_op::can_be_used(&Type::Mut(element_type.clone().into()), &value_type, op) {
+            return Err(Error::CannotDo2(element_type, op, value_type));
+        }
+        Ok(Self {
+            target,
+            path: path.into(),
+            op,
+            value,
+            element_type,
+        }
+        .into())
+    }
+}
+
+impl Accessor {
+    /// Returns accessor and type of element it accesses in value of type container
+    fn create(
+        pair: Pair<Rule>,
+        container: &Type,
+        container_str: &str,
+        local_variables: &LocalVariables,
+    ) -> Result<(Self, Type), Error> {
+        match pair.as_rule() {
+            Rule::at => {
+                let pair = pair.into_inner().next().unwrap();
+                let index = InstructionWithStr::new_expression(pair, local_variables)?;
+                let index_type = index.return_type();
+                match container {
+                    Type::Array(element_type) if index_type == Type::Int => {
+                        Ok((Self::Index(index), element_type.as_ref().clone()))
+                    }
+                    Type::Map(map_type) if index_type.matches(&map_type.key) => {
+                        Ok((Self::Key(index), map_type.value.clone()))
+                    }
+                    Type::Array(_) | Type::Map(_) => Err(Error::CannotIndexWith(index.str)),
+                    container => Err(Error::CannotIndexInto(container.clone())),
+                }
+            }
+            Rule::field_access => {
+                let ident = pair.into_inner().next().unwrap().as_str();
+                let Type::Struct(struct_type) = container else {
+                    return Err(Error::CannotFieldAccess(
+                        container_str.into(),
+                        container.clone(),
+                    ));
+                };
+                let field_type = struct_type.0.get(ident).ok_or_else(|| Error::NoField {
+                    struct_ident: container_str.into(),
+                    field_ident: ident.into(),
+                    struct_type: container.clone(),
+                })?;
+                Ok((Self::Field(ident.into()), field_type.clone()))
+            }
+            Rule::tuple_access => {
+                let pair = pair.into_inner().next().unwrap();
+                let index = Variable::try_from(pair)?.into_int().unwrap() as usize;
+                let Type::Tuple(types) = container else {
+                    return Err(Error::CannotTupleAccess(
+                        container_str.into(),
+                        container.clone(),
+                    ));
+                };
+                let element_type = types.get(index).ok_or_else(|| {
+                    Error::TupleIndexTooBig(index, container_str.into(), types.len())
+                })?;
+                Ok((Self::Tuple(index), element_type.clone()))
+            }
+            rule => unexpected!(rule),
+        }
+    }
+
+    /// Returns step of path, keys and indexes are taken from keys
+    fn step(&self, keys: &mut impl Iterator<Item = Variable>) -> Step {
+        match self {
+            Self::Index(_) => Step::Index(keys.next().unwrap().into_int().unwrap()),
+            Self::Key(_) => Step::Key(keys.next().unwrap()),
+            Self::Field(ident) => Step::Field(ident.clone()),
+            Self::Tuple(index) => Step::Tuple(*index),
+        }
+    }
+
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Self, ExecError> {
+        match self {
+            Self::Index(index) => Ok(Self::Index(index.recreate(local_variables)?)),
+            Self::Key(key) => Ok(Self::Key(key.recreate(local_variables)?)),
+            accessor => Ok(accessor.clone()),
+        }
+    }
+}
+
+impl Compile for AssignElement {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.target);
+        for accessor in self.path.iter() {
+            if let Accessor::Index(key) | Accessor::Key(key) = accessor {
+                compiler.compile(key);
+            }
+        }
+        compiler.compile(&self.value);
+        compiler.emit(Op::AssignElement {
+            path: self.path.clone(),
+            op: self.op,
+        });
+    }
+}
+
+/// Returns number of values taken by assignment to element at path: mut, keys and value
+pub(crate) fn operands(path: &[Accessor]) -> usize {
+    let keys = path
+        .iter()
+        .filter(|accessor| matches!(accessor, Accessor::Index(_) | Accessor::Key(_)))
+        .count();
+    keys + 2
+}
+
+/// Assigns value to element at path of mut and returns new value of the element
+pub(crate) fn exec(
+    operands: Vec<Variable>,
+    path: &[Accessor],
+    op: BinOperator,
+) -> Result<Variable, ExecError> {
+    let mut operands = operands.into_iter();
+    let target = operands.next().unwrap().into_mut().unwrap();
+    let rhs = operands.next_back().unwrap();
+    let path: Box<[Step]> = path
+        .iter()
+        .map(|accessor| accessor.step(&mut operands))
+        .collect();
+    let mut variable = target.variable.write().unwrap();
+    let insert = op == BinOperator::Assign;
+    update(&mut variable, &path, insert, |value| {
+        assign::new_value(op, value, rhs)
+    })
+}
+
+/// Replaces element at path with value returned by function and returns it.
+/// Containers shared with other variables are cloned before being modified.
+/// Missing key of map is an error unless it is the last step and insert is true
+fn update(
+    variable: &mut Variable,
+    path: &[Step],
+    insert: bool,
+    function: impl FnOnce(Variable) -> Result<Variable, ExecError>,
+) -> Result<Variable, ExecError> {
+    let Some((key, path)) = path.split_first() else {
+        *variable = function(variable.clone())?;
+        return Ok(variable.clone());
+    };
+    match (variable, key) {
+        (Variable::Array(array), Step::Index(index)) => {
+            let index = absolute_index(*index, array.len())?;
+            if index >= array.len() {
+                return Err(ExecError::IndexOutOfBounds);
+            }
+            let array = Arc::make_mut(array);
+            let element = &mut Arc::make_mut(&mut array.elements)[index];
+            let result = update(element, path, insert, function)?;
+            let element_type = element.as_type();
+            array.element_type |= element_type;
+            Ok(result)
+        }
+        (Variable::Map(map), Step::Key(key)) => {
+            let map = Arc::make_mut(map);
+            if map.get(key).is_none() {
+                if !insert || !path.is_empty() {
+                    return Err(ExecError::MissingKey(key.clone()));
+                }
+                map.insert(key.clone(), Variable::Void);
+            }
+            let value = map.get_mut(key).unwrap();
+            let result = update(value, path, insert, function)?;
+            let value_type = value.as_type();
+            map.value_type |= value_type;
+            Ok(result)
+        }
+        (Variable::Struct(fields), Step::Field(ident)) => update(
+            Arc::make_mut(fields).get_mut(ident).unwrap(),
+            path,
+            insert,
+            function,
+        ),
+        (Variable::Tuple(elements), Step::Tuple(index)) => {
+            update(&mut Arc::make_mut(elements)[*index], path, insert, function)
+        }
+        (variable, _) => unreachable!("Tried to assign to element of {}", variable.as_type()),
+    }
+}
+
+impl Recreate for AssignElement {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let target = self.target.recreate(local_variables)?;
+        let path = self
+            .path
+            .iter()
+            .map(|accessor| accessor.recreate(local_variables))
+            .collect::<Result<_, _>>()?;
+        let value = self.value.recreate(local_variables)?;
+        Ok(Self {
+            target,
+            path,
+            op: self.op,
+            value,
+            element_type: self.element_type.clone(),
+        }
+        .into())
+    }
+}
+
+impl ReturnType for AssignElement {
+    fn return_type(&self) -> Type {
+        if self.op == BinOperator::Assign {
+            return self.value.return_type();
+        }
+        self.element_type.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, BinOperator, Code, Error, ExecError, Interpreter, variable::Variable,
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn assign_array_element() {
+        assert_eq!(
+            parse_and_exec("a := mut [1, 2, 3]; a[1] = 5; *a"),
+            Ok(var!([1, 5, 3]))
+        );
+        assert_eq!(
+            parse_and_exec("a := mut [1, 2, 3]; a[-1] += 5; a[0] *= 4; *a"),
+            Ok(var!([4, 2, 8]))
+        );
+        assert_eq!(
+            parse_and_exec("a := mut [1, 2, 3]; b := *a; a[0] = 7; (*a, b)"),
+            Ok(var!(([7, 2, 3], [1, 2, 3])))
+        );
+        assert_eq!(
+            parse_and_exec("a := mut [[1, 2], [3]]; a[1][0] -= 1; *a"),
+            parse_and_exec("[[1, 2], [2]]")
+        );
+        assert_eq!(
+            parse_and_exec("a := mut [int | float] [1, 2]; a[0] = 0.5; *a"),
+            Ok(var!([0.5, 2]))
+        );
+        assert_eq!(parse_and_exec("a := mut [1, 2]; a[1] += 3"), Ok(var!(5)));
+        assert_eq!(
+            parse_and_exec("a := mut [1, 2]; a[2] = 3"),
+            Err(Error::from(ExecError::IndexOutOfBounds))
+        );
+        assert_eq!(
+            parse_and_exec("a := mut [1, 2]; a[0] = 0.5"),
+            Err(Error::CannotDo2(
+                var_type!(int),
+                BinOperator::Assign,
+                var_type!(float)
+            ))
+        );
+        assert_eq!(
+            parse_and_exec("a := mut [1, 2]; a[0.5] = 1"),
+            Err(Error::CannotIndexWith("0.5".into()))
+        );
+        assert_eq!(
+            parse_and_exec("a := [1, 2]; a[0] = 1"),
+            Err(Error::NotMut("a [0]".into()))
+        );
+    }
+
+    #[test]
+    fn assign_field() {
+        assert_eq!(
+            parse_and_exec("s := mut struct{a := 1, b := \"x\"}; s.a += 2; s.b = \"y\"; *s"),
+            Ok(var!(struct{a := 3, b := "y"}))
+        );
+        assert_eq!(
+            parse_and_exec(
+                r#"s := mut struct{items := [struct{name := "a"}, struct{name := "b"}]};
+                s.items[1].name = "c";
+                *s"#
+            ),
+            parse_and_exec(r#"struct{items := [struct{name := "a"}, struct{name := "c"}]}"#)
+        );
+        assert_eq!(
+            parse_and_exec("t := mut (1, (2, 3)); t.1.0 = 5; *t"),
+            Ok(var!((1, (5, 3))))
+        );
+        assert_eq!(
+            parse_and_exec("s := struct{a := mut 1}; s.a = 2; *s.a"),
+            Ok(var!(2))
+        );
+        assert_eq!(
+            parse_and_exec("s := struct{x := mut [1, 2]}; s.x[0] = 3; *s.x"),
+            Ok(var!([3, 2]))
+        );
+        assert_eq!(
+            parse_and_exec("s := mut struct{a := 1}; s.b = 2"),
+            Err(Error::NoField {
+                struct_ident: "s".into(),
+                field_ident: "b".into(),
+                struct_type: var_type!(struct{a: int})
+            })
+        );
+    }
+
+    #[test]
+    fn assign_map_element() {
+        assert_eq!(
+            parse_and_exec(
+                r#"counts := mut {string: int} {};
+                for word in ["a", "b", "a"]~ {
+                    if count: int = (*counts)[word] {
+                        counts[word] = count + 1
+                    } else {
+                        counts[word] = 1
+                    }
+                }
+                *counts"#
+            ),
+            parse_and_exec(r#"{"a": 2, "b": 1}"#)
+        );
+        assert_eq!(
+            parse_and_exec(r#"m := mut {string: int} {"a": 1}; m["a"] += 10; *m"#),
+            parse_and_exec(r#"{"a": 11}"#)
+        );
+        assert_eq!(
+            parse_and_exec(r#"m := mut {"a": 1}; m["b"] += 1"#),
+            Err(Error::MissingKey(var!("b")))
+        );
+        assert_eq!(
+            parse_and_exec(r#"m := mut {"a": [1, 2]}; m["a"][1] = 5; *m"#),
+            parse_and_exec(r#"{"a": [1, 5]}"#)
+        );
+        assert_eq!(
+            parse_and_exec(r#"m := mut {"a": [1, 2]}; m["b"][0] = 5"#),
+            Err(Error::MissingKey(var!("b")))
+        );
+        assert_eq!(
+            parse_and_exec(r#"m := mut {"a": 1}; m["a"] += "b""#),
+            Err(Error::CannotDo2(
+                var_type!(int),
+                BinOperator::AssignAdd,
+                var_type!(string)
+            ))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
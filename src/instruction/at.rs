@@ -1,12 +1,20 @@
 use super::{BinOperation, Instruction, InstructionWithStr, local_variable::LocalVariables};
 use crate::{
-    BinOperator, Error, ExecError,
+    self as simplesl, BinOperator, Error, ExecError,
+    function::Function,
+    interpreter::CallDepth,
     stdlib::len,
     variable::{ReturnType, Type, Typed, Variable},
 };
+use lazy_static::lazy_static;
 use pest::iterators::Pair;
+use simplesl_macros::var_type;
 use simplesl_parser::Rule;
-use std::ops::Range;
+use std::{ops::Range, sync::Arc};
+
+lazy_static! {
+    static ref INDEX_ITER_TYPE: Type = var_type!(() -> (bool, int));
+}
 
 pub fn create(
     instruction: InstructionWithStr,
@@ -16,7 +24,22 @@ pub fn create(
     let pair = index.into_inner().next().unwrap();
     let index = InstructionWithStr::new_expression(pair, local_variables)?;
     let instruction_return_type = instruction.return_type();
-    if index.return_type() != Type::Int {
+    let index_type = index.return_type();
+    if let Some(key_type) = instruction_return_type
+        .key_type()
+        .or_else(|| instruction_return_type.set_element_type())
+    {
+        if !index_type.matches(&key_type) {
+            return Err(Error::CannotIndexWith(index.str));
+        }
+        return Ok(BinOperation {
+            lhs: instruction.instruction,
+            rhs: index.instruction,
+            op: BinOperator::At,
+        }
+        .into());
+    }
+    if index_type != Type::Int && !index_type.matches(&INDEX_ITER_TYPE) {
         return Err(Error::CannotIndexWith(index.str));
     }
     if !instruction_return_type.can_be_indexed() {
@@ -35,8 +58,10 @@ pub fn create_from_instructions(
     index: Instruction,
 ) -> Result<Instruction, ExecError> {
     match (instruction, index) {
-        (Instruction::Variable(variable), Instruction::Variable(index)) => {
-            Ok(exec(variable, index)?.into())
+        (Instruction::Variable(variable), Instruction::Variable(index))
+            if !matches!(index, Variable::Function(_)) =>
+        {
+            Ok(exec(variable, index, CallDepth::default())?.into())
         }
         (Instruction::Array(array), Instruction::Variable(Variable::Int(value)))
             if !range(array.instructions.len()).contains(&value) =>
@@ -57,17 +82,35 @@ fn range(value: usize) -> Range<i64> {
     -value..value
 }
 
-pub fn exec(variable: Variable, index: Variable) -> Result<Variable, ExecError> {
-    let index = index.into_int().unwrap();
-    let index = if index >= 0 {
-        index as usize
-    } else {
-        let index = len(&variable) as i64 + index;
-        if index < 0 {
-            return Err(ExecError::IndexOutOfBounds);
-        }
-        index as usize
-    };
+/// Returns type of indexing into value of type lhs with value of type index
+pub fn return_type(lhs: Type, index: &Type) -> Type {
+    if let Some(value_type) = lhs.value_type() {
+        return value_type | Type::Void;
+    }
+    if lhs.set_element_type().is_some() {
+        return Type::Bool;
+    }
+    if index.matches(&INDEX_ITER_TYPE) {
+        return lhs;
+    }
+    lhs.index_result().unwrap()
+}
+
+pub fn exec(
+    variable: Variable,
+    index: Variable,
+    call_depth: CallDepth,
+) -> Result<Variable, ExecError> {
+    if let Variable::Map(map) = variable {
+        return Ok(map.get(&index).cloned().unwrap_or(Variable::Void));
+    }
+    if let Variable::Set(set) = variable {
+        return Ok(set.contains(&index).into());
+    }
+    if let Variable::Function(indexes) = index {
+        return exec_iter(variable, indexes, call_depth);
+    }
+    let index = absolute_index(index.into_int().unwrap(), len(&variable))?;
     match variable {
         Variable::String(string) => string
             .chars()
@@ -79,12 +122,59 @@ pub fn exec(variable: Variable, index: Variable) -> Result<Variable, ExecError>
     }
 }
 
+/// Returns elements at indexes returned by iterator
+fn exec_iter(
+    variable: Variable,
+    indexes: Arc<Function>,
+    call_depth: CallDepth,
+) -> Result<Variable, ExecError> {
+    let len = len(&variable);
+    let indexes = indexes
+        .iter(call_depth)
+        .map(|index| absolute_index(index?.into_int().unwrap(), len));
+    match variable {
+        Variable::String(string) => {
+            let chars: Box<[char]> = string.chars().collect();
+            indexes
+                .map(|index| chars.get(index?).ok_or(ExecError::IndexOutOfBounds))
+                .collect::<Result<String, ExecError>>()
+                .map(Variable::from)
+        }
+        Variable::Array(array) => indexes
+            .map(|index| {
+                array
+                    .get(index?)
+                    .ok_or(ExecError::IndexOutOfBounds)
+                    .cloned()
+            })
+            .collect::<Result<Arc<[Variable]>, ExecError>>()
+            .map(Variable::from),
+        variable => unreachable!("Tried to index into {}", variable.as_type()),
+    }
+}
+
+/// Converts negative index into index counted from the start
+pub(crate) fn absolute_index(index: i64, len: usize) -> Result<usize, ExecError> {
+    if index >= 0 {
+        return Ok(index as usize);
+    }
+    let index = len as i64 + index;
+    if index < 0 {
+        return Err(ExecError::IndexOutOfBounds);
+    }
+    Ok(index as usize)
+}
+
 #[cfg(test)]
 mod tests {
     use crate as simplesl;
-    use crate::{ExecError, instruction::at::exec as at};
+    use crate::{ExecError, instruction::at::exec, interpreter::CallDepth, variable::Variable};
     use simplesl_macros::var;
 
+    fn at(variable: Variable, index: Variable) -> Result<Variable, ExecError> {
+        exec(variable, index, CallDepth::default())
+    }
+
     #[test]
     fn check_at() {
         let array = var!([4, 5.5, "var"]);
@@ -1,24 +1,28 @@
-mod assign;
+pub mod assign;
 mod bitwise;
 mod filter;
 mod logic;
 mod map;
 mod math;
+pub mod overload;
 mod partition;
 mod shift;
 use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, at, function::call,
-    local_variable::LocalVariables, reduce::Reduce,
+    Compile, ExecResult, Instruction, InstructionWithStr, Recreate, at, function::call,
+    local_variable::LocalVariables, range::Range, reduce::Reduce, tuple::Tuple,
 };
 use crate::{
-    self as simplesl, BinOperator, Error, ExecError, Interpreter,
+    self as simplesl, BinOperator, Error, ExecError, OptLevel,
+    interpreter::CallDepth,
+    optimizer,
     variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 pub use bitwise::{bitwise_and, bitwise_or, xor};
 use lazy_static::lazy_static;
 pub use logic::{and, or};
 pub use math::{add, multiply, pow, subtract};
-use math::{divide, greater, greater_equal, lower, lower_equal, modulo};
+use math::{can_be_compared, divide, greater, greater_equal, lower, lower_equal, modulo};
 use pest::iterators::Pair;
 use shift::{lshift, rshift};
 use simplesl_macros::var_type;
@@ -47,53 +51,116 @@ pub struct BinOperation {
     pub op: BinOperator,
 }
 
-impl Exec for BinOperation {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let lhs = self.lhs.exec(interpreter)?;
-        if let BinOperator::And = self.op {
-            return and::exec(lhs, &self.rhs, interpreter);
+/// Applies op to values of operands. Short-circuiting && and || aren't handled
+pub fn exec(lhs: Variable, rhs: Variable, op: BinOperator, call_depth: CallDepth) -> ExecResult {
+    Ok(match op {
+        BinOperator::Add => add::exec(lhs, rhs),
+        BinOperator::Subtract => subtract::exec(lhs, rhs),
+        BinOperator::Multiply => multiply::exec(lhs, rhs),
+        BinOperator::Divide => divide::exec(lhs, rhs)?,
+        BinOperator::Modulo => modulo::exec(lhs, rhs)?,
+        BinOperator::Pow => pow::exec(lhs, rhs)?,
+        BinOperator::Equal => equal::exec(lhs, rhs),
+        BinOperator::NotEqual => not_equal::exec(lhs, rhs),
+        BinOperator::Greater => greater::exec(lhs, rhs),
+        BinOperator::GreaterOrEqual => greater_equal::exec(lhs, rhs),
+        BinOperator::Lower => lower::exec(lhs, rhs),
+        BinOperator::LowerOrEqual => lower_equal::exec(lhs, rhs),
+        BinOperator::BitwiseAnd => bitwise_and::exec(lhs, rhs),
+        BinOperator::BitwiseOr => bitwise_or::exec(lhs, rhs),
+        BinOperator::Xor => xor::exec(lhs, rhs),
+        BinOperator::LShift => lshift::exec(lhs, rhs)?,
+        BinOperator::RShift => rshift::exec(lhs, rhs)?,
+        BinOperator::Filter => filter::exec(lhs, rhs, call_depth)?,
+        BinOperator::Map => map::exec(lhs, rhs, call_depth)?,
+        BinOperator::At => at::exec(lhs, rhs, call_depth)?,
+        BinOperator::FunctionCall => call::exec(lhs, rhs, call_depth)?,
+        BinOperator::Partition => partition::exec(lhs, rhs, call_depth)?,
+        BinOperator::And | BinOperator::Or | BinOperator::Range | BinOperator::RangeInclusive => {
+            unreachable!()
         }
-        if let BinOperator::Or = self.op {
-            return or::exec(lhs, &self.rhs, interpreter);
+        op => assign::exec(lhs, rhs, op)?,
+    })
+}
+
+impl BinOperation {
+    pub fn create_instruction(
+        lhs: InstructionWithStr,
+        op: BinOperator,
+        rhs: InstructionWithStr,
+        local_variables: &LocalVariables<'_>,
+    ) -> Result<Instruction, Error> {
+        if let BinOperator::Range | BinOperator::RangeInclusive = op {
+            return Range::create_instruction(lhs, op, rhs);
+        }
+        let lhs_type = lhs.return_type();
+        let rhs_type = rhs.return_type();
+        let builtin = can_be_used(&lhs_type, &rhs_type, op);
+        // builtin equality accepts all types so it is overridden by __eq
+        if (!builtin || matches!(op, BinOperator::Equal | BinOperator::NotEqual))
+            && let Some(instruction) = overload::create(&lhs, &rhs, op, local_variables)?
+        {
+            return Ok(instruction);
+        }
+        if op.is_assign() && lhs_type.mut_element_type().is_none() {
+            return Err(Error::NotMut(lhs.str));
+        }
+        if !builtin {
+            return Err(Error::CannotDo2(lhs_type, op, rhs_type));
+        }
+        let (lhs, rhs) = (lhs.instruction, rhs.instruction);
+        Ok(BinOperation { lhs, rhs, op }.into())
+    }
+
+    /// Compiles function call with args known at parse time finished with op created from their number.
+    /// Returns false if the operation isn't such call
+    pub(crate) fn compile_call(&self, compiler: &mut Compiler, call: fn(usize) -> Op) -> bool {
+        if self.op != BinOperator::FunctionCall {
+            return false;
+        }
+        let len = match &self.rhs {
+            Instruction::Tuple(Tuple { elements }) => {
+                self.lhs.compile(compiler);
+                for arg in elements.iter() {
+                    compiler.compile(arg);
+                }
+                elements.len()
+            }
+            Instruction::Variable(Variable::Tuple(args)) => {
+                self.lhs.compile(compiler);
+                for arg in args.iter() {
+                    compiler.emit(Op::Constant(arg.clone()));
+                }
+                args.len()
+            }
+            _ => return false,
+        };
+        compiler.emit(call(len));
+        true
+    }
+}
+
+impl Compile for BinOperation {
+    fn compile(&self, compiler: &mut Compiler) {
+        if self.compile_call(compiler, Op::Call) {
+            return;
+        }
+        self.lhs.compile(compiler);
+        match (self.op, &self.rhs) {
+            (BinOperator::And | BinOperator::Or, rhs) => {
+                let jump = if self.op == BinOperator::And {
+                    compiler.emit(Op::And(0))
+                } else {
+                    compiler.emit(Op::Or(0))
+                };
+                rhs.compile(compiler);
+                compiler.patch(jump);
+            }
+            (op, rhs) => {
+                rhs.compile(compiler);
+                compiler.emit(Op::Binary(op));
+            }
         }
-        let rhs = self.rhs.exec(interpreter)?;
-        Ok(match self.op {
-            BinOperator::Add => add::exec(lhs, rhs),
-            BinOperator::Subtract => subtract::exec(lhs, rhs),
-            BinOperator::Multiply => multiply::exec(lhs, rhs),
-            BinOperator::Divide => divide::exec(lhs, rhs)?,
-            BinOperator::Modulo => modulo::exec(lhs, rhs)?,
-            BinOperator::Pow => pow::exec(lhs, rhs)?,
-            BinOperator::Equal => equal::exec(lhs, rhs),
-            BinOperator::NotEqual => not_equal::exec(lhs, rhs),
-            BinOperator::Greater => greater::exec(lhs, rhs),
-            BinOperator::GreaterOrEqual => greater_equal::exec(lhs, rhs),
-            BinOperator::Lower => lower::exec(lhs, rhs),
-            BinOperator::LowerOrEqual => lower_equal::exec(lhs, rhs),
-            BinOperator::BitwiseAnd => bitwise_and::exec(lhs, rhs),
-            BinOperator::BitwiseOr => bitwise_or::exec(lhs, rhs),
-            BinOperator::Xor => xor::exec(lhs, rhs),
-            BinOperator::LShift => lshift::exec(lhs, rhs)?,
-            BinOperator::RShift => rshift::exec(lhs, rhs)?,
-            BinOperator::Filter => filter::exec(lhs, rhs)?,
-            BinOperator::Map => map::exec(lhs, rhs)?,
-            BinOperator::At => at::exec(lhs, rhs)?,
-            BinOperator::FunctionCall => call::exec(lhs, rhs)?,
-            BinOperator::Assign => assign::exec(lhs, rhs, |_, b| b),
-            BinOperator::AssignAdd => assign::exec(lhs, rhs, add::exec),
-            BinOperator::AssignSubtract => assign::exec(lhs, rhs, subtract::exec),
-            BinOperator::AssignMultiply => assign::exec(lhs, rhs, multiply::exec),
-            BinOperator::AssignDivide => assign::try_exec(lhs, rhs, divide::exec)?,
-            BinOperator::AssignModulo => assign::try_exec(lhs, rhs, modulo::exec)?,
-            BinOperator::AssignLShift => assign::try_exec(lhs, rhs, lshift::exec)?,
-            BinOperator::AssignRShift => assign::try_exec(lhs, rhs, rshift::exec)?,
-            BinOperator::AssignBitwiseAnd => assign::exec(lhs, rhs, bitwise_and::exec),
-            BinOperator::AssignBitwiseOr => assign::exec(lhs, rhs, bitwise_or::exec),
-            BinOperator::AssignXor => assign::exec(lhs, rhs, xor::exec),
-            BinOperator::AssignPow => assign::try_exec(lhs, rhs, pow::exec)?,
-            BinOperator::Partition => partition::exec(lhs, rhs)?,
-            _ => unreachable!(),
-        })
     }
 }
 
@@ -107,6 +174,18 @@ impl Recreate for BinOperation {
             return or::recreate(lhs, &self.rhs, local_variables);
         }
         let rhs = self.rhs.recreate(local_variables)?;
+        if self.op == BinOperator::FunctionCall && local_variables.opt_level == OptLevel::Full {
+            let function = match &lhs {
+                Instruction::AnonymousFunction(function) => Some(function),
+                Instruction::LocalVariable(ident, ..) => local_variables.literal(ident),
+                _ => None,
+            };
+            if let Some(function) = function
+                && let Some(inlined) = optimizer::inline(function, &rhs, local_variables)?
+            {
+                return Ok(inlined);
+            }
+        }
         match self.op {
             BinOperator::Add => Ok(add::create_from_instructions(lhs, rhs)),
             BinOperator::Subtract => Ok(subtract::create_from_instructions(lhs, rhs)),
@@ -146,18 +225,17 @@ impl ReturnType for BinOperation {
             | BinOperator::GreaterOrEqual
             | BinOperator::Lower
             | BinOperator::LowerOrEqual => Type::Bool,
+            BinOperator::BitwiseOr | BinOperator::Xor => bitwise::return_type(lhs, rhs),
             BinOperator::Subtract
             | BinOperator::Multiply
             | BinOperator::Divide
             | BinOperator::Pow
             | BinOperator::Filter
-            | BinOperator::BitwiseAnd
-            | BinOperator::BitwiseOr
-            | BinOperator::Xor => lhs,
+            | BinOperator::BitwiseAnd => lhs,
             BinOperator::Partition => partition::return_type(lhs),
             BinOperator::Map => map::return_type(rhs),
-            BinOperator::At => lhs.index_result().unwrap(),
-            BinOperator::FunctionCall => lhs.return_type().unwrap(),
+            BinOperator::At => at::return_type(lhs, &rhs),
+            BinOperator::FunctionCall => call::return_type(&lhs, rhs),
             BinOperator::Assign => rhs,
             BinOperator::LShift | BinOperator::RShift | BinOperator::Modulo => Type::Int,
             _ => lhs.mut_element_type().unwrap(),
@@ -170,7 +248,7 @@ mod equal {
     use crate::{instruction::Instruction, variable::Variable};
 
     pub fn exec(lhs: Variable, rhs: Variable) -> Variable {
-        (lhs == rhs).into()
+        lhs.equals(&rhs).into()
     }
 
     pub fn create_from_instructions(lhs: Instruction, rhs: Instruction) -> Instruction {
@@ -183,7 +261,7 @@ mod not_equal {
     use crate::{instruction::Instruction, variable::Variable};
 
     pub fn exec(lhs: Variable, rhs: Variable) -> Variable {
-        (lhs != rhs).into()
+        (!lhs.equals(&rhs)).into()
     }
 
     pub fn create_from_instructions(lhs: Instruction, rhs: Instruction) -> Instruction {
@@ -199,42 +277,47 @@ impl InstructionWithStr {
         local_variables: &LocalVariables<'_>,
     ) -> Result<Self, Error> {
         let str = format!("{} {} {}", lhs.str, op.as_str(), rhs.str).into();
+        let location = lhs.location.clone();
         let rule = op.as_rule();
         if rule == Rule::reduce {
             return Ok(Self {
                 instruction: Reduce::create_instruction(lhs, op, rhs, local_variables)?,
                 str,
+                location,
             });
         }
-        let op = BinOperator::from(rule);
-        let (lhs, rhs) = (lhs.instruction, rhs.instruction);
-
-        let lhs_type = lhs.return_type();
-        let rhs_type = rhs.return_type();
-        if !can_be_used(&lhs_type, &rhs_type, op) {
-            return Err(Error::CannotDo2(lhs_type, op, rhs_type));
+        if rule == Rule::range_step {
+            return Ok(Self {
+                instruction: Range::create_with_step(lhs, rhs)?,
+                str,
+                location,
+            });
         }
-
-        let instruction = BinOperation { lhs, rhs, op }.into();
-
-        Ok(Self { instruction, str })
+        let op = BinOperator::from(rule);
+        Ok(Self {
+            instruction: BinOperation::create_instruction(lhs, op, rhs, local_variables)?,
+            str,
+            location,
+        })
     }
 }
 
-fn can_be_used(lhs: &Type, rhs: &Type, op: BinOperator) -> bool {
+pub fn can_be_used(lhs: &Type, rhs: &Type, op: BinOperator) -> bool {
     match op {
         BinOperator::Add => add::can_be_used(lhs, rhs),
-        BinOperator::Subtract
-        | BinOperator::Multiply
-        | BinOperator::Divide
-        | BinOperator::Pow
-        | BinOperator::Lower
+        BinOperator::Subtract => subtract::can_be_used(lhs, rhs),
+        BinOperator::Multiply | BinOperator::Divide | BinOperator::Pow => {
+            can_be_used_num(lhs.clone(), rhs.clone())
+        }
+        BinOperator::Lower
         | BinOperator::LowerOrEqual
         | BinOperator::Greater
-        | BinOperator::GreaterOrEqual => can_be_used_num(lhs.clone(), rhs.clone()),
-        BinOperator::LShift | BinOperator::RShift | BinOperator::Modulo => {
-            can_be_used_int(lhs.clone(), rhs.clone())
-        }
+        | BinOperator::GreaterOrEqual => can_be_compared(lhs, rhs),
+        BinOperator::LShift
+        | BinOperator::RShift
+        | BinOperator::Modulo
+        | BinOperator::Range
+        | BinOperator::RangeInclusive => can_be_used_int(lhs.clone(), rhs.clone()),
         BinOperator::Equal
         | BinOperator::NotEqual
         | BinOperator::At
@@ -253,15 +336,19 @@ fn can_be_used(lhs: &Type, rhs: &Type, op: BinOperator) -> bool {
                 add::return_type(lhs.clone(), rhs.clone())
             })
         }
-        BinOperator::AssignSubtract
-        | BinOperator::AssignMultiply
-        | BinOperator::AssignDivide
-        | BinOperator::AssignPow => assign::can_be_used(
-            lhs.clone(),
-            rhs.clone(),
-            assign_can_be_used_num,
-            return_type,
-        ),
+        BinOperator::AssignSubtract => {
+            assign::can_be_used(lhs.clone(), rhs.clone(), subtract::can_be_used, |lhs, _| {
+                lhs.clone()
+            })
+        }
+        BinOperator::AssignMultiply | BinOperator::AssignDivide | BinOperator::AssignPow => {
+            assign::can_be_used(
+                lhs.clone(),
+                rhs.clone(),
+                assign_can_be_used_num,
+                return_type,
+            )
+        }
         BinOperator::AssignModulo | BinOperator::AssignLShift | BinOperator::AssignRShift => {
             assign::can_be_used(
                 lhs.clone(),
@@ -270,8 +357,15 @@ fn can_be_used(lhs: &Type, rhs: &Type, op: BinOperator) -> bool {
                 return_type,
             )
         }
-        BinOperator::AssignBitwiseAnd | BinOperator::AssignBitwiseOr | BinOperator::AssignXor => {
-            assign::can_be_used(lhs.clone(), rhs.clone(), bitwise_can_be_used, return_type)
+        BinOperator::AssignBitwiseAnd => {
+            assign::can_be_used(lhs.clone(), rhs.clone(), bitwise_can_be_used, |lhs, _| {
+                lhs.clone()
+            })
+        }
+        BinOperator::AssignBitwiseOr | BinOperator::AssignXor => {
+            assign::can_be_used(lhs.clone(), rhs.clone(), bitwise_can_be_used, |lhs, rhs| {
+                bitwise::return_type(lhs.clone(), rhs.clone())
+            })
         }
     }
 }
@@ -1,5 +1,8 @@
+use super::{
+    add, bitwise_and, bitwise_or, divide, lshift, modulo, multiply, pow, rshift, subtract, xor,
+};
 use crate::{
-    ExecError,
+    BinOperator, ExecError,
     variable::{Type, Variable},
 };
 
@@ -16,24 +19,28 @@ where
     can_be_used && return_type.matches(&var_type)
 }
 
-pub fn exec<T: FnOnce(Variable, Variable) -> Variable>(
-    lhs: Variable,
-    rhs: Variable,
-    function: T,
-) -> Variable {
+pub fn exec(lhs: Variable, rhs: Variable, op: BinOperator) -> Result<Variable, ExecError> {
     let lhs = lhs.into_mut().unwrap();
     let mut lhs = lhs.variable.write().unwrap();
-    *lhs = function(lhs.clone(), rhs);
-    lhs.clone()
+    *lhs = new_value(op, lhs.clone(), rhs)?;
+    Ok(lhs.clone())
 }
 
-pub fn try_exec<T: FnOnce(Variable, Variable) -> Result<Variable, ExecError>>(
-    lhs: Variable,
-    rhs: Variable,
-    function: T,
-) -> Result<Variable, ExecError> {
-    let lhs = lhs.into_mut().unwrap();
-    let mut lhs = lhs.variable.write().unwrap();
-    *lhs = function(lhs.clone(), rhs)?;
-    Ok(lhs.clone())
+/// Returns value that assignment with op stores in place of value
+pub fn new_value(op: BinOperator, value: Variable, rhs: Variable) -> Result<Variable, ExecError> {
+    Ok(match op {
+        BinOperator::Assign => rhs,
+        BinOperator::AssignAdd => add::exec(value, rhs),
+        BinOperator::AssignSubtract => subtract::exec(value, rhs),
+        BinOperator::AssignMultiply => multiply::exec(value, rhs),
+        BinOperator::AssignDivide => divide::exec(value, rhs)?,
+        BinOperator::AssignModulo => modulo::exec(value, rhs)?,
+        BinOperator::AssignLShift => lshift::exec(value, rhs)?,
+        BinOperator::AssignRShift => rshift::exec(value, rhs)?,
+        BinOperator::AssignBitwiseAnd => bitwise_and::exec(value, rhs),
+        BinOperator::AssignBitwiseOr => bitwise_or::exec(value, rhs),
+        BinOperator::AssignXor => xor::exec(value, rhs),
+        BinOperator::AssignPow => pow::exec(value, rhs)?,
+        op => unreachable!("{op} is not assignment operator"),
+    })
 }
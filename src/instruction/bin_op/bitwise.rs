@@ -5,24 +5,33 @@ use lazy_static::lazy_static;
 use simplesl_macros::var_type;
 
 lazy_static! {
-    pub static ref ACCEPTED_TYPE: Type = var_type!((int, int) | (bool, bool));
+    pub static ref ACCEPTED_TYPE: Type = var_type!((int, int) | (bool, bool) | ({ any }, { any }));
 }
 
 pub fn can_be_used(lhs: Type, rhs: Type) -> bool {
     var_type!((lhs, rhs)).matches(&ACCEPTED_TYPE)
 }
 
+/// Returns type of union or symmetric difference of values of types lhs and rhs
+pub fn return_type(lhs: Type, rhs: Type) -> Type {
+    let Some(lhs_element) = lhs.set_element_type() else {
+        return lhs;
+    };
+    let rhs_element = rhs.set_element_type().unwrap();
+    Type::Set((lhs_element | rhs_element).into())
+}
+
 #[duplicate_item(
-    Bitwise bitwise op1 op2;
-    [BitwiseAnd] [bitwise_and] [lhs & rhs] [&];
-    [BitwiseOr] [bitwise_or] [lhs | rhs] [|];
-    [Xor] [xor] [lhs ^ rhs] [^];
+    Bitwise bitwise op1 op2 set_op;
+    [BitwiseAnd] [bitwise_and] [lhs & rhs] [&] [Set::intersection(&lhs, &rhs)];
+    [BitwiseOr] [bitwise_or] [lhs | rhs] [|] [Set::union(std::sync::Arc::unwrap_or_clone(lhs), &rhs)];
+    [Xor] [xor] [lhs ^ rhs] [^] [Set::symmetric_difference(&lhs, &rhs)];
 )]
 pub mod bitwise {
     use crate::{
         BinOperator,
         instruction::{Instruction, create_from_instructions_with_exec},
-        variable::Variable,
+        variable::{Set, Variable},
     };
 
     pub fn create_from_instructions(lhs: Instruction, rhs: Instruction) -> Instruction {
@@ -33,6 +42,7 @@ pub mod bitwise {
         match (lhs, rhs) {
             (Variable::Int(lhs), Variable::Int(rhs)) => (op1).into(),
             (Variable::Bool(lhs), Variable::Bool(rhs)) => (op1).into(),
+            (Variable::Set(lhs), Variable::Set(rhs)) => (set_op).into(),
             (lhs, rhs) => panic!(
                 "Tried to do {lhs} {} {rhs} which is imposible",
                 stringify!(op2)
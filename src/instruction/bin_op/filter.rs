@@ -1,33 +1,11 @@
 use crate::{
-    self as simplesl, Code, Interpreter,
+    self as simplesl,
     function::Function,
     instruction::ExecResult,
+    interpreter::CallDepth,
     variable::{Type, Typed, Variable},
 };
-use lazy_static::lazy_static;
 use simplesl_macros::var_type;
-use std::sync::Arc;
-
-lazy_static! {
-    static ref FILTER: Arc<Function> = Code::parse(
-        &Interpreter::without_stdlib(),
-        "(func: () -> (bool, int), predicate: (int) -> bool) -> () -> (bool, int) {
-            return () -> (bool, int) {
-                loop {
-                    res := func();
-                    (con, value) := res;
-                    if !con || predicate(value) return res;
-                }
-                return (false, 0);
-            }
-        }"
-    )
-    .unwrap()
-    .exec()
-    .unwrap()
-    .into_function()
-    .unwrap();
-}
 
 pub fn can_be_used(lhs: &Type, rhs: &Type) -> bool {
     let Some(element_type) = lhs.iter_element() else {
@@ -37,22 +15,31 @@ pub fn can_be_used(lhs: &Type, rhs: &Type) -> bool {
     rhs.matches(&expected_function)
 }
 
-pub fn exec(iter: Variable, function: Variable) -> ExecResult {
-    let element = iter.as_type().return_type().unwrap();
-    let result = FILTER
-        .exec_with_args(&[iter, function])?
-        .into_function()
-        .unwrap();
-    let mut result = Arc::unwrap_or_clone(result);
-    result.return_type = element;
-    Ok(result.into())
+pub fn exec(iter: Variable, function: Variable, call_depth: CallDepth) -> ExecResult {
+    let element_type = iter.as_type().iter_element().unwrap();
+    let predicate = function.into_function().unwrap();
+    let iter = iter.into_function().unwrap().iter(call_depth);
+    let call_depth = iter.call_depth();
+    let elements = iter.filter_map(move |element| {
+        let element = match element {
+            Ok(element) => element,
+            Err(error) => return Some(Err(error)),
+        };
+        match predicate.exec_with_args(std::slice::from_ref(&element), call_depth) {
+            Ok(Variable::Bool(true)) => Some(Ok(element)),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        }
+    });
+    Ok(Function::native_adaptor(elements, element_type, call_depth).into())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate as simplesl;
-    use crate::instruction::bin_op::filter;
-    use simplesl_macros::var_type;
+    use crate::{
+        self as simplesl, Code, Error, Interpreter, instruction::bin_op::filter, variable::Variable,
+    };
+    use simplesl_macros::{var, var_type};
 
     #[test]
     fn can_be_used() {
@@ -93,4 +80,35 @@ mod tests {
             &var_type!(string)
         ))
     }
+
+    #[test]
+    fn exec() {
+        assert_eq!(
+            parse_and_exec("[1, 2, 3, 4]~ ? (x: int) -> bool { return x % 2 == 0 } $]"),
+            Ok(var!([2, 4]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "x := [1, \"a\", 2.5, \"b\"]~ ? string;
+                (x(), x(), x())"
+            ),
+            Ok(var!(((true, "a"), (true, "b"), (false, ""))))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "[1, 2]~ ? (x: int) -> bool {
+                    if x == 2 {
+                        throw \"two\"
+                    }
+                    return true
+                } $]"
+            ),
+            Err(Error::User(var!("two")))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
 }
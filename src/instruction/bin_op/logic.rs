@@ -1,9 +1,7 @@
 pub mod and {
     use crate::{
-        BinOperator, ExecError, Interpreter,
-        instruction::{
-            BinOperation, Exec, ExecResult, Instruction, Recreate, local_variable::LocalVariables,
-        },
+        BinOperator, ExecError,
+        instruction::{BinOperation, Instruction, Recreate, local_variable::LocalVariables},
         variable::Variable,
     };
 
@@ -36,22 +34,12 @@ pub mod and {
             .into()),
         }
     }
-
-    pub fn exec(lhs: Variable, rhs: &Instruction, interpreter: &mut Interpreter) -> ExecResult {
-        let lhs = lhs.into_bool().unwrap();
-        if !lhs {
-            return Ok(Variable::Bool(false));
-        }
-        rhs.exec(interpreter)
-    }
 }
 
 pub mod or {
     use crate::{
-        BinOperator, ExecError, Interpreter,
-        instruction::{
-            BinOperation, Exec, ExecResult, Instruction, Recreate, local_variable::LocalVariables,
-        },
+        BinOperator, ExecError,
+        instruction::{BinOperation, Instruction, Recreate, local_variable::LocalVariables},
         variable::Variable,
     };
 
@@ -86,12 +74,4 @@ pub mod or {
             .into()),
         }
     }
-
-    pub fn exec(lhs: Variable, rhs: &Instruction, interpreter: &mut Interpreter) -> ExecResult {
-        let lhs = lhs.into_bool().unwrap();
-        if lhs {
-            return Ok(Variable::Bool(true));
-        }
-        rhs.exec(interpreter)
-    }
 }
@@ -1,31 +1,11 @@
 use crate::{
-    self as simplesl, Code, Interpreter,
+    self as simplesl,
     function::Function,
     instruction::ExecResult,
-    variable::{Type, Typed, Variable},
+    interpreter::CallDepth,
+    variable::{Type, Variable},
 };
-use lazy_static::lazy_static;
 use simplesl_macros::var_type;
-use std::sync::Arc;
-
-lazy_static! {
-    static ref MAP: Arc<Function> = Code::parse(
-        &Interpreter::without_stdlib(),
-        "(func: () -> (bool, int), mapper: (int) -> int) -> () -> (bool, int) {
-            return () -> (bool, int) {
-                res := func();
-                (con, value) := res;
-                if !con return res;
-                return (true, mapper(value));
-            }
-        }"
-    )
-    .unwrap()
-    .exec()
-    .unwrap()
-    .into_function()
-    .unwrap();
-}
 
 pub fn can_be_used(lhs: &Type, rhs: &Type) -> bool {
     let Some(element_type) = lhs.iter_element() else {
@@ -35,15 +15,13 @@ pub fn can_be_used(lhs: &Type, rhs: &Type) -> bool {
     rhs.matches(&expected_function)
 }
 
-pub fn exec(iter: Variable, function: Variable) -> ExecResult {
-    let result_type = function.as_type().return_type().unwrap();
-    let result = MAP
-        .exec_with_args(&[iter, function])?
-        .into_function()
-        .unwrap();
-    let mut result = Arc::unwrap_or_clone(result);
-    result.return_type = var_type!((bool, result_type));
-    Ok(result.into())
+pub fn exec(iter: Variable, function: Variable, call_depth: CallDepth) -> ExecResult {
+    let mapper = function.into_function().unwrap();
+    let result_type = mapper.return_type.clone();
+    let iter = iter.into_function().unwrap().iter(call_depth);
+    let call_depth = iter.call_depth();
+    let elements = iter.map(move |element| mapper.exec_with_args(&[element?], call_depth));
+    Ok(Function::native_adaptor(elements, result_type, call_depth).into())
 }
 
 pub fn return_type(rhs: Type) -> Type {
@@ -53,9 +31,12 @@ pub fn return_type(rhs: Type) -> Type {
 
 #[cfg(test)]
 mod tests {
-    use crate as simplesl;
-    use crate::instruction::bin_op::map;
-    use simplesl_macros::var_type;
+    use crate::{
+        self as simplesl, Code, Error, Interpreter,
+        instruction::bin_op::map,
+        variable::{Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
 
     #[test]
     fn can_be_used() {
@@ -118,4 +99,46 @@ mod tests {
             &var_type!(( any)->int | (any)->any)
         ));
     }
+
+    #[test]
+    fn exec() {
+        assert_eq!(
+            parse_and_exec("[1, 2, 3]~ @ (x: int) -> bool { return x > 1 } $]"),
+            Ok(var!([false, true, true]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "x := [1, 2]~ @ (x: int) -> float { return 1.5 };
+                (x(), x(), x())"
+            ),
+            Ok(var!(((true, 1.5), (true, 1.5), (false, 0.0))))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "i := mut 0
+                counter := () -> (bool, int) {
+                    i += 1
+                    return (*i <= 3, *i)
+                }
+                counter @ (x: int) -> int { return x * x } $]"
+            ),
+            Ok(var!([1, 4, 9]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "cell := mut [0]~
+                it := [1, 2]~ @ (x: int) -> int { return (*cell)().1 + x }
+                cell = it
+                it $]"
+            ),
+            Ok(var!([1, 2]))
+        );
+        let result = parse_and_exec("[1, 2]~ @ (x: int) -> (int, int) { return (x, x) }").unwrap();
+        assert_eq!(result.as_type(), var_type!(() -> (bool, (int, int))));
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
 }
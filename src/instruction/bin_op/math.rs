@@ -4,8 +4,27 @@ pub mod modulo;
 pub mod multiply;
 pub mod pow;
 pub mod subtract;
+use crate::variable::Type;
 use duplicate::duplicate_item;
 
+/// Returns true if values of types lhs and rhs can be compared with <, <=, > and >=.
+/// Numbers of the same type, strings, arrays and tuples of comparable elements can be compared
+pub fn can_be_compared(lhs: &Type, rhs: &Type) -> bool {
+    match (lhs, rhs) {
+        (Type::Multi(multi), rhs) => multi.iter().all(|lhs| can_be_compared(lhs, rhs)),
+        (lhs, Type::Multi(multi)) => multi.iter().all(|rhs| can_be_compared(lhs, rhs)),
+        (Type::Int, Type::Int) | (Type::Float, Type::Float) | (Type::String, Type::String) => true,
+        (Type::Array(lhs), Type::Array(rhs)) => {
+            **lhs == Type::Never || **rhs == Type::Never || can_be_compared(lhs, rhs)
+        }
+        (Type::Tuple(lhs), Type::Tuple(rhs)) => lhs
+            .iter()
+            .zip(rhs.iter())
+            .all(|(lhs, rhs)| can_be_compared(lhs, rhs)),
+        _ => false,
+    }
+}
+
 #[duplicate_item(
     ord Ord oper;
     [greater] [Greater] [>]; [greater_equal] [GreaterOrEqual] [>=]; [lower] [Lower] [<]; [lower_equal] [LowerOrEqual] [<=];
@@ -17,15 +36,94 @@ pub mod ord {
         variable::Variable,
     };
     use match_any::match_any;
+    use std::cmp::Ordering;
 
     pub fn create_from_instructions(lhs: Instruction, rhs: Instruction) -> Instruction {
         create_from_instructions_with_exec(lhs, rhs, BinOperator::Ord, exec)
     }
+    /// Elements of arrays and tuples are compared with the same rules as scalars
     pub fn exec(lhs: Variable, rhs: Variable) -> Variable {
         match_any! { (lhs, rhs),
             (Variable::Int(lhs), Variable::Int(rhs)) | (Variable::Float(lhs), Variable::Float(rhs))
                 => (lhs oper rhs).into(),
-            (lhs, rhs) => panic!("Tried to do {lhs} {} {rhs}", stringify!(op))
+            (lhs, rhs) => lhs.compare(&rhs).is_some_and(|ordering| ordering oper Ordering::Equal).into()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as simplesl, BinOperator, Code, Error, Interpreter, variable::Variable};
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn comparison() {
+        assert_eq!(
+            parse_and_exec(r#"("apple" < "banana", "b" <= "abc", "ab" > "a", "" >= "")"#),
+            Ok(var!((true, false, true, true)))
+        );
+        assert_eq!(
+            parse_and_exec("([1, 2, 3] < [1, 3], [1, 2] < [1, 2, 0], [] < [1], [2] <= [2])"),
+            Ok(var!((true, true, true, true)))
+        );
+        assert_eq!(
+            parse_and_exec(
+                r#"((1, 10, 0) >= (1, 9, 5), (1, "b") > (1, "a"), (1.5, 2) < (1.5, 1))"#
+            ),
+            Ok(var!((true, true, false)))
+        );
+        assert_eq!(
+            parse_and_exec(r#"[("a", 2), ("a", 1)] < [("a", 1)]"#),
+            Ok(var!(false))
+        );
+        assert_eq!(
+            parse_and_exec("[1] < [1.5]"),
+            Err(Error::CannotDo2(
+                var_type!([int]),
+                BinOperator::Lower,
+                var_type!([float])
+            ))
+        );
+        assert_eq!(
+            parse_and_exec(r#"(1, "a") < (1, 2)"#),
+            Err(Error::CannotDo2(
+                var_type!((int, string)),
+                BinOperator::Lower,
+                var_type!((int, int))
+            ))
+        );
+        assert_eq!(
+            parse_and_exec(r#""a" > 1"#),
+            Err(Error::CannotDo2(
+                var_type!(string),
+                BinOperator::Greater,
+                var_type!(int)
+            ))
+        );
+    }
+
+    #[test]
+    fn float_element_comparison() {
+        assert_eq!(
+            parse_and_exec("([-0.0] < [0.0], -0.0 < 0.0, [-0.0] <= [0.0], [-0.0] == [0.0])"),
+            Ok(var!((false, false, true, true)))
+        );
+        assert_eq!(parse_and_exec("(-0.0, 1) >= (0.0, 1)"), Ok(var!(true)));
+        assert_eq!(
+            parse_and_exec(
+                "n := 0.0 / 0.0;
+                (n <= n, [n] <= [n], [n] >= [n], [n] < [1.0], [n] == [n], (n, 1) <= (n, 1))"
+            ),
+            Ok(var!((false, false, false, false, false, false)))
+        );
+        assert_eq!(
+            parse_and_exec("n := 0.0 / 0.0; ([1.0, n] < [2.0, n], [n] != [n])"),
+            Ok(var!((true, true)))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
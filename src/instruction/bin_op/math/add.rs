@@ -1,12 +1,14 @@
 use crate::instruction::{Instruction, create_from_instructions_with_exec};
-use crate::variable::{Array, Type, Variable};
+use crate::variable::{Array, Map, MapType, Type, Variable};
 use crate::{self as simplesl, BinOperator};
 use lazy_static::lazy_static;
 use simplesl_macros::var_type;
+use std::sync::Arc;
 
 lazy_static! {
-    static ref ACCEPTED_TYPE: Type =
-        var_type!((int, int) | (float, float) | (string, string) | ([any], [any]));
+    static ref ACCEPTED_TYPE: Type = var_type!(
+        (int, int) | (float, float) | (string, string) | ([any], [any]) | ({any: any}, {any: any})
+    );
 }
 
 pub(crate) fn can_be_used(lhs: &Type, rhs: &Type) -> bool {
@@ -25,11 +27,21 @@ pub fn exec(lhs: Variable, rhs: Variable) -> Variable {
         (Variable::Float(value1), Variable::Float(value2)) => (value1 + value2).into(),
         (Variable::String(value1), Variable::String(value2)) => format!("{value1}{value2}").into(),
         (Variable::Array(array1), Variable::Array(array2)) => Array::concat(array1, array2).into(),
+        (Variable::Map(map1), Variable::Map(map2)) => {
+            Map::merge(Arc::unwrap_or_clone(map1), &map2).into()
+        }
         (lhs, rhs) => panic!("Tried to do {lhs} + {rhs} which is imposible"),
     }
 }
 
 pub fn return_type(lhs: Type, rhs: Type) -> Type {
+    if let (Some(lhs_key), Some(lhs_value)) = (lhs.key_type(), lhs.value_type()) {
+        return MapType {
+            key: lhs_key | rhs.key_type().unwrap(),
+            value: lhs_value | rhs.value_type().unwrap(),
+        }
+        .into();
+    }
     let Some(lhs_element) = lhs.element_type() else {
         return lhs;
     };
@@ -1,6 +1,18 @@
-use crate::BinOperator;
 use crate::instruction::{Instruction, create_from_instructions_with_exec};
-use crate::variable::Variable;
+use crate::variable::{Set, Type, Variable};
+use crate::{self as simplesl, BinOperator};
+use lazy_static::lazy_static;
+use simplesl_macros::var_type;
+
+lazy_static! {
+    static ref ACCEPTED_TYPE: Type = var_type!((int, int) | (float, float) | ({ any }, { any }));
+}
+
+pub(crate) fn can_be_used(lhs: &Type, rhs: &Type) -> bool {
+    let lhs = lhs.clone();
+    let rhs = rhs.clone();
+    var_type!((lhs, rhs)).matches(&ACCEPTED_TYPE)
+}
 
 pub fn create_from_instructions(lhs: Instruction, rhs: Instruction) -> Instruction {
     create_from_instructions_with_exec(lhs, rhs, BinOperator::Subtract, exec)
@@ -10,6 +22,7 @@ pub fn exec(lhs: Variable, rhs: Variable) -> Variable {
     match (lhs, rhs) {
         (Variable::Int(lhs), Variable::Int(rhs)) => lhs.wrapping_sub(rhs).into(),
         (Variable::Float(lhs), Variable::Float(rhs)) => (lhs - rhs).into(),
+        (Variable::Set(lhs), Variable::Set(rhs)) => Set::difference(&lhs, &rhs).into(),
         (lhs, rhs) => panic!("Tried to do {lhs} - {rhs}"),
     }
 }
@@ -0,0 +1,251 @@
+use crate::{
+    self as simplesl, BinOperator, Error,
+    instruction::{
+        BinOperation, Instruction, InstructionWithStr, function::FunctionDeclaration,
+        local_variable::LocalVariables, method::Method, unary_operation::UnaryOperation,
+    },
+    unary_operator::UnaryOperator,
+    variable::{ReturnType, Type},
+};
+use simplesl_macros::var_type;
+
+/// Returns name of method implementing op for types declared in scripts.
+/// Compound assignment uses method of its operator
+fn method_name(op: BinOperator) -> Option<&'static str> {
+    Some(match op {
+        BinOperator::Add | BinOperator::AssignAdd => "__add",
+        BinOperator::Subtract | BinOperator::AssignSubtract => "__sub",
+        BinOperator::Multiply | BinOperator::AssignMultiply => "__mul",
+        BinOperator::Divide | BinOperator::AssignDivide => "__div",
+        BinOperator::Modulo | BinOperator::AssignModulo => "__mod",
+        BinOperator::Pow | BinOperator::AssignPow => "__pow",
+        BinOperator::Equal | BinOperator::NotEqual => "__eq",
+        BinOperator::Lower => "__lt",
+        BinOperator::LowerOrEqual => "__le",
+        BinOperator::Greater => "__gt",
+        BinOperator::GreaterOrEqual => "__ge",
+        BinOperator::BitwiseAnd | BinOperator::AssignBitwiseAnd => "__and",
+        BinOperator::BitwiseOr | BinOperator::AssignBitwiseOr => "__or",
+        BinOperator::Xor | BinOperator::AssignXor => "__xor",
+        BinOperator::LShift | BinOperator::AssignLShift => "__shl",
+        BinOperator::RShift | BinOperator::AssignRShift => "__shr",
+        _ => return None,
+    })
+}
+
+/// Checks that method implementing operator takes one param besides self
+/// and comparison methods return bool. Other methods are accepted as they are
+pub fn check_method(name: &str, method: &FunctionDeclaration) -> Result<(), Error> {
+    let returns_bool = match name {
+        "__eq" | "__lt" | "__le" | "__gt" | "__ge" => true,
+        "__add" | "__sub" | "__mul" | "__div" | "__mod" | "__pow" | "__and" | "__or" | "__xor"
+        | "__shl" | "__shr" => false,
+        _ => return Ok(()),
+    };
+    let function_type = method.return_type();
+    let return_type = function_type.return_type().unwrap();
+    if method.params.len() == 2 && (!returns_bool || return_type == Type::Bool) {
+        return Ok(());
+    }
+    let self_type = method.params[0].var_type.clone();
+    let expected_return = if returns_bool {
+        Type::Bool
+    } else {
+        return_type
+    };
+    Err(Error::WrongType(
+        method.ident.clone(),
+        var_type!((self_type, any) -> expected_return),
+    ))
+}
+
+/// Returns call of method implementing op for type of lhs or None if there is no such method.
+/// `!=` is negation of `__eq`
+pub fn create(
+    lhs: &InstructionWithStr,
+    rhs: &InstructionWithStr,
+    op: BinOperator,
+    local_variables: &LocalVariables,
+) -> Result<Option<Instruction>, Error> {
+    if op.is_assign() {
+        return create_assign(lhs, rhs, op, local_variables);
+    }
+    let Some(method) = method_name(op).and_then(|name| Method::create(lhs, name, local_variables))
+    else {
+        return Ok(None);
+    };
+    let rhs_type = rhs.return_type();
+    let returns_bool = method.return_type.matches(&Type::Bool);
+    if method.params.len() != 1
+        || !rhs_type.matches(&method.params[0].var_type)
+        || matches!(op, BinOperator::Equal | BinOperator::NotEqual) && !returns_bool
+    {
+        return Err(Error::CannotDo2(lhs.return_type(), op, rhs_type));
+    }
+    let call = method.call([rhs.clone()].into());
+    if op == BinOperator::NotEqual {
+        return Ok(Some(
+            UnaryOperation {
+                instruction: call,
+                op: UnaryOperator::Not,
+            }
+            .into(),
+        ));
+    }
+    Ok(Some(call))
+}
+
+/// Returns `lhs = *lhs op rhs` calling method of value stored in mut lhs
+fn create_assign(
+    lhs: &InstructionWithStr,
+    rhs: &InstructionWithStr,
+    op: BinOperator,
+    local_variables: &LocalVariables,
+) -> Result<Option<Instruction>, Error> {
+    let lhs_type = lhs.return_type();
+    let Some(element_type) = lhs_type.mut_element_type() else {
+        return Ok(None);
+    };
+    let receiver = InstructionWithStr {
+        instruction: UnaryOperation {
+            instruction: lhs.instruction.clone(),
+            op: UnaryOperator::Indirection,
+        }
+        .into(),
+        str: format!("*{}", lhs.str).into(),
+        location: lhs.location.clone(),
+    };
+    let Some(method) =
+        method_name(op).and_then(|name| Method::create(&receiver, name, local_variables))
+    else {
+        return Ok(None);
+    };
+    let rhs_type = rhs.return_type();
+    if method.params.len() != 1
+        || !rhs_type.matches(&method.params[0].var_type)
+        || !method.return_type.matches(&element_type)
+    {
+        return Err(Error::CannotDo2(lhs_type, op, rhs_type));
+    }
+    Ok(Some(
+        BinOperation {
+            lhs: lhs.instruction.clone(),
+            rhs: method.call([rhs.clone()].into()),
+            op: BinOperator::Assign,
+        }
+        .into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as simplesl, BinOperator, Code, Error, Interpreter, variable::Variable};
+    use simplesl_macros::{var, var_type};
+
+    const VECTOR: &str = "type Vector = struct{x: float, y: float}
+        impl Vector {
+            fn __add(self, other: Vector) -> Vector {
+                return struct{x := self.x + other.x, y := self.y + other.y}
+            }
+            fn __mul(self, scale: float) -> Vector {
+                return struct{x := self.x * scale, y := self.y * scale}
+            }
+            fn __eq(self, other: Vector) -> bool {
+                return self.x == other.x
+            }
+            fn __lt(self, other: Vector) -> bool {
+                return self.x < other.x
+            }
+        }
+        a := struct{x := 1.0, y := 2.0}
+        b := struct{x := 3.0, y := 4.0};";
+
+    #[test]
+    fn overloaded_operators() {
+        assert_eq!(parse_and_exec("(a + b * 2.0).y"), Ok(var!(10.0)));
+        assert_eq!(
+            parse_and_exec("(a == struct{x := 1.0, y := 5.0}, a != b, a < b, b < a)"),
+            Ok(var!((true, true, true, false)))
+        );
+        assert_eq!(
+            parse_and_exec("[a, b]~ @ (v: Vector) { v * 0.5 } $]")
+                .map(|array| array.into_array().unwrap()[1].clone()),
+            parse_and_exec("struct{x := 1.5, y := 2.0}")
+        );
+        assert_eq!(
+            parse_and_exec("a - b"),
+            Err(Error::CannotDo2(
+                var_type!(struct{x: float, y: float}),
+                BinOperator::Subtract,
+                var_type!(struct{x: float, y: float})
+            ))
+        );
+        assert_eq!(
+            parse_and_exec("a + 1.0"),
+            Err(Error::CannotDo2(
+                var_type!(struct{x: float, y: float}),
+                BinOperator::Add,
+                var_type!(float)
+            ))
+        );
+        assert_eq!(parse_and_exec("1 + 2 == 3"), Ok(var!(true)));
+    }
+
+    #[test]
+    fn overloaded_compound_assignment() {
+        assert_eq!(
+            parse_and_exec("c := mut Vector a; c += b; c *= 2.0; *c"),
+            parse_and_exec("struct{x := 8.0, y := 12.0}")
+        );
+        assert_eq!(
+            parse_and_exec("c := mut Vector a; c += 1.0"),
+            Err(Error::CannotDo2(
+                var_type!(mut struct{x: float, y: float}),
+                BinOperator::AssignAdd,
+                var_type!(float)
+            ))
+        );
+        assert_eq!(
+            parse_and_exec("c := mut Vector a; c -= b"),
+            Err(Error::CannotDo2(
+                var_type!(mut struct{x: float, y: float}),
+                BinOperator::AssignSubtract,
+                var_type!(struct{x: float, y: float})
+            ))
+        );
+    }
+
+    #[test]
+    fn operator_method_signature() {
+        assert_eq!(
+            parse_and_exec(
+                "impl Vector {
+                    fn __le(self, other: Vector) -> int { return 1 }
+                }"
+            ),
+            Err(Error::WrongType(
+                "Vector.__le".into(),
+                var_type!((struct{x: float, y: float}, any) -> bool)
+            ))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "impl Vector {
+                    fn __sub(self) -> Vector { return self }
+                }"
+            ),
+            Err(Error::WrongType(
+                "Vector.__sub".into(),
+                var_type!((struct{x: float, y: float}, any) -> struct{x: float, y: float})
+            ))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(
+            &Interpreter::without_stdlib(),
+            &format!("{VECTOR}\n{script}"),
+        )
+        .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
@@ -1,22 +1,25 @@
 use crate::{
     self as simplesl,
     instruction::ExecResult,
+    interpreter::CallDepth,
     variable::{Array, Type, Typed, Variable},
 };
 use simplesl_macros::{var, var_type};
 
-pub fn exec(iter: Variable, function: Variable) -> ExecResult {
+pub fn exec(iter: Variable, function: Variable, call_depth: CallDepth) -> ExecResult {
     let (Variable::Function(iter), Variable::Function(function)) = (&iter, &function) else {
         unreachable!("Tried to do {iter} ? {function}")
     };
     let mut left = Vec::new();
     let mut right = Vec::new();
-    while let Variable::Tuple(tuple) = iter.exec_with_args(&[])? {
+    while let Variable::Tuple(tuple) = iter.exec_with_args(&[], call_depth)? {
         if tuple[0] == Variable::Bool(false) {
             break;
         }
         let element = tuple[1].clone();
-        if let Variable::Bool(true) = function.exec_with_args(std::slice::from_ref(&element))? {
+        if let Variable::Bool(true) =
+            function.exec_with_args(std::slice::from_ref(&element), call_depth)?
+        {
             left.push(element)
         } else {
             right.push(element)
@@ -1,11 +1,11 @@
 use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
-    recreate_instructions,
+    Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    recreate_sequence,
 };
 use crate::{
     Error, ExecError,
-    interpreter::Interpreter,
-    variable::{ReturnType, Type, Variable},
+    variable::{ReturnType, Type},
+    vm::Compiler,
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -27,21 +27,16 @@ impl Block {
     }
 }
 
-impl Exec for Block {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let mut interpreter = interpreter.create_layer();
-        Ok(interpreter
-            .exec(&self.instructions)?
-            .last()
-            .cloned()
-            .unwrap_or(Variable::Void))
+impl Compile for Block {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile_sequence(&self.instructions);
     }
 }
 
 impl Recreate for Block {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
         let mut local_variables = local_variables.create_layer();
-        let instructions = recreate_instructions(&self.instructions, &mut local_variables)?;
+        let instructions = recreate_sequence(&self.instructions, &mut local_variables)?;
         Ok(Self { instructions }.into())
     }
 }
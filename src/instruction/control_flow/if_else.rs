@@ -1,10 +1,10 @@
 use crate::{
     Error, ExecError,
     instruction::{
-        Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+        Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
     },
-    interpreter::Interpreter,
     variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -43,13 +43,15 @@ impl IfElse {
     }
 }
 
-impl Exec for IfElse {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let condition = self.condition.exec(interpreter)?.into_bool().unwrap();
-        if condition {
-            return self.if_true.exec(interpreter);
-        }
-        self.if_false.exec(interpreter)
+impl Compile for IfElse {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.condition);
+        let if_false = compiler.emit(Op::JumpIfFalse(0));
+        compiler.compile(&self.if_true);
+        let end = compiler.emit(Op::Jump(0));
+        compiler.patch(if_false);
+        compiler.compile(&self.if_false);
+        compiler.patch(end);
     }
 }
 
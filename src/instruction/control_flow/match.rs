@@ -1,14 +1,15 @@
 use super::match_arm::MatchArm;
 use crate::{
-    Error, ExecError,
+    Error, ExecError, Warning,
     instruction::{
-        Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+        Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
     },
-    interpreter::Interpreter,
     variable::{ReturnType, Type},
+    vm::Compiler,
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Match {
@@ -25,35 +26,118 @@ impl Match {
         let pair = inner.next().unwrap();
         let expression = InstructionWithStr::new(pair, local_variables)?;
         let var_type = expression.return_type();
-        let arms = inner
-            .map(|pair| MatchArm::new(pair, local_variables))
-            .collect::<Result<Box<[MatchArm]>, Error>>()?;
-        let result = Self { expression, arms };
-        if !result.is_covering_type(&var_type) {
-            return Err(Error::MatchNotCovered);
+        let mut arms = Vec::new();
+        for pair in inner {
+            let location = local_variables.location(&pair);
+            let str = arm_head(&pair);
+            let arm = MatchArm::new(pair, &var_type, local_variables)?;
+            if is_unreachable(&arms, &arm, &var_type) {
+                local_variables.warn(Warning::UnreachableArm {
+                    arm: str,
+                    location: Some(location),
+                });
+            }
+            arms.push(arm);
+        }
+        if let Some(uncovered) = uncovered_type(&arms, &var_type) {
+            let variants = uncovered_variants(&arms, &uncovered);
+            if !variants.is_empty() {
+                return Err(Error::VariantsNotCovered(uncovered, variants));
+            }
+            return Err(Error::MatchNotCovered(uncovered));
         }
-        Ok(result.into())
+        let arms = arms.into();
+        Ok(Self { expression, arms }.into())
     }
+}
 
-    fn is_covering_type(&self, checked_type: &Type) -> bool {
-        if let Type::Multi(types) = checked_type {
-            return types.iter().all(|var_type| self.is_covering_type(var_type));
+/// Returns part of the arm before `=>`
+fn arm_head(pair: &Pair<Rule>) -> Arc<str> {
+    let mut inner = pair.clone().into_inner().collect::<Vec<_>>();
+    inner.pop();
+    let (Some(first), Some(last)) = (inner.first(), inner.last()) else {
+        return "=>".into();
+    };
+    let span = first.as_span().start_pos().span(&last.as_span().end_pos());
+    span.as_str().into()
+}
+
+/// Returns part of checked_type not covered by arms, None if all values are covered
+fn uncovered_type(arms: &[MatchArm], checked_type: &Type) -> Option<Type> {
+    if let Type::Multi(types) = checked_type {
+        return types
+            .iter()
+            .filter_map(|var_type| uncovered_type(arms, var_type))
+            .reduce(Type::concat);
+    }
+    if is_covering_type(arms, checked_type) {
+        return None;
+    }
+    Some(checked_type.clone())
+}
+
+fn is_covering_type(arms: &[MatchArm], checked_type: &Type) -> bool {
+    if let Type::Enum(enum_type) = checked_type {
+        let all_variants_covered = (0..enum_type.variants.len()).all(|variant| {
+            arms.iter()
+                .any(|arm| arm.is_covering_variant(enum_type, variant))
+        });
+        if all_variants_covered {
+            return true;
         }
-        self.arms
+    }
+    arms.iter().any(|arm| arm.is_covering_type(checked_type))
+}
+
+/// Returns names of variants of enums in uncovered type that aren't covered by arms
+fn uncovered_variants(arms: &[MatchArm], uncovered: &Type) -> Arc<[Arc<str>]> {
+    let types = match uncovered {
+        Type::Multi(types) => types.iter().collect(),
+        uncovered => vec![uncovered],
+    };
+    types
+        .into_iter()
+        .filter_map(|var_type| match var_type {
+            Type::Enum(enum_type) => Some(enum_type),
+            _ => None,
+        })
+        .flat_map(|enum_type| {
+            enum_type
+                .variants
+                .iter()
+                .enumerate()
+                .filter(|(variant, _)| {
+                    !arms
+                        .iter()
+                        .any(|arm| arm.is_covering_variant(enum_type, *variant))
+                })
+                .map(|(_, variant)| format!("{}.{}", enum_type.name, variant.name).into())
+        })
+        .collect()
+}
+
+/// Returns true if values matched by arm are already covered by previous arms
+fn is_unreachable(previous: &[MatchArm], arm: &MatchArm, var_type: &Type) -> bool {
+    if !previous.is_empty() && uncovered_type(previous, var_type).is_none() {
+        return true;
+    }
+    if let Some((enum_type, variant)) = arm.variant() {
+        return previous
             .iter()
-            .any(|arm| arm.is_covering_type(checked_type))
+            .any(|arm| arm.is_covering_variant(enum_type, variant));
     }
+    arm.matched_type(var_type).is_some_and(|matched| {
+        matched == Type::Never || uncovered_type(previous, &matched).is_none()
+    })
 }
 
-impl Exec for Match {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let variable = self.expression.exec(interpreter)?;
-        for arm in self.arms.iter() {
-            if arm.covers(&variable, interpreter)? {
-                return arm.exec(variable, interpreter);
-            }
+impl Compile for Match {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.expression);
+        let ends: Vec<usize> = self.arms.iter().map(|arm| arm.compile(compiler)).collect();
+        for end in ends {
+            compiler.patch(end);
         }
-        panic!()
     }
 }
 
@@ -78,3 +162,179 @@ impl ReturnType for Match {
             .expect("match statement without arms")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter, Location, Warning, variable::Variable,
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn match_guards() {
+        let script = "classify := (value: int | string) -> string {
+            return match value {
+                x: int if x < 0 => \"negative\",
+                0 => \"zero\",
+                x: int if x % 2 == 0 => \"even\",
+                x: int => \"odd\",
+                s: string if s == \"\" => \"empty\",
+                => \"string\",
+            }
+        }
+        [classify(-3), classify(0), classify(4), classify(7), classify(\"\"), classify(\"a\")]";
+        assert_eq!(
+            parse_and_exec(script),
+            Ok(var!(["negative", "zero", "even", "odd", "empty", "string"]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match 5 {
+                    x: int if x > 0 => 1,
+                }"
+            ),
+            Err(Error::MatchNotCovered(var_type!(int)))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match (1, 2) {
+                    (a, b) if a + b => 1,
+                    => 0,
+                }"
+            ),
+            Err(Error::WrongCondition("a + b".into(), var_type!(int)))
+        );
+    }
+
+    #[test]
+    fn match_coverage() {
+        let f = "f := (value: int | string | ()) -> int {
+            return match value {
+                x: int => x,
+            }
+        }";
+        assert_eq!(
+            parse_and_exec(f),
+            Err(Error::MatchNotCovered(var_type!(string | ())))
+        );
+        let f = "f := (value: int | string | ()) -> int {
+            return match value {
+                x: int => x,
+                s: string => 1,
+                => 0,
+            }
+        }";
+        assert!(parse_and_exec(f).is_ok());
+        let f = "f := (value: int | ()) -> int {
+            return match value {
+                x: int => x,
+                () => 0,
+            }
+        }
+        (f(()), f(5))";
+        assert_eq!(parse_and_exec(f), Ok(var!((0, 5))));
+    }
+
+    #[test]
+    fn unreachable_arms() {
+        let script = "f := (value: int | string | ()) -> int {
+    return match value {
+        x: int | () => 0,
+        x: int => x,
+        s: string => 1,
+        => 2,
+    }
+}";
+        let code = Code::parse(&Interpreter::without_stdlib(), script).unwrap();
+        assert_eq!(
+            code.warnings(),
+            [
+                Warning::UnreachableArm {
+                    arm: "x: int".into(),
+                    location: Some(Location {
+                        file: None,
+                        line: 4,
+                        column: 9
+                    })
+                },
+                Warning::UnreachableArm {
+                    arm: "=>".into(),
+                    location: Some(Location {
+                        file: None,
+                        line: 6,
+                        column: 9
+                    })
+                }
+            ]
+        );
+        let script = "match 5 {
+            0 => 1,
+            x: int if x > 0 => 2,
+            => 3,
+        }";
+        let code = Code::parse(&Interpreter::without_stdlib(), script).unwrap();
+        assert_eq!(code.warnings(), []);
+    }
+
+    #[test]
+    fn range_patterns() {
+        let script = "f := (value: int) -> string {
+            return match value {
+                -10..0 => \"negative\",
+                0..10 => \"digit\",
+                10..=99 => \"two digits\",
+                => \"other\",
+            }
+        }
+        [f(-10), f(-1), f(0), f(9), f(10), f(99), f(100), f(-11)]";
+        assert_eq!(
+            parse_and_exec(script),
+            Ok(var!([
+                "negative",
+                "negative",
+                "digit",
+                "digit",
+                "two digits",
+                "two digits",
+                "other",
+                "other"
+            ]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match \"pear\" {
+                    \"a\"..\"m\" => 1,
+                    \"m\"..=\"z\" => 2,
+                    => 0,
+                }"
+            ),
+            Ok(var!(2))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match (5, \"b\") {
+                    (0..10, \"a\"..=\"c\") => true,
+                    => false,
+                }"
+            ),
+            Ok(var!(true))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match 5 {
+                    \"a\"..\"m\" => 1,
+                    => 0,
+                }"
+            ),
+            Err(Error::PatternNeverMatches(
+                "\"a\"..\"m\"".into(),
+                var_type!(int)
+            ))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
@@ -1,135 +1,336 @@
 use crate::{
     Error, ExecError,
     instruction::{
-        Exec, ExecResult, ExecStop, InstructionWithStr,
-        local_variable::{LocalVariable, LocalVariables},
+        InstructionWithStr,
+        local_variable::{LocalVariable, LocalVariables, Slot},
+        pattern::Pattern,
         recreate_instructions,
     },
     interpreter::Interpreter,
-    variable::{ReturnType, Type, Typed, Variable},
+    variable::{EnumType, ReturnType, Type, Typed, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::{Rule, unexpected};
 use std::sync::Arc;
 
 #[derive(Debug)]
-pub enum MatchArm {
+pub struct MatchArm {
+    kind: MatchArmKind,
+    guard: Option<InstructionWithStr>,
+    instruction: InstructionWithStr,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum MatchArmKind {
     Type {
         ident: Arc<str>,
         var_type: Type,
-        instruction: InstructionWithStr,
+        slot: Slot,
     },
-    Value(Arc<[InstructionWithStr]>, InstructionWithStr),
-    Other(InstructionWithStr),
+    Variant {
+        enum_type: Arc<EnumType>,
+        variant: usize,
+        /// Idents bound to payload with their slots
+        idents: Arc<[(Arc<str>, Slot)]>,
+    },
+    Pattern(Pattern),
+    Value(Arc<[InstructionWithStr]>),
+    Other,
 }
 
 impl MatchArm {
-    pub fn new(pair: Pair<Rule>, local_variables: &mut LocalVariables) -> Result<Self, Error> {
+    pub fn new(
+        pair: Pair<Rule>,
+        var_type: &Type,
+        local_variables: &mut LocalVariables,
+    ) -> Result<Self, Error> {
         let match_rule = pair.as_rule();
-        let mut inner = pair.into_inner();
-        let pair = inner.next().unwrap();
-        match match_rule {
+        let mut inner = pair.into_inner().collect::<Vec<_>>();
+        let body = inner.pop().unwrap();
+        let guard = inner
+            .pop_if(|pair| pair.as_rule() == Rule::match_guard)
+            .map(|pair| pair.into_inner().next().unwrap());
+        let mut inner = inner.into_iter();
+        let kind = match match_rule {
             Rule::match_type => {
-                let ident: Arc<str> = pair.as_str().into();
-                let var_type = Type::from(inner.next().unwrap());
-                let pair = inner.next().unwrap();
-                let mut local_variables = local_variables.create_layer();
-                local_variables.insert(ident.clone(), LocalVariable::Other(var_type.clone()));
-                let instruction = InstructionWithStr::new(pair, &mut local_variables)?;
-                Ok(Self::Type {
+                let ident: Arc<str> = inner.next().unwrap().as_str().into();
+                let var_type = local_variables.create_type(inner.next().unwrap())?;
+                MatchArmKind::Type {
                     ident,
                     var_type,
-                    instruction,
-                })
+                    slot: 0,
+                }
+            }
+            Rule::match_variant => MatchArmKind::new_variant(inner, var_type, local_variables)?,
+            Rule::match_pattern => {
+                let pair = inner.next().unwrap();
+                MatchArmKind::Pattern(Pattern::create(pair, var_type, local_variables)?)
             }
             Rule::match_value => {
-                let inner_values = pair.into_inner();
-                let values = inner_values
+                let values = inner
                     .map(|pair| InstructionWithStr::new(pair, local_variables))
                     .collect::<Result<Arc<[InstructionWithStr]>, Error>>()?;
-                let pair = inner.next().unwrap();
-                let instruction = InstructionWithStr::new(pair, local_variables)?;
-                Ok(Self::Value(values, instruction))
-            }
-            Rule::match_other => {
-                let instruction = InstructionWithStr::new(pair, local_variables)?;
-                Ok(Self::Other(instruction))
+                MatchArmKind::Value(values)
             }
+            Rule::match_other => MatchArmKind::Other,
             rule => unexpected!(rule),
+        };
+        let mut local_variables = local_variables.create_layer();
+        let kind = kind.insert_local_variables(&mut local_variables);
+        let guard = guard
+            .map(|pair| InstructionWithStr::new(pair, &mut local_variables))
+            .transpose()?;
+        if let Some(guard) = &guard {
+            let return_type = guard.return_type();
+            if return_type != Type::Bool {
+                return Err(Error::WrongCondition(guard.str.clone(), return_type));
+            }
         }
+        let instruction = InstructionWithStr::new(body, &mut local_variables)?;
+        Ok(Self {
+            kind,
+            guard,
+            instruction,
+        })
     }
     pub fn is_covering_type(&self, checked_type: &Type) -> bool {
-        match self {
-            Self::Value(..) => false,
-            Self::Other(_) => true,
-            Self::Type { var_type, .. } => checked_type.matches(var_type),
+        if self.guard.is_some() {
+            return false;
+        }
+        match &self.kind {
+            // `()` is the only value of its type
+            MatchArmKind::Value(values) => {
+                *checked_type == Type::Void
+                    && values.iter().any(|value| value.return_type() == Type::Void)
+            }
+            MatchArmKind::Variant { .. } => false,
+            MatchArmKind::Other => true,
+            MatchArmKind::Type { var_type, .. } => checked_type.matches(var_type),
+            MatchArmKind::Pattern(pattern) => pattern.is_covering_type(checked_type),
         }
     }
-    pub fn covers(
-        &self,
-        variable: &Variable,
-        interpreter: &mut Interpreter,
-    ) -> Result<bool, ExecStop> {
-        Ok(match self {
-            MatchArm::Other(_) => true,
-            MatchArm::Type { var_type, .. } => variable.as_type().matches(var_type),
-            MatchArm::Value(instructions, _) => {
-                for instruction in instructions.iter() {
-                    let match_variable = instruction.exec(interpreter)?;
-                    if match_variable == *variable {
-                        return Ok(true);
-                    }
+    pub fn is_covering_variant(&self, checked_type: &EnumType, checked_variant: usize) -> bool {
+        match &self.kind {
+            MatchArmKind::Variant {
+                enum_type, variant, ..
+            } if self.guard.is_none() => {
+                **enum_type == *checked_type && *variant == checked_variant
+            }
+            _ => false,
+        }
+    }
+    /// Returns members of var_type matched by type arm, None for other arms
+    pub fn matched_type(&self, var_type: &Type) -> Option<Type> {
+        let MatchArmKind::Type {
+            var_type: arm_type, ..
+        } = &self.kind
+        else {
+            return None;
+        };
+        let members: Box<dyn Iterator<Item = &Type>> = match var_type {
+            Type::Multi(types) => Box::new(types.iter()),
+            var_type => Box::new(std::iter::once(var_type)),
+        };
+        Some(
+            members
+                .filter(|member| member.matches(arm_type) || arm_type.matches(member))
+                .cloned()
+                .reduce(Type::concat)
+                .unwrap_or(Type::Never),
+        )
+    }
+    /// Returns enum variant matched by variant arm
+    pub fn variant(&self) -> Option<(&EnumType, usize)> {
+        match &self.kind {
+            MatchArmKind::Variant {
+                enum_type, variant, ..
+            } => Some((enum_type, *variant)),
+            _ => None,
+        }
+    }
+    /// Compiles arm executed with matched value on the top of the stack,
+    /// jumps to the next arm if the value doesn't match. Returns position of jump to the end of match
+    pub fn compile(&self, compiler: &mut Compiler) -> usize {
+        let mut otherwise = Vec::new();
+        match &self.kind {
+            MatchArmKind::Value(values) => {
+                let matched: Vec<usize> = values
+                    .iter()
+                    .map(|value| {
+                        compiler.compile(value);
+                        compiler.emit(Op::JumpIfEqual(0))
+                    })
+                    .collect();
+                otherwise.push(compiler.emit(Op::Jump(0)));
+                for jump in matched {
+                    compiler.patch(jump);
                 }
-                false
             }
+            MatchArmKind::Other => (),
+            kind => otherwise.push(compiler.emit(Op::MatchArm {
+                arm: kind.clone(),
+                otherwise: 0,
+            })),
+        }
+        if let Some(guard) = &self.guard {
+            compiler.compile(guard);
+            otherwise.push(compiler.emit(Op::JumpIfFalse(0)));
+        }
+        compiler.emit(Op::Pop);
+        compiler.compile(&self.instruction);
+        let end = compiler.emit(Op::Jump(0));
+        for jump in otherwise {
+            compiler.patch(jump);
+        }
+        end
+    }
+    pub fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Self, ExecError> {
+        let kind = self.kind.recreate(local_variables)?;
+        let mut local_variables = local_variables.create_layer();
+        let kind = kind.insert_local_variables(&mut local_variables);
+        let guard = self
+            .guard
+            .as_ref()
+            .map(|guard| guard.recreate(&mut local_variables))
+            .transpose()?;
+        let instruction = self.instruction.recreate(&mut local_variables)?;
+        Ok(Self {
+            kind,
+            guard,
+            instruction,
         })
     }
-    pub fn exec(&self, variable: Variable, interpreter: &mut Interpreter) -> ExecResult {
-        match self {
-            MatchArm::Type {
-                ident, instruction, ..
-            } => {
-                let mut interpreter = interpreter.create_layer();
-                interpreter.insert(ident.clone(), variable);
-                instruction.exec(&mut interpreter)
-            }
-            MatchArm::Other(instruction) | MatchArm::Value(_, instruction) => {
-                instruction.exec(interpreter)
+}
+
+impl MatchArmKind {
+    fn new_variant<'a>(
+        mut inner: impl Iterator<Item = Pair<'a, Rule>>,
+        var_type: &Type,
+        local_variables: &mut LocalVariables,
+    ) -> Result<Self, Error> {
+        let pair = inner.next().unwrap();
+        let field_access = inner.next().unwrap();
+        let pair_idents = inner.next();
+        let Some(Type::Enum(enum_type)) = local_variables.get_type(pair.as_str()) else {
+            if pair_idents.is_some() {
+                return Err(Error::NotAnEnum(pair.as_str().into()));
             }
+            let value = InstructionWithStr::new_ident(pair, local_variables)?;
+            let value = InstructionWithStr::create_postfix(field_access, value, local_variables)?;
+            return Ok(Self::Value([value].into()));
+        };
+        let variant_name = field_access.into_inner().next().unwrap().as_str();
+        let Some((variant, variant_type)) = enum_type.variant(variant_name) else {
+            return Err(Error::NoVariant(
+                enum_type.name.clone(),
+                variant_name.into(),
+            ));
+        };
+        if !Type::Enum(enum_type.clone()).matches(var_type) {
+            return Err(Error::PatternNeverMatches(
+                format!("{}.{variant_name}", enum_type.name).into(),
+                var_type.clone(),
+            ));
         }
+        let idents: Arc<[(Arc<str>, Slot)]> = pair_idents.map_or_else(
+            || Arc::from([]),
+            |pair| {
+                pair.into_inner()
+                    .map(|pair| (pair.as_str().into(), 0))
+                    .collect()
+            },
+        );
+        if !idents.is_empty() && idents.len() != variant_type.payload.len() {
+            return Err(Error::WrongLength {
+                ins: format!("{}.{variant_name}", enum_type.name).into(),
+                len: variant_type.payload.len(),
+                idents_len: idents.len(),
+            });
+        }
+        Ok(Self::Variant {
+            enum_type,
+            variant,
+            idents,
+        })
     }
-    pub fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Self, ExecError> {
-        Ok(match self {
+    /// Inserts idents bound by arm into local_variables and returns arm binding their slots
+    fn insert_local_variables(&self, local_variables: &mut LocalVariables) -> Self {
+        match self {
             Self::Type {
-                ident,
-                var_type,
-                instruction,
+                ident, var_type, ..
+            } => Self::Type {
+                ident: ident.clone(),
+                var_type: var_type.clone(),
+                slot: local_variables.insert(ident.clone(), LocalVariable::Other(var_type.clone())),
+            },
+            Self::Variant {
+                enum_type,
+                variant,
+                idents,
             } => {
-                let mut local_variables = local_variables.create_layer();
-                local_variables.insert(ident.clone(), LocalVariable::Other(var_type.clone()));
-                let instruction = instruction.recreate(&mut local_variables)?;
-                Self::Type {
-                    ident: ident.clone(),
-                    var_type: var_type.clone(),
-                    instruction,
+                let payload = enum_type.variants[*variant].payload.iter();
+                let idents = idents
+                    .iter()
+                    .zip(payload)
+                    .map(|((ident, _), var_type)| {
+                        let variable = LocalVariable::Other(var_type.clone());
+                        (
+                            ident.clone(),
+                            local_variables.insert(ident.clone(), variable),
+                        )
+                    })
+                    .collect();
+                Self::Variant {
+                    enum_type: enum_type.clone(),
+                    variant: *variant,
+                    idents,
                 }
             }
-            Self::Value(values, instruction) => {
-                let values = recreate_instructions(values, local_variables)?;
-                let instruction = instruction.recreate(local_variables)?;
-                Self::Value(values, instruction)
+            Self::Pattern(pattern) => {
+                Self::Pattern(pattern.insert_local_variables(local_variables))
             }
-            MatchArm::Other(instruction) => Self::Other(instruction.recreate(local_variables)?),
+            Self::Value(_) | Self::Other => self.clone(),
+        }
+    }
+    /// Returns true if variable matches arm. Values of value arm are compared by the vm
+    pub fn matches(&self, variable: &Variable) -> bool {
+        match self {
+            Self::Other => true,
+            Self::Type { var_type, .. } => variable.as_type().matches(var_type),
+            Self::Pattern(pattern) => pattern.matches(variable),
+            Self::Variant {
+                enum_type, variant, ..
+            } => matches!(variable, Variable::Enum(value)
+                if value.enum_type == *enum_type && value.variant == *variant),
+            Self::Value(_) => unreachable!("Tried to match value arm without the vm"),
+        }
+    }
+    /// Stores parts of variable in slots of idents bound by arm
+    pub fn bind(&self, variable: &Variable, interpreter: &mut Interpreter) {
+        match (self, variable) {
+            (Self::Type { slot, .. }, variable) => {
+                interpreter.set_local(*slot, variable.clone());
+            }
+            (Self::Variant { idents, .. }, Variable::Enum(value)) => {
+                for ((_, slot), value) in idents.iter().zip(value.values.iter()) {
+                    interpreter.set_local(*slot, value.clone());
+                }
+            }
+            (Self::Pattern(pattern), variable) => pattern.bind(variable, interpreter),
+            _ => (),
+        }
+    }
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Self, ExecError> {
+        Ok(match self {
+            Self::Value(values) => Self::Value(recreate_instructions(values, local_variables)?),
+            kind => kind.clone(),
         })
     }
 }
 
 impl ReturnType for MatchArm {
     fn return_type(&self) -> Type {
-        match self {
-            MatchArm::Type { instruction, .. }
-            | MatchArm::Value(_, instruction)
-            | MatchArm::Other(instruction) => instruction.return_type(),
-        }
+        self.instruction.return_type()
     }
 }
@@ -2,4 +2,6 @@ mod if_else;
 mod r#match;
 mod match_arm;
 mod set_if_else;
-pub use {if_else::IfElse, r#match::Match, set_if_else::SetIfElse};
+mod try_catch;
+pub(crate) use match_arm::MatchArmKind;
+pub use {if_else::IfElse, r#match::Match, set_if_else::SetIfElse, try_catch::TryCatch};
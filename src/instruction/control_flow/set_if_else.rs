@@ -1,11 +1,12 @@
+use super::MatchArmKind;
 use crate::{
     Error, ExecError,
     instruction::{
-        Exec, ExecResult, Instruction, InstructionWithStr, Recreate,
-        local_variable::{LocalVariable, LocalVariables},
+        Compile, Instruction, InstructionWithStr, Recreate,
+        local_variable::{LocalVariable, LocalVariables, Slot},
     },
-    interpreter::Interpreter,
-    variable::{ReturnType, Type, Typed, Variable},
+    variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -15,6 +16,7 @@ use std::sync::Arc;
 pub struct SetIfElse {
     ident: Arc<str>,
     var_type: Type,
+    slot: Slot,
     expression: InstructionWithStr,
     if_match: InstructionWithStr,
     pub else_instruction: InstructionWithStr,
@@ -25,14 +27,15 @@ impl SetIfElse {
         let mut inner = pair.into_inner();
         let ident: Arc<str> = inner.next().unwrap().as_str().into();
         let pair = inner.next().unwrap();
-        let var_type = Type::from(pair);
+        let var_type = local_variables.create_type(pair)?;
         let pair = inner.next().unwrap();
         let expression = InstructionWithStr::new(pair, local_variables)?;
         let pair = inner.next().unwrap();
-        let if_match = {
+        let (slot, if_match) = {
             let mut local_variables = local_variables.create_layer();
-            local_variables.insert(ident.clone(), LocalVariable::Other(var_type.clone()));
-            InstructionWithStr::new(pair, &mut local_variables)?
+            let slot =
+                local_variables.insert(ident.clone(), LocalVariable::Other(var_type.clone()));
+            (slot, InstructionWithStr::new(pair, &mut local_variables)?)
         };
         let else_instruction = inner
             .next()
@@ -41,6 +44,7 @@ impl SetIfElse {
         Ok(Self {
             ident,
             var_type,
+            slot,
             expression,
             if_match,
             else_instruction,
@@ -55,34 +59,43 @@ impl SetIfElse {
     }
 }
 
-impl Exec for SetIfElse {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let expression_result = self.expression.exec(interpreter)?;
-        let result_type = expression_result.as_type();
-        if !result_type.matches(&self.var_type) {
-            return self.else_instruction.exec(interpreter);
-        }
-        let mut interpreter = interpreter.create_layer();
-        interpreter.insert(self.ident.clone(), expression_result);
-        self.if_match.exec(&mut interpreter)
+impl Compile for SetIfElse {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.expression);
+        let otherwise = compiler.emit(Op::MatchArm {
+            arm: MatchArmKind::Type {
+                ident: self.ident.clone(),
+                var_type: self.var_type.clone(),
+                slot: self.slot,
+            },
+            otherwise: 0,
+        });
+        compiler.emit(Op::Pop);
+        compiler.compile(&self.if_match);
+        let end = compiler.emit(Op::Jump(0));
+        compiler.patch(otherwise);
+        compiler.emit(Op::Pop);
+        compiler.compile(&self.else_instruction);
+        compiler.patch(end);
     }
 }
 
 impl Recreate for SetIfElse {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
         let expression = self.expression.recreate(local_variables)?;
-        let if_match = {
+        let (slot, if_match) = {
             let mut local_variables = local_variables.create_layer();
-            local_variables.insert(
+            let slot = local_variables.insert(
                 self.ident.clone(),
                 LocalVariable::Other(self.var_type.clone()),
             );
-            self.if_match.recreate(&mut local_variables)?
+            (slot, self.if_match.recreate(&mut local_variables)?)
         };
         let else_instruction = self.else_instruction.recreate(local_variables)?;
         Ok(Self {
             ident: self.ident.clone(),
             var_type: self.var_type.clone(),
+            slot,
             expression,
             if_match,
             else_instruction,
@@ -0,0 +1,188 @@
+use crate::{
+    Error, ExecError,
+    instruction::{
+        Compile, Instruction, InstructionWithStr, Recreate,
+        local_variable::{LocalVariable, LocalVariables, Slot},
+    },
+    variable::{ReturnType, Type},
+    vm::{Compiler, Op},
+};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct TryCatch {
+    try_block: InstructionWithStr,
+    ident: Arc<str>,
+    var_type: Type,
+    slot: Slot,
+    catch_block: InstructionWithStr,
+}
+
+impl TryCatch {
+    pub fn create_instruction(
+        pair: Pair<Rule>,
+        local_variables: &mut LocalVariables,
+    ) -> Result<Instruction, Error> {
+        let mut inner = pair.into_inner();
+        let try_block = InstructionWithStr::new(inner.next().unwrap(), local_variables)?;
+        let ident: Arc<str> = inner.next().unwrap().as_str().into();
+        let var_type = local_variables.create_type(inner.next().unwrap())?;
+        let mut local_variables = local_variables.create_layer();
+        let slot = local_variables.insert(ident.clone(), LocalVariable::Other(var_type.clone()));
+        let catch_block = InstructionWithStr::new(inner.next().unwrap(), &mut local_variables)?;
+        Ok(Self {
+            try_block,
+            ident,
+            var_type,
+            slot,
+            catch_block,
+        }
+        .into())
+    }
+}
+
+impl Compile for TryCatch {
+    fn compile(&self, compiler: &mut Compiler) {
+        let catch = compiler.emit(Op::Try {
+            var_type: self.var_type.clone(),
+            slot: self.slot,
+            catch: 0,
+        });
+        // errors of calls in tail position have to be caught before the frame is released
+        compiler.without_tail_calls(|compiler| compiler.compile(&self.try_block));
+        compiler.emit(Op::EndTry);
+        let end = compiler.emit(Op::Jump(0));
+        compiler.patch(catch);
+        compiler.compile(&self.catch_block);
+        compiler.patch(end);
+    }
+}
+
+impl Recreate for TryCatch {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let try_block = self.try_block.recreate(local_variables)?;
+        let mut local_variables = local_variables.create_layer();
+        let slot = local_variables.insert(
+            self.ident.clone(),
+            LocalVariable::Other(self.var_type.clone()),
+        );
+        let catch_block = self.catch_block.recreate(&mut local_variables)?;
+        Ok(Self {
+            try_block,
+            ident: self.ident.clone(),
+            var_type: self.var_type.clone(),
+            slot,
+            catch_block,
+        }
+        .into())
+    }
+}
+
+impl ReturnType for TryCatch {
+    fn return_type(&self) -> Type {
+        self.try_block.return_type() | self.catch_block.return_type()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, ExecError, Interpreter,
+        function::{Function, Params},
+        variable::Variable,
+    };
+    use simplesl_macros::{var, var_type};
+    use std::sync::Arc;
+
+    #[test]
+    fn try_catch() {
+        assert_eq!(
+            parse_and_exec("try { 5 } catch e: string { 7 }"),
+            Ok(var!(5))
+        );
+        assert_eq!(
+            parse_and_exec(r#"try { throw "error" } catch e: string { e + "!" }"#),
+            Ok(var!("error!"))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "f := (a: int) -> int {
+                    if a < 0 throw struct{error_code := 1, msg := \"negative\"}
+                    return a
+                }
+                try { f(-4) } catch e: struct{error_code: int} { e.error_code }"
+            ),
+            Ok(var!(1))
+        );
+        assert_eq!(
+            parse_and_exec("try { throw 5 } catch e: string { e }"),
+            Err(Error::User(var!(5)))
+        );
+        assert_eq!(
+            parse_and_exec("try { 5 / 0 } catch e: any { 0 }"),
+            Err(Error::ZeroDivision)
+        );
+        assert_eq!(parse_and_exec("throw 4.5"), Err(Error::User(var!(4.5))));
+    }
+
+    #[test]
+    fn throw_from_host() {
+        let mut interpreter = Interpreter::without_stdlib();
+        let fail = Function::new(
+            Params(Arc::new([])),
+            |_| Err(ExecError::User(var!("host error"))),
+            var_type!(!),
+        );
+        interpreter.insert("fail".into(), fail.into());
+        let result = Code::parse(&interpreter, "try { fail() } catch e: string { e }")
+            .unwrap()
+            .exec();
+        assert_eq!(result, Ok(var!("host error")));
+    }
+
+    #[test]
+    fn nested_try_catch() {
+        assert_eq!(
+            parse_and_exec(
+                "try {
+                    try { throw 1 } catch e: string { 0 }
+                } catch e: int { e + 10 }"
+            ),
+            Ok(var!(11))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "count := mut 0
+                for i in 0..5 {
+                    try {
+                        if i == 3 { break }
+                        throw i
+                    } catch e: int { count += e }
+                }
+                try { throw 5 } catch e: int { *count + e }"
+            ),
+            Ok(var!(8))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "f := (n: int) -> int {
+                    try {
+                        if n == 0 throw \"zero\"
+                        return f(n - 1)
+                    } catch e: string {
+                        return 7
+                    }
+                }
+                f(3)"
+            ),
+            Ok(var!(7))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
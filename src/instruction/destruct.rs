@@ -0,0 +1,66 @@
+use super::{
+    Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    pattern::Pattern,
+};
+use crate::{
+    Error, ExecError,
+    variable::{ReturnType, Type},
+    vm::{Compiler, Op},
+};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+
+#[derive(Debug)]
+pub struct Destruct {
+    pub pattern: Pattern,
+    pub instruction: InstructionWithStr,
+}
+
+impl Destruct {
+    pub fn create_instruction(
+        pair: Pair<Rule>,
+        local_variables: &mut LocalVariables,
+    ) -> Result<Instruction, Error> {
+        let mut inner = pair.into_inner();
+        let pattern_pair = inner.next().unwrap();
+        let pair = inner.next().unwrap();
+        let instruction = InstructionWithStr::new(pair, local_variables)?;
+        let return_type = instruction.return_type();
+        let str = pattern_pair.as_str().into();
+        let pattern = Pattern::create(pattern_pair, &return_type, local_variables)?;
+        if !pattern.is_covering_type(&return_type) {
+            return Err(Error::PatternNotCovering(str, return_type));
+        }
+        let pattern = pattern.insert_local_variables(local_variables);
+        Ok(Self {
+            pattern,
+            instruction,
+        }
+        .into())
+    }
+}
+
+impl Compile for Destruct {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.instruction);
+        compiler.emit(Op::Bind(self.pattern.clone()));
+    }
+}
+
+impl Recreate for Destruct {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let instruction = self.instruction.recreate(local_variables)?;
+        let pattern = self.pattern.insert_local_variables(local_variables);
+        Ok(Self {
+            pattern,
+            instruction,
+        }
+        .into())
+    }
+}
+
+impl ReturnType for Destruct {
+    fn return_type(&self) -> Type {
+        self.instruction.return_type()
+    }
+}
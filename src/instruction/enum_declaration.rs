@@ -0,0 +1,288 @@
+use super::{
+    Compile, Instruction, InstructionWithStr, Recreate,
+    local_variable::{LocalVariable, LocalVariables},
+    recreate_instructions,
+    set::Set,
+    unary_operation::UnaryOperation,
+};
+use crate::{
+    Error, ExecError,
+    function::{Body, Function, Param, Params},
+    interpreter::VariableMap,
+    unary_operator::UnaryOperator,
+    variable::{Enum, EnumType, ReturnType, Type, Variable, VariantType},
+    vm::{Compiler, Op},
+};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+use std::sync::Arc;
+
+/// Declares enum type and variable of the same name holding constructors of its variants
+pub fn create(
+    pair: Pair<Rule>,
+    local_variables: &mut LocalVariables,
+) -> Result<Instruction, Error> {
+    let mut inner = pair.into_inner();
+    let name: Arc<str> = inner.next().unwrap().as_str().into();
+    let variants = inner
+        .map(|pair| {
+            let mut inner = pair.into_inner();
+            let name = inner.next().unwrap().as_str().into();
+            let payload = inner
+                .map(|pair| local_variables.create_type(pair))
+                .collect::<Result<_, Error>>()?;
+            Ok(VariantType { name, payload })
+        })
+        .collect::<Result<Arc<[_]>, Error>>()?;
+    if variants.is_empty() {
+        return Err(Error::EmptyEnum(name));
+    }
+    let enum_type = Arc::new(EnumType {
+        name: name.clone(),
+        variants,
+    });
+    local_variables.insert_type(name.clone(), enum_type.clone().into());
+    let constructors: VariableMap = enum_type
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(variant, variant_type)| {
+            let constructor = constructor(&enum_type, variant, variant_type);
+            (variant_type.name.clone(), constructor)
+        })
+        .collect();
+    let instruction = Variable::Struct(constructors.into()).into();
+    Ok(Set::new(name, instruction, local_variables).into())
+}
+
+fn constructor(enum_type: &Arc<EnumType>, variant: usize, variant_type: &VariantType) -> Variable {
+    if variant_type.payload.is_empty() {
+        return Enum {
+            enum_type: enum_type.clone(),
+            variant,
+            values: Arc::new([]),
+        }
+        .into();
+    }
+    let params = variant_type
+        .payload
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, var_type)| Param {
+            name: format!("p{i}").into(),
+            var_type,
+        })
+        .collect::<Params>();
+    let values = params
+        .iter()
+        .enumerate()
+        .map(|(slot, Param { name, var_type })| InstructionWithStr {
+            instruction: Instruction::LocalVariable(
+                name.clone(),
+                LocalVariable::Other(var_type.clone()),
+                slot,
+            ),
+            str: name.clone(),
+            location: None,
+        })
+        .collect();
+    let str = format!("{}.{}", enum_type.name, variant_type.name);
+    let instruction = UnaryOperation {
+        instruction: EnumVariant {
+            enum_type: enum_type.clone(),
+            variant,
+            values,
+        }
+        .into(),
+        op: UnaryOperator::Return,
+    }
+    .into();
+    Function {
+        ident: Some(str.clone().into()),
+        params,
+        body: Body::lang(
+            [InstructionWithStr {
+                instruction,
+                str: format!("return {str}").into(),
+                location: None,
+            }]
+            .into(),
+            false,
+        ),
+        return_type: Type::Enum(enum_type.clone()),
+        captured: Arc::from([]),
+    }
+    .into()
+}
+
+/// Creates value of enum variant from its payload
+#[derive(Debug)]
+pub struct EnumVariant {
+    enum_type: Arc<EnumType>,
+    variant: usize,
+    values: Arc<[InstructionWithStr]>,
+}
+
+impl Compile for EnumVariant {
+    fn compile(&self, compiler: &mut Compiler) {
+        for value in self.values.iter() {
+            compiler.compile(value);
+        }
+        compiler.emit(Op::EnumVariant {
+            enum_type: self.enum_type.clone(),
+            variant: self.variant,
+            len: self.values.len(),
+        });
+    }
+}
+
+impl Recreate for EnumVariant {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let values = recreate_instructions(&self.values, local_variables)?;
+        Ok(Self {
+            enum_type: self.enum_type.clone(),
+            variant: self.variant,
+            values,
+        }
+        .into())
+    }
+}
+
+impl ReturnType for EnumVariant {
+    fn return_type(&self) -> Type {
+        Type::Enum(self.enum_type.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Code, Error, Interpreter, variable::Variable};
+
+    #[test]
+    fn enum_declaration() {
+        assert_eq!(
+            parse_and_exec(
+                "enum Shape { Circle(float), Rect(float, float), Empty }
+                area := (shape: Shape) -> float {
+                    return match shape {
+                        Shape.Circle(r) => 3.0 * r * r,
+                        Shape.Rect(w, h) => w * h,
+                        Shape.Empty => 0.0,
+                    }
+                }
+                (area(Shape.Circle(2.0)), area(Shape.Rect(2.0, 3.5)), area(Shape.Empty))"
+            )
+            .map(|var| var.to_string()),
+            Ok("(12.0, 7.0, 0.0)".into())
+        );
+        assert_eq!(
+            parse_and_exec("enum Shape { Circle(float), Empty }\n[Shape.Circle(1.5), Shape.Empty]")
+                .map(|var| var.to_string()),
+            Ok("[Shape.Circle(1.5), Shape.Empty]".into())
+        );
+        assert_eq!(
+            parse_and_exec(
+                "enum Shape { Circle(float), Empty }
+                Shape.Circle(1.5) == Shape.Circle(1.5) && Shape.Circle(1.5) != Shape.Empty"
+            ),
+            Ok(Variable::Bool(true))
+        );
+        assert!(matches!(
+            parse_and_exec(
+                "enum Shape { Circle(float), Empty }
+                match Shape.Empty {
+                    Shape.Circle(r) => r,
+                }"
+            ),
+            Err(Error::VariantsNotCovered(var_type, variants))
+                if var_type.to_string() == "Shape" && *variants == ["Shape.Empty".into()]
+        ));
+        assert_eq!(
+            parse_and_exec(
+                "enum Shape { Circle(float), Empty }
+                match Shape.Empty {
+                    Shape.Circle => 1,
+                    => 0,
+                }"
+            ),
+            Ok(Variable::Int(0))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "enum Shape { Circle(float), Empty }
+                match Shape.Empty {
+                    Shape.Square(a) => a,
+                    => 0.0,
+                }"
+            ),
+            Err(Error::NoVariant("Shape".into(), "Square".into()))
+        );
+        assert!(matches!(
+            parse_and_exec(
+                "enum Shape { Circle(float), Empty }
+                enum Color { Red, Green }
+                match Shape.Empty {
+                    Color.Red => 1,
+                    => 0,
+                }"
+            ),
+            Err(Error::PatternNeverMatches(pattern, _)) if &*pattern == "Color.Red"
+        ));
+        assert_eq!(
+            parse_and_exec(
+                "enum Shape { Circle(float), Empty }
+                match Shape.Empty {
+                    Shape.Circle(a, b) => a,
+                    => 0.0,
+                }"
+            ),
+            Err(Error::WrongLength {
+                ins: "Shape.Circle".into(),
+                len: 1,
+                idents_len: 2
+            })
+        );
+        assert_eq!(
+            parse_and_exec(
+                "s := struct { a := 5 }
+                match 5 {
+                    s.a => true,
+                    => false,
+                }"
+            ),
+            Ok(Variable::Bool(true))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "s := struct { a := 5 }
+                match 5 {
+                    s.a(x) => true,
+                    => false,
+                }"
+            ),
+            Err(Error::NotAnEnum("s".into()))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "enum E {}
+                f := (e: E) -> int { return match e { } }"
+            ),
+            Err(Error::EmptyEnum("E".into()))
+        );
+        assert!(matches!(
+            parse_and_exec(
+                "enum A { X }
+                enum B { X }
+                f := (a: A) -> int { return 0 }
+                f(B.X)"
+            ),
+            Err(Error::WrongArgument { .. })
+        ));
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
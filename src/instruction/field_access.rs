@@ -1,40 +1,49 @@
 use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    method::Method,
+};
+use crate::{
+    Error, ExecError,
+    variable::ReturnType,
+    vm::{Compiler, Op},
 };
-use crate::{Error, ExecError, Interpreter, variable::ReturnType};
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct FieldAccess {
-    var: InstructionWithStr,
-    ident: Arc<str>,
+    pub var: InstructionWithStr,
+    pub ident: Arc<str>,
 }
 
 impl FieldAccess {
     pub fn create_instruction(
         tuple: InstructionWithStr,
         op: Pair<Rule>,
+        local_variables: &LocalVariables,
     ) -> Result<Instruction, Error> {
         let return_type = tuple.return_type();
-        if !return_type.is_struct() {
-            return Err(Error::CannotFieldAccess(tuple.str, return_type));
-        }
         let pair = op.into_inner().next().unwrap();
         let ident = pair.as_str();
-        if !return_type.has_field(ident) {
-            return Err(Error::NoField {
-                struct_ident: tuple.str,
-                field_ident: ident.into(),
-                struct_type: return_type,
-            });
+        if return_type.has_field(ident) {
+            return Ok(Self {
+                var: tuple,
+                ident: ident.into(),
+            }
+            .into());
         }
-        Ok(Self {
-            var: tuple,
-            ident: ident.into(),
+        if let Some(method) = Method::create(&tuple, ident, local_variables) {
+            return Ok(method.into());
+        }
+        if !return_type.is_struct() {
+            return Err(Error::CannotFieldAccess(tuple.str, return_type));
         }
-        .into())
+        Err(Error::NoField {
+            struct_ident: tuple.str,
+            field_ident: ident.into(),
+            struct_type: return_type,
+        })
     }
 }
 
@@ -52,9 +61,9 @@ impl Recreate for FieldAccess {
     }
 }
 
-impl Exec for FieldAccess {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let var = self.var.exec(interpreter)?.into_struct().unwrap();
-        Ok(var.get(&self.ident).unwrap().clone())
+impl Compile for FieldAccess {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.var);
+        compiler.emit(Op::FieldAccess(self.ident.clone()));
     }
 }
@@ -1,13 +1,17 @@
 use crate::{
     self as simplesl, Error, ExecError,
-    function::{Body, Function, Param, Params},
+    function::{Closure, Params},
+    instruction::function::{
+        compile_closure, contains_yield, create_body, declare_type_params, type_params,
+    },
     instruction::{
-        Exec, ExecResult, Instruction, InstructionWithStr, Recreate,
-        local_variable::{FunctionInfo, LocalVariableMap, LocalVariables},
-        recreate_instructions,
+        Compile, Instruction, InstructionWithStr, Recreate,
+        local_variable::{FunctionInfo, LocalVariables},
+        recreate_sequence,
     },
-    interpreter::Interpreter,
+    unary_operator::UnaryOperator,
     variable::{ReturnType, Type},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_macros::var_type;
@@ -17,8 +21,12 @@ use std::sync::Arc;
 #[derive(Clone, Debug)]
 pub struct AnonymousFunction {
     pub params: Params,
-    body: Arc<[InstructionWithStr]>,
-    return_type: Type,
+    pub(crate) body: Arc<[InstructionWithStr]>,
+    pub(crate) return_type: Type,
+    pub(crate) generator: bool,
+    pub(crate) closure: Arc<Closure>,
+    /// Instructions loading values captured by the function
+    pub(crate) captures: Arc<[Instruction]>,
 }
 
 impl AnonymousFunction {
@@ -27,66 +35,111 @@ impl AnonymousFunction {
         local_variables: &LocalVariables,
     ) -> Result<Instruction, Error> {
         let mut inner = pair.into_inner();
+        let type_params = type_params(&mut inner);
+        let generics_layer = local_variables.create_layer();
+        declare_type_params(&generics_layer, &type_params);
         let params_pair = inner.next().unwrap();
-        let params = Params(params_pair.into_inner().map(Param::from).collect());
+        let params = Params::create(params_pair, &generics_layer)?;
         let return_type = if matches!(inner.peek(), Some(pair)
             if pair.as_rule() == Rule::return_type_decl)
         {
-            Type::from(inner.next().unwrap().into_inner().next().unwrap())
+            Some(generics_layer.create_type(inner.next().unwrap().into_inner().next().unwrap())?)
         } else {
-            Type::Void
+            None
         };
-        let mut local_variables = local_variables.function_layer(
-            LocalVariableMap::from(params.clone()),
-            FunctionInfo::new(None, return_type.clone()),
+        let generator = contains_yield(inner.clone());
+        let mut local_variables = generics_layer.function_layer(
+            params.clone(),
+            FunctionInfo::new(None, return_type).generator(generator),
         );
-        let body = local_variables.create_instructions(inner)?;
-        if !Type::Void.matches(&return_type)
-            && !body
-                .iter()
-                .map(ReturnType::return_type)
-                .any(|var_type| var_type == Type::Never)
-        {
-            return Err(Error::MissingReturn {
-                function_name: None,
-                return_type,
-            });
-        }
+        let (body, return_type) = create_body(inner, &mut local_variables)?;
+        let captures = local_variables.function().unwrap().captures();
+        drop(local_variables);
+        Ok(Self::new(
+            params,
+            body,
+            return_type,
+            generator,
+            captures,
+            &generics_layer,
+        )?
+        .into())
+    }
+
+    /// Creates function literal compiling its body
+    fn new(
+        params: Params,
+        body: Arc<[InstructionWithStr]>,
+        return_type: Type,
+        generator: bool,
+        captures: Vec<Arc<str>>,
+        local_variables: &LocalVariables,
+    ) -> Result<Self, ExecError> {
+        let (closure, captures) = compile_closure(
+            None,
+            &params,
+            &body,
+            &return_type,
+            generator,
+            captures,
+            local_variables,
+        )?;
         Ok(Self {
             params,
             body,
             return_type,
+            generator,
+            closure,
+            captures,
+        })
+    }
+}
+
+impl AnonymousFunction {
+    /// Returns expression returned by function if its body consists only of returning it
+    pub(crate) fn returned_expression(&self) -> Option<&Instruction> {
+        if self.generator {
+            return None;
         }
-        .into())
+        let [
+            InstructionWithStr {
+                instruction: Instruction::UnaryOperation(operation),
+                ..
+            },
+        ] = &*self.body
+        else {
+            return None;
+        };
+        (operation.op == UnaryOperator::Return).then_some(&operation.instruction)
     }
 }
 
-impl Exec for AnonymousFunction {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let mut fn_local_variables = LocalVariables::from_params(self.params.clone(), interpreter);
-        let body = recreate_instructions(&self.body, &mut fn_local_variables)?;
-        Ok(Function {
-            ident: None,
-            params: self.params.clone(),
-            body: Body::Lang(body),
-            return_type: self.return_type.clone(),
+impl Compile for AnonymousFunction {
+    fn compile(&self, compiler: &mut Compiler) {
+        for capture in self.captures.iter() {
+            capture.compile(compiler);
         }
-        .into())
+        compiler.emit(Op::Closure(self.closure.clone()));
     }
 }
 
 impl Recreate for AnonymousFunction {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
-        let mut local_variables = local_variables.function_layer(
-            self.params.clone().into(),
-            FunctionInfo::new(None, self.return_type.clone()),
+        let mut fn_local_variables = local_variables.function_layer(
+            self.params.clone(),
+            FunctionInfo::new(None, Some(self.return_type.clone())).generator(self.generator),
         );
-        let body = recreate_instructions(&self.body, &mut local_variables)?;
-        Ok(Self {
-            params: self.params.clone(),
+        let body = recreate_sequence(&self.body, &mut fn_local_variables)?;
+        let captures = fn_local_variables.function().unwrap().captures();
+        drop(fn_local_variables);
+        Ok(Self::new(
+            self.params.clone(),
             body,
-            return_type: self.return_type.clone(),
-        }
+            self.return_type.clone(),
+            self.generator,
+            captures,
+            local_variables,
+        )?
         .into())
     }
 }
@@ -5,12 +5,10 @@ use crate::{
         BinOperation, Instruction, InstructionWithStr,
         function::AnonymousFunction,
         local_variable::{LocalVariable, LocalVariables},
-        set::Set,
         tuple::Tuple,
-        unary_operation::UnaryOperation,
     },
-    unary_operator::UnaryOperator,
-    variable::{ReturnType, Typed, Variable},
+    interpreter::CallDepth,
+    variable::{Generics, ReturnType, Type, Typed, Variable, infer_generics},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -29,12 +27,16 @@ pub fn create_instruction(
         Instruction::Variable(Variable::Function(function2)) => {
             check_args_with_params(&function.str, &function2.params, &args)?;
         }
-        Instruction::LocalVariable(ident, LocalVariable::Function(params, _)) => {
+        Instruction::LocalVariable(ident, LocalVariable::Function(params, _), _) => {
             check_args_with_params(ident, params, &args)?;
         }
         Instruction::AnonymousFunction(AnonymousFunction { params, .. }) => {
             check_args_with_params(&function.str, params, &args)?;
         }
+        Instruction::Method(method) => {
+            check_args_with_params(&function.str, &method.params, &args)?;
+            return Ok(method.call(args));
+        }
         _ => {
             let f_type = function.return_type();
             if !f_type.is_function() {
@@ -67,7 +69,7 @@ pub fn create_from_variables(
     ident: Arc<str>,
     function: Arc<Function>,
     args: Vec<Variable>,
-) -> Result<Arc<[InstructionWithStr]>, Error> {
+) -> Result<Instruction, Error> {
     if function.params.len() != args.len() {
         return Err(Error::WrongNumberOfArguments(
             ident.clone(),
@@ -76,7 +78,7 @@ pub fn create_from_variables(
     }
     for (arg, param) in zip(args.iter(), function.params.iter()) {
         let arg_type = arg.as_type();
-        if !arg_type.matches(&param.var_type) {
+        if !param.accepts(&arg_type) {
             return Err(Error::WrongArgument {
                 function: ident.clone(),
                 param: param.clone(),
@@ -85,44 +87,13 @@ pub fn create_from_variables(
             });
         }
     }
-    let instruction: Instruction = Variable::Function(function.clone()).into();
-    let ident = function.ident.clone().unwrap_or_else(|| "$".into());
-    let str = format!("{ident} = {function}").into();
-    let rec = InstructionWithStr {
-        instruction: Set {
-            ident: ident.clone(),
-            instruction: InstructionWithStr {
-                instruction: instruction.clone(),
-                str: format!("{function}").into(),
-            },
-        }
-        .into(),
-        str,
-    };
-    let call = InstructionWithStr {
-        instruction: UnaryOperation {
-            instruction,
-            op: UnaryOperator::FunctionCall,
-        }
-        .into(),
-        str: format!("{ident}()").into(),
-    };
-    let args = args.into_iter().map(InstructionWithStr::from);
-    Ok(zip(function.params.iter(), args)
-        .map(|(param, arg)| {
-            let str = format!("{} := {}", param.name, arg.str).into();
-            InstructionWithStr {
-                instruction: Set {
-                    ident: param.name.clone(),
-                    instruction: arg,
-                }
-                .into(),
-                str,
-            }
-        })
-        .chain(std::iter::once(rec))
-        .chain(std::iter::once(call))
-        .collect())
+    let elements = args.into_iter().map(InstructionWithStr::from).collect();
+    Ok(BinOperation {
+        lhs: Variable::Function(function).into(),
+        rhs: Tuple { elements }.into(),
+        op: BinOperator::FunctionCall,
+    }
+    .into())
 }
 
 fn check_args_with_params(
@@ -133,9 +104,18 @@ fn check_args_with_params(
     if params.len() != args.len() {
         return Err(Error::WrongNumberOfArguments(ident.clone(), params.len()));
     }
+    let params = if params.is_generic() {
+        let generics = infer_generics(
+            params.iter().map(|param| &param.var_type),
+            args.iter().map(ReturnType::return_type),
+        );
+        params.substitute(&generic_getter(&generics))
+    } else {
+        params.clone()
+    };
     for (arg, param) in zip(args, params.iter()) {
         let arg_type = arg.return_type();
-        if !arg_type.matches(&param.var_type) {
+        if !param.accepts(&arg_type) {
             return Err(Error::WrongArgument {
                 function: ident.clone(),
                 param: param.clone(),
@@ -147,8 +127,92 @@ fn check_args_with_params(
     Ok(())
 }
 
-pub fn exec(function: Variable, args: Variable) -> Result<Variable, ExecError> {
+/// Returns type returned by function of type function_type called with args of type args_type.
+/// Type parameters of generic function are instantiated with types inferred from args
+pub fn return_type(function_type: &Type, args_type: Type) -> Type {
+    let return_type = function_type.return_type().unwrap();
+    if !return_type.is_generic() {
+        return return_type;
+    }
+    let params = function_type.params().unwrap_or_else(|| Arc::from([]));
+    let args = args_type.flatten_tuple().unwrap_or_else(|| Arc::from([]));
+    let generics = infer_generics(params.iter(), args.iter().cloned());
+    return_type.substitute(&generic_getter(&generics))
+}
+
+/// Returns function getting types of type parameters from generics.
+/// Type parameters that could not be inferred are treated as any
+fn generic_getter(generics: &Generics) -> impl Fn(&str) -> Option<Type> + '_ {
+    |name| Some(generics.get(name).cloned().unwrap_or(Type::Any))
+}
+
+pub fn exec(
+    function: Variable,
+    args: Variable,
+    call_depth: CallDepth,
+) -> Result<Variable, ExecError> {
     let function = function.into_function().unwrap();
     let args = args.into_tuple().unwrap();
-    function.exec_with_args(&args)
+    function.exec_with_args(&args, call_depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter,
+        function::Param,
+        variable::{Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn generic_functions() {
+        let map = "map := <T>(array: [T], f: (T) -> T) -> [T] {
+            return array~ @ f $]
+        }";
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{map}
+                map([1, 2, 3], (x: int) -> int {{ return x * 2 }})"
+            )),
+            Ok(var!([2, 4, 6]))
+        );
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{map}
+                result := map([\"a\", \"b\"], (x: string) -> string {{ return x + x }});
+                result[0]"
+            )),
+            Ok(var!("aa"))
+        );
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{map}
+                map([1, 2], (x: string) -> string {{ return x }})"
+            )),
+            Err(Error::WrongArgument {
+                function: "map".into(),
+                param: Param {
+                    name: "f".into(),
+                    var_type: var_type!((int) -> int)
+                },
+                given: "(x: string) -> string { return x }".into(),
+                given_type: var_type!((string) -> string)
+            })
+        );
+        let result = parse_and_exec(
+            "id := <T>(value: T) -> T { return value }
+            twice := <T>(value: T) -> () -> (T, T) {
+                return () -> (T, T) { return (value, value) }
+            }
+            twice(id(1.5))",
+        )
+        .unwrap();
+        assert_eq!(result.as_type(), var_type!(() -> (float, float)));
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
 }
@@ -1,15 +1,19 @@
 use crate::{
-    self as simplesl, Error, ExecError,
-    function::{Body, Function, Param, Params},
+    self as simplesl, Error, ExecError, OptLevel,
+    function::{Closure, Param, Params},
+    instruction::function::{
+        AnonymousFunction, compile_closure, contains_yield, create_body, declare_type_params,
+        type_params,
+    },
     instruction::{
-        Exec, ExecResult, Instruction, InstructionWithStr, Recreate,
-        local_variable::{FunctionInfo, LocalVariable, LocalVariableMap, LocalVariables},
-        recreate_instructions,
+        Compile, Instruction, InstructionWithStr, Recreate,
+        local_variable::{FunctionInfo, LocalVariable, LocalVariables, Slot},
+        recreate_sequence,
     },
-    interpreter::Interpreter,
     variable::{ReturnType, Type},
+    vm::{Compiler, Op},
 };
-use pest::iterators::Pair;
+use pest::iterators::{Pair, Pairs};
 use simplesl_macros::var_type;
 use simplesl_parser::Rule;
 use std::sync::Arc;
@@ -18,8 +22,13 @@ use std::sync::Arc;
 pub struct FunctionDeclaration {
     pub ident: Arc<str>,
     pub params: Params,
+    slot: Slot,
     body: Arc<[InstructionWithStr]>,
     return_type: Type,
+    generator: bool,
+    closure: Arc<Closure>,
+    /// Instructions loading values captured by the function
+    captures: Arc<[Instruction]>,
 }
 
 impl FunctionDeclaration {
@@ -30,84 +39,177 @@ impl FunctionDeclaration {
         let mut inner = pair.into_inner();
         let ident: Arc<str> = inner.next().unwrap().as_str().into();
         let mut inner = inner.next().unwrap().into_inner();
+        let type_params = type_params(&mut inner);
+        let generics_layer = local_variables.create_layer();
+        declare_type_params(&generics_layer, &type_params);
         let params_pair = inner.next().unwrap();
-        let params = Params(params_pair.into_inner().map(Param::from).collect());
+        let params = Params::create(params_pair, &generics_layer)?;
         let return_type = if matches!(inner.peek(), Some(pair)
             if pair.as_rule() == Rule::return_type_decl)
         {
-            Type::from(inner.next().unwrap().into_inner().next().unwrap())
+            Some(generics_layer.create_type(inner.next().unwrap().into_inner().next().unwrap())?)
         } else {
-            Type::Void
+            None
+        };
+        drop(generics_layer);
+        Self::create(
+            ident,
+            type_params,
+            params,
+            return_type,
+            inner,
+            local_variables,
+        )
+        .map(Instruction::from)
+    }
+
+    /// Creates declaration of function with given signature and body consisting of pairs
+    pub(crate) fn create(
+        ident: Arc<str>,
+        type_params: Arc<[Arc<str>]>,
+        params: Params,
+        return_type: Option<Type>,
+        inner: Pairs<Rule>,
+        local_variables: &mut LocalVariables,
+    ) -> Result<Self, Error> {
+        let generator = contains_yield(inner.clone());
+        let create = |assumed: Type, local_variables: &mut LocalVariables| {
+            local_variables.insert(
+                ident.clone(),
+                LocalVariable::Function(params.clone(), assumed),
+            );
+            let mut local_variables = local_variables.function_layer(
+                params.clone(),
+                FunctionInfo::new(Some(ident.clone()), return_type.clone()).generator(generator),
+            );
+            declare_type_params(&local_variables, &type_params);
+            let result = create_body(inner.clone(), &mut local_variables);
+            let function = local_variables.function().unwrap();
+            let result = result.map(|(body, return_type)| (body, return_type, function.captures()));
+            (result, function.is_recursive())
         };
-        local_variables.insert(
+        let (body, return_type, captures) = match &return_type {
+            Some(return_type) => create(return_type.clone(), local_variables).0?,
+            None => {
+                // Body is first created assuming that recursive calls never return.
+                // If the function is recursive it is created again with inferred return type
+                let warnings = local_variables.warnings_len();
+                let (result, recursive) = create(Type::Never, local_variables);
+                if !recursive {
+                    result?
+                } else {
+                    local_variables.truncate_warnings(warnings);
+                    let assumed = match result {
+                        Ok((_, Type::Never, _)) | Err(_) => {
+                            return Err(Error::CannotInferReturnType(ident));
+                        }
+                        Ok((_, assumed, _)) => assumed,
+                    };
+                    let (body, return_type, captures) =
+                        create(assumed.clone(), local_variables).0?;
+                    if !return_type.matches(&assumed) {
+                        return Err(Error::CannotInferReturnType(ident));
+                    }
+                    (body, assumed, captures)
+                }
+            }
+        };
+        let slot = local_variables.insert(
             ident.clone(),
             LocalVariable::Function(params.clone(), return_type.clone()),
         );
+        Ok(Self::new(
+            ident,
+            params,
+            slot,
+            body,
+            return_type,
+            generator,
+            captures,
+            local_variables,
+        )?)
+    }
 
-        let mut local_variables = local_variables.function_layer(
-            LocalVariableMap::from(params.clone()),
-            FunctionInfo::new(Some(ident.clone()), return_type.clone()),
-        );
-        let body = local_variables.create_instructions(inner)?;
-        if !Type::Void.matches(&return_type)
-            && !body
-                .iter()
-                .map(ReturnType::return_type)
-                .any(|var_type| var_type == Type::Never)
-        {
-            return Err(Error::MissingReturn {
-                function_name: Some(ident),
-                return_type,
-            });
-        }
+    /// Creates declaration compiling body of the function
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        ident: Arc<str>,
+        params: Params,
+        slot: Slot,
+        body: Arc<[InstructionWithStr]>,
+        return_type: Type,
+        generator: bool,
+        captures: Vec<Arc<str>>,
+        local_variables: &LocalVariables,
+    ) -> Result<Self, ExecError> {
+        let (closure, captures) = compile_closure(
+            Some(&ident),
+            &params,
+            &body,
+            &return_type,
+            generator,
+            captures,
+            local_variables,
+        )?;
         Ok(Self {
             ident,
             params,
+            slot,
             body,
             return_type,
-        }
-        .into())
+            generator,
+            closure,
+            captures,
+        })
     }
 }
 
-impl Exec for FunctionDeclaration {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let mut local_variables = LocalVariables::from_params(self.params.clone(), interpreter);
-        local_variables.insert(
-            self.ident.clone(),
-            LocalVariable::Function(self.params.clone(), self.return_type.clone()),
-        );
-        let body = recreate_instructions(&self.body, &mut local_variables)?;
-        let function: Arc<Function> = Function {
-            ident: Some(self.ident.clone()),
-            params: self.params.clone(),
-            body: Body::Lang(body),
-            return_type: self.return_type.clone(),
+impl Compile for FunctionDeclaration {
+    fn compile(&self, compiler: &mut Compiler) {
+        for capture in self.captures.iter() {
+            capture.compile(compiler);
         }
-        .into();
-        interpreter.insert(self.ident.clone(), function.clone().into());
-        Ok(function.into())
+        compiler.emit(Op::Closure(self.closure.clone()));
+        compiler.emit(Op::Store(self.slot));
     }
 }
 
 impl Recreate for FunctionDeclaration {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
-        local_variables.insert(
+        let slot = local_variables.insert(
             self.ident.clone(),
             LocalVariable::Function(self.params.clone(), self.return_type.clone()),
         );
-        let mut local_variables = local_variables.function_layer(
-            self.params.clone().into(),
-            FunctionInfo::new(Some(self.ident.clone()), self.return_type.clone()),
+        let mut fn_local_variables = local_variables.function_layer(
+            self.params.clone(),
+            FunctionInfo::new(Some(self.ident.clone()), Some(self.return_type.clone()))
+                .generator(self.generator),
         );
-        let body = recreate_instructions(&self.body, &mut local_variables)?;
-        Ok(Self {
-            ident: self.ident.clone(),
-            params: self.params.clone(),
+        let body = recreate_sequence(&self.body, &mut fn_local_variables)?;
+        let captures = fn_local_variables.function().unwrap().captures();
+        drop(fn_local_variables);
+        let declaration = Self::new(
+            self.ident.clone(),
+            self.params.clone(),
+            slot,
             body,
-            return_type: self.return_type.clone(),
+            self.return_type.clone(),
+            self.generator,
+            captures,
+            local_variables,
+        )?;
+        if local_variables.opt_level == OptLevel::Full {
+            let function = AnonymousFunction {
+                params: declaration.params.clone(),
+                body: declaration.body.clone(),
+                return_type: declaration.return_type.clone(),
+                generator: declaration.generator,
+                closure: declaration.closure.clone(),
+                captures: declaration.captures.clone(),
+            };
+            local_variables.insert_literal(self.ident.clone(), function);
         }
-        .into())
+        Ok(declaration.into())
     }
 }
 
@@ -122,3 +224,81 @@ impl ReturnType for FunctionDeclaration {
         var_type!(params -> return_type)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter,
+        variable::{Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn return_type_inference() {
+        let result = parse_and_exec("(x: int) { x * 2 }").unwrap();
+        assert_eq!(result.as_type(), var_type!((int) -> int));
+        let result = parse_and_exec("(x: int) { x * 2; }").unwrap();
+        assert_eq!(result.as_type(), var_type!((int) -> ()));
+        let result = parse_and_exec(
+            "(x: int) {
+                if x > 0 {
+                    return \"positive\"
+                }
+            }",
+        )
+        .unwrap();
+        assert_eq!(result.as_type(), var_type!((int) -> (string | ())));
+        assert_eq!(
+            parse_and_exec("[1, 2, 3]~ @ (x: int) { x + 1 } $]"),
+            Ok(var!([2, 3, 4]))
+        );
+        let script = "fact := (n: int) {
+            if n <= 1 {
+                return 1
+            }
+            n * fact(n - 1)
+        }
+        (fact, fact(5))";
+        let result = parse_and_exec(script).unwrap().into_tuple().unwrap();
+        assert_eq!(result[0].as_type(), var_type!((int) -> int));
+        assert_eq!(result[1], var!(120));
+        assert_eq!(
+            parse_and_exec("f := (n: int) { return f(n) }"),
+            Err(Error::CannotInferReturnType("f".into()))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "f := (n: int) {
+                    if n == 0 {
+                        return 0
+                    }
+                    (f(n - 1), 1)
+                }"
+            ),
+            Err(Error::CannotInferReturnType("f".into()))
+        );
+    }
+
+    #[test]
+    fn void_function_param() {
+        let script = "y := (f: (int) -> ()) { f(1) }
+            c := mut 0;
+            (y((x: int) { c += x }), *c)";
+        assert_eq!(parse_and_exec(script), Ok(var!(((), 1))));
+    }
+
+    #[test]
+    fn void_function_param_result_dropped() {
+        let script = "h := (f: (int) -> ()) -> () { v := ([1]~ @ f $])[0]; return v }
+            h((x: int) -> int { return x })";
+        assert_eq!(parse_and_exec(script), Ok(Variable::Void));
+        let script = "f := (x: int) -> int { return x }
+            g: (int) -> () = f";
+        assert!(parse_and_exec(script).is_err());
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
@@ -1,4 +1,190 @@
 mod anonymous;
 pub mod call;
 mod declaration;
-pub use self::{anonymous::AnonymousFunction, declaration::FunctionDeclaration};
+mod propagate;
+pub use self::{
+    anonymous::AnonymousFunction, declaration::FunctionDeclaration, propagate::Propagate,
+};
+use super::{
+    Instruction, InstructionWithStr,
+    local_variable::{LocalVariable, LocalVariables},
+    recreate_instructions,
+    unary_operation::UnaryOperation,
+};
+use crate::{
+    self as simplesl, Error, ExecError,
+    function::{Body, Closure, Params},
+    unary_operator::UnaryOperator,
+    variable::{ReturnType, Type},
+};
+use pest::iterators::{Pair, Pairs};
+use simplesl_macros::var_type;
+use simplesl_parser::Rule;
+use std::sync::Arc;
+
+/// Returns type parameters of generic function, consuming them from inner pairs of function
+fn type_params(inner: &mut Pairs<Rule>) -> Arc<[Arc<str>]> {
+    if !matches!(inner.peek(), Some(pair) if pair.as_rule() == Rule::type_params) {
+        return Arc::from([]);
+    }
+    inner
+        .next()
+        .unwrap()
+        .into_inner()
+        .map(|pair| pair.as_str().into())
+        .collect()
+}
+
+/// Returns true if function body contains yield statement outside of nested functions
+fn contains_yield(pairs: Pairs<Rule>) -> bool {
+    pairs.into_iter().any(|pair| match pair.as_rule() {
+        Rule::r#yield => true,
+        Rule::function => false,
+        _ => contains_yield(pair.into_inner()),
+    })
+}
+
+/// Creates body of function and returns it together with return type of the function.
+/// If return type isn't declared it is inferred from return statements
+/// and final expression of the body, that is its last line not followed by `;`.
+/// Generator returns iterator over values yielded by the body
+fn create_body(
+    inner: Pairs<Rule>,
+    local_variables: &mut LocalVariables,
+) -> Result<(Arc<[InstructionWithStr]>, Type), Error> {
+    let function = local_variables.function().unwrap();
+    let (name, declared) = (function.name(), function.return_type().cloned());
+    let generator = function.is_generator();
+    if let Some(declared) = &declared
+        && generator
+        && declared.iter_element().is_none()
+    {
+        return Err(Error::WrongGeneratorType(declared.clone()));
+    }
+    let mut pairs = inner.collect::<Vec<_>>();
+    let final_expression = pairs
+        .pop_if(|pair| declared.is_none() && !generator && is_final_expression(pair))
+        .map(|pair| create_final_return(pair, local_variables))
+        .transpose()?;
+    let mut body = local_variables.create_instructions(pairs)?.to_vec();
+    body.extend(final_expression);
+    if generator {
+        let return_type = declared.unwrap_or_else(|| {
+            let element_type = local_variables.function().unwrap().yielded();
+            var_type!(() -> (bool, element_type))
+        });
+        return Ok((body.into(), return_type));
+    }
+    let returns = body
+        .iter()
+        .map(ReturnType::return_type)
+        .any(|var_type| var_type == Type::Never);
+    let Some(return_type) = declared else {
+        let returned = local_variables.function().unwrap().returned();
+        let return_type = if returns {
+            returned
+        } else {
+            returned | Type::Void
+        };
+        return Ok((body.into(), return_type));
+    };
+    if !Type::Void.matches(&return_type) && !returns {
+        return Err(Error::MissingReturn {
+            function_name: name,
+            return_type,
+        });
+    }
+    Ok((body.into(), return_type))
+}
+
+fn is_final_expression(pair: &Pair<Rule>) -> bool {
+    let followed_by_semicolon = pair.as_span().get_input()[pair.as_span().end()..]
+        .trim_start()
+        .starts_with(';');
+    !followed_by_semicolon
+        && !matches!(
+            pair.as_rule(),
+            Rule::r#return
+                | Rule::throw
+                | Rule::r#break
+                | Rule::r#continue
+                | Rule::set
+                | Rule::destruct
+                | Rule::type_declaration
+                | Rule::enum_declaration
+                | Rule::function_declaration
+        )
+}
+
+/// Creates instruction returning value of final expression of function body
+fn create_final_return(
+    pair: Pair<Rule>,
+    local_variables: &mut LocalVariables,
+) -> Result<InstructionWithStr, Error> {
+    let InstructionWithStr {
+        instruction,
+        str,
+        location,
+    } = InstructionWithStr::new(pair, local_variables)?;
+    local_variables
+        .function()
+        .unwrap()
+        .check_return(instruction.return_type())?;
+    Ok(InstructionWithStr {
+        instruction: UnaryOperation {
+            instruction,
+            op: UnaryOperator::Return,
+        }
+        .into(),
+        str: format!("return {str}").into(),
+        location,
+    })
+}
+
+/// Declares type parameters as aliases of generic types in the current layer
+fn declare_type_params(local_variables: &LocalVariables, type_params: &[Arc<str>]) {
+    for name in type_params {
+        local_variables.insert_type(name.clone(), Type::Generic(name.clone()));
+    }
+}
+
+/// Compiles body of function literal which frame starts with params followed by the function itself
+/// if it has ident and values of variables it captures.
+/// Returns closure and instructions loading the captured values in the enclosing scope
+fn compile_closure(
+    ident: Option<&Arc<str>>,
+    params: &Params,
+    body: &[InstructionWithStr],
+    return_type: &Type,
+    generator: bool,
+    captures: Vec<Arc<str>>,
+    local_variables: &LocalVariables,
+) -> Result<(Arc<Closure>, Arc<[Instruction]>), ExecError> {
+    let mut frame = LocalVariables::from_params(params.clone(), local_variables.interpreter);
+    if let Some(ident) = ident {
+        frame.insert(
+            ident.clone(),
+            LocalVariable::Function(params.clone(), return_type.clone()),
+        );
+    }
+    let captured: Arc<[Instruction]> = captures
+        .into_iter()
+        .filter(|name| Some(name) != ident)
+        .filter_map(|name| local_variables.instruction(&name))
+        .filter(|instruction| matches!(instruction, Instruction::LocalVariable(..)))
+        .collect();
+    for instruction in captured.iter() {
+        if let Instruction::LocalVariable(name, variable, _) = instruction {
+            frame.insert(name.clone(), variable.clone());
+        }
+    }
+    let body = recreate_instructions(body, &mut frame)?;
+    let closure = Closure {
+        ident: ident.cloned(),
+        params: params.clone(),
+        body: Body::lang(body, generator),
+        return_type: return_type.clone(),
+        captures: captured.len(),
+    };
+    Ok((closure.into(), captured))
+}
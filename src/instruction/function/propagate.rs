@@ -0,0 +1,159 @@
+use crate::{
+    self as simplesl, Error, ExecError,
+    instruction::{
+        Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    },
+    variable::{ReturnType, Type},
+    vm::{Compiler, Op},
+};
+use lazy_static::lazy_static;
+use simplesl_macros::var_type;
+
+lazy_static! {
+    /// Types of values that are returned from the enclosing function by `?`
+    pub static ref FAILURE_TYPE: Type = var_type!(() | struct{error_code: int, msg: string});
+}
+
+#[derive(Debug)]
+pub struct Propagate {
+    instruction: Instruction,
+    success: Type,
+    failure: Type,
+}
+
+impl Propagate {
+    pub fn create_instruction(
+        lhs: InstructionWithStr,
+        local_variables: &LocalVariables,
+    ) -> Result<Instruction, Error> {
+        let return_type = lhs.return_type();
+        let Some((success, failure)) = split(&return_type) else {
+            return Err(Error::CannotPropagate(lhs.str, return_type));
+        };
+        let Some(function) = local_variables.function() else {
+            return Err(Error::PropagateOutsideFunction);
+        };
+        function.check_return(failure.clone())?;
+        Ok(Self {
+            instruction: lhs.instruction,
+            success,
+            failure,
+        }
+        .into())
+    }
+}
+
+/// Splits type into part that is passed on and part that is returned from the function.
+/// Returns None if any of the parts would be empty
+fn split(var_type: &Type) -> Option<(Type, Type)> {
+    let Type::Multi(types) = var_type else {
+        return None;
+    };
+    let (failure, success): (Vec<&Type>, Vec<&Type>) = types
+        .iter()
+        .partition(|var_type| var_type.matches(&FAILURE_TYPE));
+    let success = success.into_iter().cloned().reduce(Type::concat)?;
+    let failure = failure.into_iter().cloned().reduce(Type::concat)?;
+    Some((success, failure))
+}
+
+impl Compile for Propagate {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.instruction.compile(compiler);
+        compiler.emit(Op::Propagate(self.failure.clone()));
+    }
+}
+
+impl Recreate for Propagate {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let instruction = self.instruction.recreate(local_variables)?;
+        Ok(Self {
+            instruction,
+            success: self.success.clone(),
+            failure: self.failure.clone(),
+        }
+        .into())
+    }
+}
+
+impl ReturnType for Propagate {
+    fn return_type(&self) -> Type {
+        self.success.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as simplesl, Code, Error, Interpreter, variable::Variable};
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn propagate() {
+        let script = "parse := (s: string) -> int | () {
+            if s == \"\" return ()
+            return 5
+        }
+        double := (s: string) -> int | () {
+            return parse(s)? + 5
+        }";
+        assert_eq!(
+            parse_and_exec(&format!("{script}\ndouble(\"5\")")),
+            Ok(var!(10))
+        );
+        assert_eq!(
+            parse_and_exec(&format!("{script}\ndouble(\"\")")),
+            Ok(var!(()))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "f := (a: int) -> int | struct{error_code: int, msg: string} {
+                    if a < 0 return struct{error_code := 1, msg := \"negative\"}
+                    return a
+                }
+                g := (a: int) -> struct{error_code: int, msg: string} | int {
+                    x := f(a)?
+                    return x + 1
+                }
+                (g(4), g(-4))"
+            ),
+            Ok(var!((5, struct{error_code := 1, msg := "negative"})))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "f := (a: int) -> struct{msg: string} | struct{error_code: int, msg: string} {
+                    if a < 0 return struct{error_code := 1, msg := \"negative\"}
+                    return struct{msg := \"ok\"}
+                }
+                g := (a: int) -> string | struct{error_code: int, msg: string} {
+                    return f(a)?.msg
+                }
+                (g(4), g(-4))"
+            ),
+            Ok(var!(("ok", struct{error_code := 1, msg := "negative"})))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "f := () -> int | () { return 5 }
+                g := () -> int { return f()? }"
+            ),
+            Err(Error::WrongReturn {
+                function_name: Some("g".into()),
+                function_return_type: var_type!(int),
+                returned: var_type!(())
+            })
+        );
+        assert_eq!(
+            parse_and_exec("f := () -> int | () { return 5 }\nf()?"),
+            Err(Error::PropagateOutsideFunction)
+        );
+        assert_eq!(
+            parse_and_exec("f := () -> int { return 5 }\ng := () -> int | () { return f()? }"),
+            Err(Error::CannotPropagate("f ()".into(), var_type!(int)))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
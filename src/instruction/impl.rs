@@ -0,0 +1,237 @@
+use super::{
+    Compile, Instruction, InstructionWithStr, Recreate, bin_op::overload,
+    function::FunctionDeclaration, local_variable::LocalVariables, recreate_instructions,
+};
+use crate::{
+    Error, ExecError,
+    function::{Param, Params},
+    variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
+};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+use std::sync::Arc;
+
+/// Block declaring methods of type alias
+#[derive(Debug)]
+pub struct Impl {
+    methods: Arc<[InstructionWithStr]>,
+}
+
+impl Impl {
+    pub fn create_instruction(
+        pair: Pair<Rule>,
+        local_variables: &mut LocalVariables,
+    ) -> Result<Instruction, Error> {
+        let mut inner = pair.into_inner();
+        let alias: Arc<str> = inner.next().unwrap().as_str().into();
+        let self_type = local_variables
+            .get_type(&alias)
+            .ok_or_else(|| Error::TypeDoesntExist(alias.clone()))?;
+        local_variables.insert_impl(alias.clone(), self_type.clone());
+        let methods = inner
+            .map(|pair| {
+                let str = pair.as_str().into();
+                let location = Some(local_variables.location(&pair));
+                let instruction = create_method(&alias, &self_type, pair, local_variables)?.into();
+                Ok(InstructionWithStr {
+                    instruction,
+                    str,
+                    location,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { methods }.into())
+    }
+}
+
+/// Creates method as function named `alias.method` taking receiver as first param
+fn create_method(
+    alias: &str,
+    self_type: &Type,
+    pair: Pair<Rule>,
+    local_variables: &mut LocalVariables,
+) -> Result<FunctionDeclaration, Error> {
+    let mut inner = pair.into_inner();
+    let name = inner.next().unwrap().as_str();
+    let ident = format!("{alias}.{name}").into();
+    let receiver = Param {
+        name: "self".into(),
+        var_type: self_type.clone(),
+    };
+    let params = std::iter::once(Ok(receiver))
+        .chain(
+            inner
+                .next()
+                .unwrap()
+                .into_inner()
+                .map(|pair| Param::create(pair, local_variables)),
+        )
+        .collect::<Result<Params, Error>>()?;
+    let return_type = if matches!(inner.peek(), Some(pair)
+        if pair.as_rule() == Rule::return_type_decl)
+    {
+        Some(local_variables.create_type(inner.next().unwrap().into_inner().next().unwrap())?)
+    } else {
+        None
+    };
+    let method = FunctionDeclaration::create(
+        ident,
+        Arc::from([]),
+        params,
+        return_type,
+        inner,
+        local_variables,
+    )?;
+    overload::check_method(name, &method)?;
+    Ok(method)
+}
+
+impl Compile for Impl {
+    fn compile(&self, compiler: &mut Compiler) {
+        for method in self.methods.iter() {
+            compiler.compile(method);
+            compiler.emit(Op::Pop);
+        }
+        compiler.emit(Op::Constant(Variable::Void));
+    }
+}
+
+impl Recreate for Impl {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let methods = recreate_instructions(&self.methods, local_variables)?;
+        Ok(Self { methods }.into())
+    }
+}
+
+impl ReturnType for Impl {
+    fn return_type(&self) -> Type {
+        Type::Void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter,
+        function::Param,
+        variable::{Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn methods() {
+        let circle = "type Circle = struct{radius: float}
+            impl Circle {
+                fn area(self) -> float {
+                    return 3.0 * self.radius * self.radius
+                }
+                fn scaled(self, factor: float) {
+                    struct{radius := self.radius * factor}
+                }
+                fn scaled_area(self, factor: float) -> float {
+                    return self.scaled(factor).area()
+                }
+            }
+            c := struct{radius := 2.0}";
+        assert_eq!(
+            parse_and_exec(&format!("{circle}\nc.area()")),
+            Ok(var!(12.0))
+        );
+        assert_eq!(
+            parse_and_exec(&format!("{circle}\nc.scaled_area(0.5)")),
+            Ok(var!(3.0))
+        );
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{circle}
+                total := (circles: [Circle]) -> float {{ return circles~ @ (c: Circle) {{ c.area() }} $+ }}
+                total([c, c.scaled(0.5)])"
+            )),
+            Ok(var!(15.0))
+        );
+        assert_eq!(
+            parse_and_exec(&format!("{circle}\nf := c.scaled; f(2.0).radius")),
+            Ok(var!(4.0))
+        );
+        assert_eq!(
+            parse_and_exec(&format!("{circle}\nc.scaled")).map(|f| f.as_type()),
+            Ok(var_type!((float) -> struct{radius: float}))
+        );
+        assert_eq!(
+            parse_and_exec(&format!("{circle}\nc.scaled(\"2\")")),
+            Err(Error::WrongArgument {
+                function: "c .scaled".into(),
+                param: Param {
+                    name: "factor".into(),
+                    var_type: var_type!(float)
+                },
+                given: r#""2""#.into(),
+                given_type: var_type!(string)
+            })
+        );
+        assert_eq!(
+            parse_and_exec(&format!("{circle}\nc.area(1.0)")),
+            Err(Error::WrongNumberOfArguments("c .area".into(), 0))
+        );
+        assert_eq!(
+            parse_and_exec(&format!("{circle}\nc.perimeter()")),
+            Err(Error::NoField {
+                struct_ident: "c".into(),
+                field_ident: "perimeter".into(),
+                struct_type: var_type!(struct{radius: float})
+            })
+        );
+    }
+
+    #[test]
+    fn method_resolution() {
+        assert_eq!(
+            parse_and_exec(
+                "type Named = struct{name: string}
+                type Person = struct{name: string, age: int}
+                impl Named {
+                    fn describe(self) -> string { return self.name }
+                    fn greet(self) -> string { return \"hello \" + self.describe() }
+                }
+                impl Person {
+                    fn describe(self) -> string { return self.name + \"!\" }
+                }
+                (struct{name := \"box\"}.greet(), struct{name := \"Ann\", age := 5}.describe())"
+            ),
+            Ok(var!(("hello box", "Ann!")))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "type Counter = struct{count: int, describe: () -> string}
+                impl Counter {
+                    fn describe(self) -> string { return \"method\" }
+                }
+                struct{count := 1, describe := () -> string { return \"field\" }}.describe()"
+            ),
+            Ok(var!("field"))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "type Node = struct{value: int, next: int}
+                impl Node {
+                    fn sum(self, n: int) -> int {
+                        if n == 0 { return self.value }
+                        return self.value + struct{value := self.next, next := 0}.sum(n - 1)
+                    }
+                }
+                struct{value := 1, next := 2}.sum(1)"
+            ),
+            Ok(var!(3))
+        );
+        assert_eq!(
+            parse_and_exec("impl Missing { fn f(self) -> int { return 0 } }"),
+            Err(Error::TypeDoesntExist("Missing".into()))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
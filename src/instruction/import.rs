@@ -12,5 +12,5 @@ pub fn create_instruction(
         .into_string()
         .unwrap();
     let instructions = local_variables.load(&path)?;
-    module::new(instructions, local_variables.drop_layer())
+    module::new(instructions, local_variables.drop_layer_exporting_types())
 }
@@ -1,22 +1,42 @@
 use super::{Instruction, InstructionWithStr, function::AnonymousFunction};
 use crate::{
-    self as simplesl, Error, Interpreter,
+    self as simplesl, Error, Interpreter, Location, OptLevel, Warning,
     function::{Param, Params},
     variable::{ReturnType, Type, Typed, Variable},
 };
 use derive_more::From;
-use pest::{Parser, iterators::Pairs};
+use pest::{Parser, iterators::Pair};
 use simplesl_macros::var_type;
 use simplesl_parser::{Rule, SimpleSLParser};
-use std::{collections::HashMap, fs, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fs,
+    sync::Arc,
+};
 
 pub type LocalVariableMap = HashMap<Arc<str>, LocalVariable>;
+pub type TypeMap = HashMap<Arc<str>, Type>;
+/// Index of local variable in frame of the function being executed
+pub type Slot = usize;
 pub struct LocalVariables<'a> {
     variables: LocalVariableMap,
+    /// Slots of variables declared in this layer
+    slots: HashMap<Arc<str>, Slot>,
+    /// The first slot that isn't used by this layer nor lower layers of the same frame
+    next_slot: Cell<Slot>,
+    /// Function literals bound to variables declared in this layer, used for inlining
+    literals: HashMap<Arc<str>, AnonymousFunction>,
     lower_layer: Option<&'a Self>,
     function: Option<FunctionInfo>,
     pub in_loop: bool,
+    pub(crate) opt_level: OptLevel,
     pub interpreter: &'a Interpreter<'a>,
+    file: Option<Arc<str>>,
+    types: RefCell<TypeMap>,
+    /// Type aliases with impl blocks declared in this layer and their types
+    impls: RefCell<Vec<(Arc<str>, Type)>>,
+    warnings: RefCell<Vec<Warning>>,
 }
 
 impl<'a> LocalVariables<'a> {
@@ -24,30 +44,145 @@ impl<'a> LocalVariables<'a> {
     pub fn new(interpreter: &'a Interpreter) -> Self {
         Self {
             variables: LocalVariableMap::new(),
+            slots: HashMap::new(),
+            next_slot: Cell::new(0),
+            literals: HashMap::new(),
             lower_layer: None,
             function: None,
             interpreter,
             in_loop: false,
+            opt_level: OptLevel::None,
+            file: None,
+            types: RefCell::default(),
+            impls: RefCell::default(),
+            warnings: RefCell::default(),
         }
     }
 
+    /// Creates layer of function which params occupy the first slots of frame
     pub fn from_params(params: Params, interpreter: &'a Interpreter) -> Self {
-        Self {
-            variables: LocalVariableMap::from(params),
-            lower_layer: None,
-            function: None,
-            interpreter,
-            in_loop: false,
-        }
+        let mut local_variables = Self::new(interpreter);
+        local_variables.extend(
+            params
+                .iter()
+                .map(|Param { name, var_type }| (name.clone(), var_type.clone())),
+        );
+        local_variables
     }
-    pub fn insert(&mut self, name: Arc<str>, variable: LocalVariable) {
-        self.variables.insert(name, variable);
+    /// Declares variable in the current layer and returns its slot.
+    /// Variable redeclared in the same layer keeps its slot
+    pub fn insert(&mut self, name: Arc<str>, variable: LocalVariable) -> Slot {
+        self.literals.remove(&name);
+        self.variables.insert(name.clone(), variable);
+        *self.slots.entry(name).or_insert_with(|| {
+            let slot = self.next_slot.get();
+            self.next_slot.set(slot + 1);
+            slot
+        })
     }
     #[must_use]
     pub fn get(&self, name: &str) -> Option<&LocalVariable> {
-        self.variables
-            .get(name)
-            .or_else(|| self.lower_layer?.get(name))
+        self.resolve(name).map(|(variable, _)| variable)
+    }
+    /// Returns variable with given name and its slot
+    #[must_use]
+    pub fn resolve(&self, name: &str) -> Option<(&LocalVariable, Slot)> {
+        match self.variables.get(name) {
+            Some(variable) => Some((variable, self.slots[name])),
+            None => self.lower_layer?.resolve(name),
+        }
+    }
+    /// Remembers function literal bound to variable declared in this layer
+    pub(crate) fn insert_literal(&mut self, name: Arc<str>, function: AnonymousFunction) {
+        self.literals.insert(name, function);
+    }
+    /// Returns function literal bound to variable with given name if it is known
+    pub(crate) fn literal(&self, name: &str) -> Option<&AnonymousFunction> {
+        if self.variables.contains_key(name) {
+            return self.literals.get(name);
+        }
+        self.lower_layer?.literal(name)
+    }
+    /// Returns instruction reading variable with given name if it is declared.
+    /// Variables with known values are replaced with them
+    #[must_use]
+    pub fn instruction(&self, name: &Arc<str>) -> Option<Instruction> {
+        let (variable, slot) = self.resolve(name)?;
+        Some(match variable {
+            LocalVariable::Variable(variable) => Instruction::Variable(variable.clone()),
+            variable => {
+                self.capture(name);
+                Instruction::LocalVariable(name.clone(), variable.clone(), slot)
+            }
+        })
+    }
+    /// Records variable with given name as captured by functions
+    /// which bodies are parsed in layers above the one declaring it
+    fn capture(&self, name: &Arc<str>) {
+        let mut layer = self;
+        while !layer.variables.contains_key(name) {
+            if let Some(function) = &layer.function {
+                function.capture(name);
+            }
+            let Some(lower_layer) = layer.lower_layer else {
+                return;
+            };
+            layer = lower_layer;
+        }
+    }
+    /// Returns names and slots of variables declared in the current layer
+    pub(crate) fn layer_slots(&self) -> Arc<[(Arc<str>, Slot)]> {
+        self.slots
+            .iter()
+            .map(|(name, slot)| (name.clone(), *slot))
+            .collect()
+    }
+    /// Declares type alias in the current layer.
+    /// Aliases can be declared through shared reference so import and mod can export them
+    pub fn insert_type(&self, name: Arc<str>, var_type: Type) {
+        self.types.borrow_mut().insert(name, var_type);
+    }
+    #[must_use]
+    pub fn get_type(&self, name: &str) -> Option<Type> {
+        self.types.borrow().get(name).cloned().or_else(|| {
+            self.lower_layer.map_or_else(
+                || self.interpreter.get_type(name).cloned(),
+                |layer| layer.get_type(name),
+            )
+        })
+    }
+    /// Registers impl block of type alias, its methods are declared as functions named `alias.method`
+    pub fn insert_impl(&self, alias: Arc<str>, var_type: Type) {
+        self.impls.borrow_mut().push((alias, var_type));
+    }
+    /// Returns ident, params and return type of method with given name
+    /// implemented for type matched by receiver.
+    /// If many impls provide it, the one for the most specific type is chosen
+    #[must_use]
+    pub fn method(&self, receiver: &Type, name: &str) -> Option<(Arc<str>, Params, Type)> {
+        let mut found: Option<(Type, Arc<str>, Params, Type)> = None;
+        let mut layer = Some(self);
+        while let Some(current) = layer {
+            for (alias, var_type) in current.impls.borrow().iter().rev() {
+                let more_specific = |(found_type, ..): &(Type, _, _, _)| {
+                    var_type.matches(found_type) && !found_type.matches(var_type)
+                };
+                if !receiver.matches(var_type) || found.as_ref().is_some_and(|f| !more_specific(f))
+                {
+                    continue;
+                }
+                let ident: Arc<str> = format!("{alias}.{name}").into();
+                if let Some(LocalVariable::Function(params, return_type)) = self.get(&ident) {
+                    found = Some((var_type.clone(), ident, params.clone(), return_type.clone()));
+                }
+            }
+            layer = current.lower_layer;
+        }
+        found.map(|(_, ident, params, return_type)| (ident, params, return_type))
+    }
+    /// Builds type from pair resolving type aliases declared in scope
+    pub fn create_type(&self, pair: Pair<Rule>) -> Result<Type, Error> {
+        Type::from_pair(pair, &|ident| self.get_type(ident)).map_err(Error::TypeDoesntExist)
     }
     #[must_use]
     pub fn contains_key(&self, name: &Arc<str>) -> bool {
@@ -60,10 +195,18 @@ impl<'a> LocalVariables<'a> {
     pub fn create_layer(&'a self) -> Self {
         Self {
             variables: LocalVariableMap::new(),
+            slots: HashMap::new(),
+            next_slot: Cell::new(self.next_slot.get()),
+            literals: HashMap::new(),
             lower_layer: Some(self),
             function: None,
             interpreter: self.interpreter,
             in_loop: self.in_loop,
+            opt_level: self.opt_level,
+            file: self.file.clone(),
+            types: RefCell::default(),
+            impls: RefCell::default(),
+            warnings: RefCell::default(),
         }
     }
 
@@ -72,15 +215,61 @@ impl<'a> LocalVariables<'a> {
         self.variables
     }
 
+    /// Drops layer moving type aliases declared in it to the lower layer.
+    /// Returns variables declared in the layer with their slots
     #[must_use]
-    pub fn function_layer(&'a self, layer: LocalVariableMap, function: FunctionInfo) -> Self {
-        Self {
-            variables: layer,
+    pub fn drop_layer_exporting_types(self) -> Vec<(Arc<str>, LocalVariable, Slot)> {
+        if let Some(layer) = self.lower_layer {
+            layer.types.borrow_mut().extend(self.types.take());
+        }
+        let slots = self.slots;
+        self.variables
+            .into_iter()
+            .map(|(ident, variable)| {
+                let slot = slots[&ident];
+                (ident, variable, slot)
+            })
+            .collect()
+    }
+
+    /// Creates layer of function body, its frame starts with params
+    #[must_use]
+    pub fn function_layer(&'a self, params: Params, function: FunctionInfo) -> Self {
+        let mut layer = Self {
+            variables: LocalVariableMap::new(),
+            slots: HashMap::new(),
+            next_slot: Cell::new(0),
+            literals: HashMap::new(),
             lower_layer: Some(self),
             function: Some(function),
             interpreter: self.interpreter,
             in_loop: false,
+            opt_level: self.opt_level,
+            file: self.file.clone(),
+            types: RefCell::default(),
+            impls: RefCell::default(),
+            warnings: RefCell::default(),
+        };
+        layer.extend(
+            params
+                .iter()
+                .map(|Param { name, var_type }| (name.clone(), var_type.clone())),
+        );
+        layer
+    }
+
+    /// Returns info of function which return type is being inferred if name refers to it
+    pub fn inferred_function(&self, name: &str) -> Option<&FunctionInfo> {
+        if self.variables.contains_key(name) {
+            return None;
+        }
+        if let Some(function) = &self.function
+            && function.name.as_deref() == Some(name)
+        {
+            return function.return_type.is_none().then_some(function);
         }
+        self.lower_layer
+            .and_then(|layer| layer.inferred_function(name))
     }
 
     pub fn function(&'a self) -> Option<&'a FunctionInfo> {
@@ -91,7 +280,52 @@ impl<'a> LocalVariables<'a> {
 
     pub(crate) fn load(&mut self, path: &str) -> Result<Arc<[InstructionWithStr]>, Error> {
         let contents = fs::read_to_string(path)?;
-        self.parse_input(&contents)
+        let file = self.file.replace(path.into());
+        let result = self.parse_input(&contents);
+        self.file = file;
+        result
+    }
+
+    /// Sets path of the file being parsed, used in [`Location`]s of created instructions
+    pub(crate) fn set_file(&mut self, path: &str) {
+        self.file = Some(path.into());
+    }
+
+    /// Reports warning, warnings are collected in the lowest layer
+    pub fn warn(&self, warning: Warning) {
+        match self.lower_layer {
+            Some(layer) => layer.warn(warning),
+            None => self.warnings.borrow_mut().push(warning),
+        }
+    }
+
+    pub(crate) fn warnings_len(&self) -> usize {
+        match self.lower_layer {
+            Some(layer) => layer.warnings_len(),
+            None => self.warnings.borrow().len(),
+        }
+    }
+
+    /// Discards warnings issued after there were len of them
+    pub(crate) fn truncate_warnings(&self, len: usize) {
+        match self.lower_layer {
+            Some(layer) => layer.truncate_warnings(len),
+            None => self.warnings.borrow_mut().truncate(len),
+        }
+    }
+
+    pub(crate) fn take_warnings(&self) -> Vec<Warning> {
+        self.warnings.take()
+    }
+
+    /// Returns position of the pair in the code being parsed
+    pub fn location(&self, pair: &Pair<Rule>) -> Location {
+        let (line, column) = pair.line_col();
+        Location {
+            file: self.file.clone(),
+            line,
+            column,
+        }
     }
 
     pub(crate) fn parse_input(&mut self, input: &str) -> Result<Arc<[InstructionWithStr]>, Error> {
@@ -99,11 +333,12 @@ impl<'a> LocalVariables<'a> {
         self.create_instructions(pairs)
     }
 
-    pub(crate) fn create_instructions(
+    pub(crate) fn create_instructions<'b>(
         &mut self,
-        pairs: Pairs<'_, Rule>,
+        pairs: impl IntoIterator<Item = Pair<'b, Rule>>,
     ) -> Result<Arc<[InstructionWithStr]>, Error> {
         let mut instructions = pairs
+            .into_iter()
             .map(|pair| InstructionWithStr::new(pair, self))
             .collect::<Result<Vec<InstructionWithStr>, Error>>()?;
         let Some(last) = instructions.pop() else {
@@ -128,8 +363,9 @@ where
     V: Into<LocalVariable>,
 {
     fn extend<T: IntoIterator<Item = (Arc<str>, V)>>(&mut self, iter: T) {
-        self.variables
-            .extend(iter.into_iter().map(|(ident, var)| (ident, var.into())));
+        for (ident, var) in iter {
+            self.insert(ident, var.into());
+        }
     }
 }
 
@@ -146,7 +382,7 @@ impl From<&Instruction> for LocalVariable {
     fn from(value: &Instruction) -> Self {
         match value {
             Instruction::AnonymousFunction(function) => function.into(),
-            Instruction::LocalVariable(_, var) => var.clone(),
+            Instruction::LocalVariable(_, var, _) => var.clone(),
             Instruction::Variable(var) => var.clone().into(),
             ins => ins.return_type().into(),
         }
@@ -179,19 +415,126 @@ impl Typed for LocalVariable {
 #[derive(Clone, Debug)]
 pub struct FunctionInfo {
     name: Option<Arc<str>>,
-    return_type: Type,
+    /// Declared return type, None if it is inferred
+    return_type: Option<Type>,
+    returned: RefCell<Type>,
+    recursive: Cell<bool>,
+    /// Type of yielded values, None if the function isn't generator
+    yielded: Option<RefCell<Type>>,
+    /// Variables declared outside of the function used in its body
+    captures: RefCell<Vec<Arc<str>>>,
 }
 
 impl FunctionInfo {
-    pub fn new(name: Option<Arc<str>>, return_type: Type) -> Self {
-        Self { name, return_type }
+    pub fn new(name: Option<Arc<str>>, return_type: Option<Type>) -> Self {
+        Self {
+            name,
+            return_type,
+            returned: RefCell::new(Type::Never),
+            recursive: Cell::new(false),
+            yielded: None,
+            captures: RefCell::default(),
+        }
+    }
+
+    /// Marks function as generator, that is function containing yield statements
+    #[must_use]
+    pub fn generator(mut self, generator: bool) -> Self {
+        self.yielded = generator.then(|| RefCell::new(Type::Never));
+        self
+    }
+
+    pub fn is_generator(&self) -> bool {
+        self.yielded.is_some()
     }
 
     pub fn name(&self) -> Option<Arc<str>> {
         self.name.clone()
     }
 
-    pub fn return_type(&self) -> &Type {
-        &self.return_type
+    pub fn return_type(&self) -> Option<&Type> {
+        self.return_type.as_ref()
+    }
+
+    /// Checks if value of type returned can be returned from the function.
+    /// If return type is inferred, returned is added to it instead
+    pub fn check_return(&self, returned: Type) -> Result<(), Error> {
+        if self.is_generator() {
+            // return only ends generator so it cannot return any value
+            if !returned.matches(&Type::Void) {
+                return Err(Error::WrongReturn {
+                    function_name: self.name(),
+                    function_return_type: Type::Void,
+                    returned,
+                });
+            }
+            return Ok(());
+        }
+        let Some(return_type) = &self.return_type else {
+            self.returned
+                .replace_with(|inferred| inferred.clone() | returned);
+            return Ok(());
+        };
+        if !returned.matches(return_type) {
+            return Err(Error::WrongReturn {
+                function_name: self.name(),
+                function_return_type: return_type.clone(),
+                returned,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn mark_recursive(&self) {
+        self.recursive.set(true);
+    }
+
+    /// Returns true if the function refers to itself
+    pub fn is_recursive(&self) -> bool {
+        self.recursive.get()
+    }
+
+    /// Returns concatenation of types returned by return statements
+    pub fn returned(&self) -> Type {
+        self.returned.borrow().clone()
+    }
+
+    /// Checks if value of type yielded can be yielded from the generator.
+    /// If type of elements isn't declared, yielded is added to it instead
+    pub fn check_yield(&self, yielded: Type) -> Result<(), Error> {
+        let Some(element_type) = self.return_type.as_ref().and_then(Type::iter_element) else {
+            self.yielded
+                .as_ref()
+                .unwrap()
+                .replace_with(|inferred| inferred.clone() | yielded);
+            return Ok(());
+        };
+        if !yielded.matches(&element_type) {
+            return Err(Error::WrongYield {
+                function_name: self.name(),
+                element_type,
+                yielded,
+            });
+        }
+        Ok(())
+    }
+
+    fn capture(&self, name: &Arc<str>) {
+        let mut captures = self.captures.borrow_mut();
+        if !captures.contains(name) {
+            captures.push(name.clone());
+        }
+    }
+
+    /// Returns names of variables declared outside of the function used in its body
+    pub fn captures(&self) -> Vec<Arc<str>> {
+        self.captures.borrow().clone()
+    }
+
+    /// Returns concatenation of types yielded by yield statements
+    pub fn yielded(&self) -> Type {
+        self.yielded
+            .as_ref()
+            .map_or(Type::Never, |yielded| yielded.borrow().clone())
     }
 }
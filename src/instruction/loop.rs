@@ -1,11 +1,12 @@
 pub mod r#for;
 pub mod r#while;
 pub mod while_set;
-use super::{
-    Exec, ExecResult, ExecStop, Instruction, InstructionWithStr, Recreate,
-    local_variable::LocalVariables,
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
+use crate::{
+    Error, ExecError,
+    variable::Variable,
+    vm::{Compiler, Op},
 };
-use crate::{Error, ExecError, Interpreter, variable::Variable};
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
 
@@ -26,16 +27,14 @@ impl Loop {
     }
 }
 
-impl Exec for Loop {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        loop {
-            match self.0.exec(interpreter) {
-                Ok(_) | Err(ExecStop::Continue) => (),
-                Err(ExecStop::Break) => break,
-                e => return e,
-            }
-        }
-        Ok(Variable::Void)
+impl Compile for Loop {
+    fn compile(&self, compiler: &mut Compiler) {
+        let start = compiler.emit(Op::Loop { end: 0 });
+        compiler.compile(&self.0);
+        compiler.emit(Op::Pop);
+        compiler.emit(Op::Jump(start + 1));
+        compiler.patch(start);
+        compiler.emit(Op::Constant(Variable::Void));
     }
 }
 
@@ -2,8 +2,7 @@ use crate::{
     self as simplesl, BinOperator, Error,
     instruction::{
         BinOperation, Instruction, InstructionWithStr, Loop, block::Block, control_flow::IfElse,
-        destruct_tuple::DestructTuple, local_variable::LocalVariable,
-        local_variable::LocalVariables, set::Set,
+        destruct::Destruct, local_variable::LocalVariables, pattern::Pattern, set::Set,
     },
     variable::{ReturnType, Type, Variable},
 };
@@ -26,7 +25,7 @@ pub fn create_instruction(
     local_variables: &mut LocalVariables,
 ) -> Result<Instruction, Error> {
     let mut inner = pair.into_inner();
-    let ident: Arc<str> = inner.next().unwrap().as_str().into();
+    let pattern_pair = inner.next().unwrap();
     let iter = InstructionWithStr::new(inner.next().unwrap(), local_variables)?;
     let Some(iter_element) = iter.return_type().iter_element() else {
         return Err(Error::WrongType(
@@ -34,47 +33,58 @@ pub fn create_instruction(
             var_type!(() -> (bool, any)),
         ));
     };
+    let pattern_str: Arc<str> = pattern_pair.as_str().into();
+    let pattern = Pattern::create(pattern_pair, &iter_element, local_variables)?;
+    if !pattern.is_covering_type(&iter_element) {
+        return Err(Error::PatternNotCovering(pattern_str, iter_element));
+    }
     let mut local_variables = local_variables.create_layer();
     local_variables.in_loop = true;
-    local_variables.insert(ident.clone(), LocalVariable::Other(iter_element));
     let str = format!("$iter = {}", iter.str).into();
     let iter = InstructionWithStr {
-        instruction: Set {
-            ident: ITER.clone(),
-            instruction: iter,
-        }
-        .into(),
+        instruction: Set::new(ITER.clone(), iter, &mut local_variables).into(),
         str,
+        location: None,
     };
     let iter_call = InstructionWithStr {
         instruction: BinOperation {
-            lhs: Instruction::LocalVariable(
-                ITER.clone(),
-                LocalVariable::Other(var_type!(()->(bool, any))),
-            ),
+            lhs: Instruction::new_ident(&ITER, &local_variables)?,
             rhs: Variable::Tuple([].into()).into(),
             op: BinOperator::FunctionCall,
         }
         .into(),
         str: "$iter()".into(),
+        location: None,
+    };
+    let mut local_variables = local_variables.create_layer();
+    let str = format!("($con, {pattern_str}) = {}", iter_call.str).into();
+    let con = Pattern::Bind {
+        ident: CON.clone(),
+        var_type: Type::Bool,
+        filter: None,
+        slot: 0,
     };
-    let str = format!("($con, {ident}) = {}", iter_call.str).into();
+    let pattern =
+        Pattern::Tuple([con, pattern].into()).insert_local_variables(&mut local_variables);
     let destruct = InstructionWithStr {
-        instruction: DestructTuple {
-            idents: [CON.clone(), ident].into(),
+        instruction: Destruct {
+            pattern,
             instruction: iter_call,
         }
         .into(),
         str,
+        location: None,
     };
     let instruction = InstructionWithStr::new(inner.next().unwrap(), &mut local_variables)?;
     let condition = InstructionWithStr {
-        instruction: Instruction::LocalVariable(CON.clone(), LocalVariable::Other(Type::Bool)),
+        instruction: Instruction::new_ident(&CON, &local_variables)?,
         str: CON.clone(),
+        location: None,
     };
     let if_false = InstructionWithStr {
         instruction: Instruction::Break,
         str: "break".into(),
+        location: None,
     };
     let str = format!("if $con {} else break", instruction.str).into();
     let if_else = InstructionWithStr {
@@ -85,6 +95,7 @@ pub fn create_instruction(
         }
         .into(),
         str,
+        location: None,
     };
     let str = format!("{{{}\n{}}}", destruct.str, if_else.str).into();
     let body = Block {
@@ -94,11 +105,13 @@ pub fn create_instruction(
     let body = InstructionWithStr {
         instruction: body,
         str,
+        location: None,
     };
     let str = format!("loop {}", body.str).into();
     let l = InstructionWithStr {
         instruction: Loop(body).into(),
         str,
+        location: None,
     };
     Ok(Block {
         instructions: [iter, l].into(),
@@ -37,10 +37,12 @@ pub fn create_instruction(
             if_false: InstructionWithStr {
                 instruction: Instruction::Break,
                 str: "Break".into(),
+                location: None,
             },
         }
         .into(),
         str,
+        location: None,
     };
     Ok(Loop(instruction).into())
 }
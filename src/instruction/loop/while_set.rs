@@ -20,11 +20,13 @@ pub fn create_instruction(
     set_if_else.else_instruction = InstructionWithStr {
         instruction: Instruction::Break,
         str: "break".into(),
+        location: None,
     };
     let str = format!("if {} else break", str.strip_prefix("while").unwrap()).into();
     let instruction = InstructionWithStr {
         instruction: set_if_else.into(),
         str,
+        location: None,
     };
     Ok(Loop(instruction).into())
 }
@@ -0,0 +1,243 @@
+use super::{
+    Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    recreate_instructions,
+};
+use crate::{
+    Error, ExecError,
+    variable::{self, MapType, ReturnType, Type, Variable},
+    vm::{Compiler, Op},
+};
+use itertools::Itertools;
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct Map {
+    pub keys: Arc<[InstructionWithStr]>,
+    pub values: Arc<[InstructionWithStr]>,
+    pub key_type: Type,
+    pub value_type: Type,
+}
+
+impl Map {
+    pub fn create_instruction(
+        pair: Pair<Rule>,
+        local_variables: &LocalVariables,
+    ) -> Result<Instruction, Error> {
+        let (keys, values): (Vec<_>, Vec<_>) = pair
+            .into_inner()
+            .map(|pair| InstructionWithStr::new_expression(pair, local_variables))
+            .process_results(|iter| iter.tuples().unzip())?;
+        let key_type = types_concat(&keys);
+        if !key_type.is_hashable() {
+            return Err(Error::CannotBeKey(key_type));
+        }
+        let value_type = types_concat(&values);
+        Ok(Self {
+            keys: keys.into(),
+            values: values.into(),
+            key_type,
+            value_type,
+        }
+        .into())
+    }
+}
+
+fn types_concat(instructions: &[InstructionWithStr]) -> Type {
+    instructions
+        .iter()
+        .map(ReturnType::return_type)
+        .reduce(Type::concat)
+        .unwrap_or(Type::Never)
+}
+
+impl Compile for Map {
+    fn compile(&self, compiler: &mut Compiler) {
+        for (key, value) in self.keys.iter().zip(self.values.iter()) {
+            compiler.compile(key);
+            compiler.compile(value);
+        }
+        compiler.emit(Op::Map {
+            key_type: self.key_type.clone(),
+            value_type: self.value_type.clone(),
+            len: self.keys.len(),
+        });
+    }
+}
+
+/// Returns map of entries given as keys each followed by its value
+pub(crate) fn exec(key_type: &Type, value_type: &Type, entries: Vec<Variable>) -> Variable {
+    let mut map = variable::Map::new_with_type(key_type.clone(), value_type.clone());
+    for (key, value) in entries.into_iter().tuples() {
+        map.insert(key, value);
+    }
+    map.into()
+}
+
+impl Recreate for Map {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let keys = recreate_instructions(&self.keys, local_variables)?;
+        let values = recreate_instructions(&self.values, local_variables)?;
+        let mut map = variable::Map::new_with_type(self.key_type.clone(), self.value_type.clone());
+        for (key, value) in keys.iter().zip(values.iter()) {
+            let (Instruction::Variable(key), Instruction::Variable(value)) =
+                (&key.instruction, &value.instruction)
+            else {
+                return Ok(Self {
+                    keys,
+                    values,
+                    key_type: self.key_type.clone(),
+                    value_type: self.value_type.clone(),
+                }
+                .into());
+            };
+            map.insert(key.clone(), value.clone());
+        }
+        Ok(Instruction::Variable(map.into()))
+    }
+}
+
+impl ReturnType for Map {
+    fn return_type(&self) -> Type {
+        MapType {
+            key: self.key_type.clone(),
+            value: self.value_type.clone(),
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter,
+        variable::{Map, Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+    use std::str::FromStr;
+
+    #[test]
+    fn map_literals() {
+        assert_eq!(
+            parse_and_exec(r#"{"a": 1, "b": 2}"#),
+            Ok(Map::from_iter([(var!("a"), var!(1)), (var!("b"), var!(2))]).into())
+        );
+        assert_eq!(
+            parse_and_exec(r#"x := {"a": 1, "b": 2.5}; x"#).map(|map| map.as_type()),
+            Ok(var_type!({string: int | float}))
+        );
+        assert_eq!(
+            parse_and_exec(r#"k := "b"; {"a": 1, k: 2, "a": 3}"#),
+            Ok(Map::from_iter([(var!("a"), var!(3)), (var!("b"), var!(2))]).into())
+        );
+        assert_eq!(
+            parse_and_exec("{(1, true): [1], (2, false): []}"),
+            Ok(Map::from_iter([
+                (var!((1, true)), var!([1])),
+                (var!((2, false)), Variable::from([]))
+            ])
+            .into())
+        );
+        assert_eq!(parse_and_exec(r#"{"a": 1} == {"a": 1}"#), Ok(var!(true)));
+        assert_eq!(
+            parse_and_exec(r#"{"a": 1, "b": 2} == {"b": 2, "a": 1}"#),
+            Ok(var!(true))
+        );
+        assert_eq!(parse_and_exec(r#"{"a": 1} == {"a": 2}"#), Ok(var!(false)));
+        assert_eq!(
+            parse_and_exec("{1.5: 1}"),
+            Err(Error::CannotBeKey(var_type!(float)))
+        );
+        assert_eq!(
+            parse_and_exec("{[1]: 1}"),
+            Err(Error::CannotBeKey(var_type!([int])))
+        );
+        assert_eq!(parse_and_exec("x := {:}; x"), Ok(Map::from_iter([]).into()));
+        assert_eq!(
+            parse_and_exec("x := mut {string: int} {:}; *x"),
+            Ok(Map::from_iter([]).into())
+        );
+        assert_eq!(Variable::from_str("{:}"), Ok(Map::from_iter([]).into()));
+    }
+
+    #[test]
+    fn map_indexing() {
+        assert_eq!(parse_and_exec(r#"{"a": 1, "b": 2}["b"]"#), Ok(var!(2)));
+        assert_eq!(parse_and_exec(r#"{"a": 1, "b": 2}["c"]"#), Ok(var!(())));
+        assert_eq!(
+            parse_and_exec(
+                r#"x := {"a": 1};
+                if value: int = x["a"] { value } else { 0 }"#
+            ),
+            Ok(var!(1))
+        );
+        assert_eq!(
+            parse_and_exec(r#"{"a": 1}[0]"#),
+            Err(Error::CannotIndexWith("0".into()))
+        );
+        assert_eq!(parse_and_exec("{(1, 2): 3}[(1, 2)]"), Ok(var!(3)));
+    }
+
+    #[test]
+    fn map_insertion() {
+        assert_eq!(
+            parse_and_exec(
+                r#"counts := mut {string: int} {};
+                for word in ["a", "b", "a"]~ {
+                    if count: int = (*counts)[word] {
+                        counts += {word: count + 1}
+                    } else {
+                        counts += {word: 1}
+                    }
+                }
+                *counts"#
+            ),
+            Ok(Map::from_iter([(var!("a"), var!(2)), (var!("b"), var!(1))]).into())
+        );
+        assert_eq!(
+            parse_and_exec(r#"{1: "a"} + {2: 2.5}"#).map(|map| map.as_type()),
+            Ok(var_type!({int: string | float}))
+        );
+        assert_eq!(
+            parse_and_exec(r#"x := mut {"a": 1}; x += {"b": "c"}"#),
+            Err(Error::CannotDo2(
+                var_type!(mut {string: int}),
+                simplesl::BinOperator::AssignAdd,
+                var_type!({string: string})
+            ))
+        );
+    }
+
+    #[test]
+    fn map_iteration() {
+        assert_eq!(
+            parse_and_exec(r#"{"a": 1, "b": 2}~$]"#),
+            Ok(Variable::from([var!(("a", 1)), var!(("b", 2))]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                r#"sum := mut 0;
+                for (key, value) in {"a": 1, "b": 2}~ { sum += value }
+                *sum"#
+            ),
+            Ok(var!(3))
+        );
+    }
+
+    #[test]
+    fn map_from_str() {
+        let map = Map::from_iter([(var!("a"), var!(1)), (var!((1, "b")), var!([2.5]))]);
+        assert_eq!(Variable::from_str(&map.to_string()), Ok(map.into()));
+        assert_eq!(Variable::from_str("{}"), Ok(Map::from_iter([]).into()));
+        assert_eq!(
+            Variable::from_str(r#"{"a": {1: true}}"#).map(|map| map.to_string()),
+            Ok(r#"{"a": {1: true}}"#.into())
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
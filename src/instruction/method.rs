@@ -0,0 +1,136 @@
+use super::{
+    BinOperation, Compile, Instruction, InstructionWithStr, Recreate,
+    local_variable::{LocalVariable, LocalVariables, Slot},
+    tuple::Tuple,
+    unary_operation::UnaryOperation,
+};
+use crate::{
+    self as simplesl, BinOperator, ExecError,
+    function::{Body, Closure, Param, Params},
+    unary_operator::UnaryOperator,
+    variable::{ReturnType, Type},
+    vm::{Compiler, Op},
+};
+use simplesl_macros::var_type;
+use std::{iter::once, sync::Arc};
+
+/// Method bound to receiver
+#[derive(Debug)]
+pub struct Method {
+    pub receiver: InstructionWithStr,
+    pub function: Instruction,
+    /// Params of method without receiver
+    pub params: Params,
+    pub return_type: Type,
+}
+
+impl Method {
+    /// Returns method with given name implemented for type of receiver if there is one
+    pub fn create(
+        receiver: &InstructionWithStr,
+        name: &str,
+        local_variables: &LocalVariables,
+    ) -> Option<Self> {
+        let (ident, params, return_type) = local_variables.method(&receiver.return_type(), name)?;
+        let function = Instruction::new_ident(&ident, local_variables).ok()?;
+        Some(Self {
+            receiver: receiver.clone(),
+            function,
+            params: params.iter().skip(1).cloned().collect(),
+            return_type,
+        })
+    }
+
+    /// Returns call of method with receiver prepended to args
+    pub fn call(&self, args: Arc<[InstructionWithStr]>) -> Instruction {
+        let elements = once(self.receiver.clone())
+            .chain(args.iter().cloned())
+            .collect();
+        BinOperation {
+            lhs: self.function.clone(),
+            rhs: Tuple { elements }.into(),
+            op: BinOperator::FunctionCall,
+        }
+        .into()
+    }
+}
+
+impl Compile for Method {
+    // Method used as value is compiled into function capturing receiver and the function implementing it
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.receiver);
+        self.function.compile(compiler);
+        compiler.emit(Op::Closure(self.closure().into()));
+    }
+}
+
+impl Method {
+    /// Returns function calling the method, its frame holds params followed by receiver and the function
+    fn closure(&self) -> Closure {
+        let local = |name: &Arc<str>, var_type: Type, slot: Slot| InstructionWithStr {
+            instruction: Instruction::LocalVariable(
+                name.clone(),
+                LocalVariable::Other(var_type),
+                slot,
+            ),
+            str: name.clone(),
+            location: None,
+        };
+        let args = self
+            .params
+            .iter()
+            .enumerate()
+            .map(|(slot, Param { name, var_type })| local(name, var_type.clone(), slot))
+            .collect();
+        let len = self.params.len();
+        let call = Self {
+            receiver: local(&self.receiver.str, self.receiver.return_type(), len),
+            function: local(&"$method".into(), self.function.return_type(), len + 1).instruction,
+            params: self.params.clone(),
+            return_type: self.return_type.clone(),
+        }
+        .call(args);
+        let body = InstructionWithStr {
+            instruction: UnaryOperation {
+                instruction: call,
+                op: UnaryOperator::Return,
+            }
+            .into(),
+            str: self.receiver.str.clone(),
+            location: None,
+        };
+        Closure {
+            ident: None,
+            params: self.params.clone(),
+            body: Body::lang([body].into(), false),
+            return_type: self.return_type.clone(),
+            captures: 2,
+        }
+    }
+}
+
+impl Recreate for Method {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let receiver = self.receiver.recreate(local_variables)?;
+        let function = self.function.recreate(local_variables)?;
+        Ok(Self {
+            receiver,
+            function,
+            params: self.params.clone(),
+            return_type: self.return_type.clone(),
+        }
+        .into())
+    }
+}
+
+impl ReturnType for Method {
+    fn return_type(&self) -> Type {
+        let params: Arc<[Type]> = self
+            .params
+            .iter()
+            .map(|Param { name: _, var_type }| var_type.clone())
+            .collect();
+        let return_type = self.return_type.clone();
+        var_type!(params -> return_type)
+    }
+}
@@ -3,7 +3,11 @@ use std::sync::Arc;
 use super::{Instruction, InstructionWithStr, local_variable::LocalVariables};
 use crate::{
     Error,
-    instruction::{block::Block, local_variable::LocalVariableMap, r#struct::Struct},
+    instruction::{
+        block::Block,
+        local_variable::{LocalVariable, Slot},
+        r#struct::Struct,
+    },
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -15,21 +19,22 @@ pub fn create_instruction(
     let mut local_variables = local_variables.create_layer();
     let instructions =
         local_variables.create_instructions(pair.into_inner().next().unwrap().into_inner())?;
-    new(instructions, local_variables.drop_layer())
+    new(instructions, local_variables.drop_layer_exporting_types())
 }
 
 pub fn new(
     instructions: Arc<[InstructionWithStr]>,
-    lv_layer: LocalVariableMap,
+    lv_layer: Vec<(Arc<str>, LocalVariable, Slot)>,
 ) -> Result<Instruction, Error> {
     let (idents, values): (Vec<_>, Vec<_>) = lv_layer
-        .iter()
-        .map(|(ident, var)| {
+        .into_iter()
+        .map(|(ident, var, slot)| {
             (
                 ident.clone(),
                 InstructionWithStr {
-                    instruction: Instruction::LocalVariable(ident.clone(), var.clone()),
+                    instruction: Instruction::LocalVariable(ident.clone(), var, slot),
                     str: ident.clone(),
+                    location: None,
                 },
             )
         })
@@ -42,6 +47,7 @@ pub fn new(
     let struct_ins = InstructionWithStr {
         instruction: struct_ins,
         str: "struct".into(),
+        location: None,
     };
     let instructions = [instructions, [struct_ins].into()].concat().into();
     Ok(Block { instructions }.into())
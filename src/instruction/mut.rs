@@ -1,12 +1,11 @@
-use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
-};
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
 use crate::{
-    Error, ExecError, Interpreter,
-    variable::{self, ReturnType, Type},
+    Error, ExecError,
+    variable::{ReturnType, Type},
+    vm::{Compiler, Op},
 };
-use pest::iterators::Pair;
-use simplesl_parser::Rule;
+use pest::{Parser, iterators::Pair};
+use simplesl_parser::{Rule, SimpleSLParser};
 
 #[derive(Debug)]
 pub struct Mut {
@@ -19,19 +18,25 @@ impl Mut {
         pair: Pair<Rule>,
         local_variables: &LocalVariables,
     ) -> Result<Instruction, Error> {
+        let end = pair.as_span().end();
         let mut inner = pair.into_inner();
         let pair = inner.next().unwrap();
         if pair.as_rule() == Rule::expr {
             let instruction = InstructionWithStr::new_expression(pair, local_variables)?;
-            let var_type = instruction.return_type();
-            return Ok(Mut {
-                var_type,
-                instruction,
-            }
-            .into());
+            return Ok(Self::untyped(instruction));
         }
-        let var_type = Type::from(pair);
-        let pair = inner.next().unwrap();
+        let mut inner = pair.into_inner();
+        let pair = inner.next().unwrap().into_inner().next().unwrap();
+        let var_type = match local_variables.create_type(pair.clone()) {
+            Ok(var_type) => var_type,
+            // `mut x - 1` is parsed as `x` type followed by `-1`
+            Err(error) => {
+                let instruction =
+                    Self::reparse_expression(&pair, end, local_variables).ok_or(error)??;
+                return Ok(Self::untyped(instruction));
+            }
+        };
+        let pair = inner.next().unwrap().into_inner().next().unwrap();
         let instruction = InstructionWithStr::new_expression(pair, local_variables)?;
         let instruction_return_type = instruction.return_type();
         if !instruction_return_type.matches(&var_type) {
@@ -47,16 +52,42 @@ impl Mut {
         }
         .into())
     }
-}
 
-impl Exec for Mut {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let variable = self.instruction.exec(interpreter)?.into();
-        Ok(variable::Mut {
-            var_type: self.var_type.clone(),
-            variable,
+    fn untyped(instruction: InstructionWithStr) -> Instruction {
+        let var_type = instruction.return_type();
+        Mut {
+            var_type,
+            instruction,
         }
-        .into())
+        .into()
+    }
+
+    /// Parses text from start of `pair` to `end` as expression, returns None if it isn't one.
+    /// Preceding text is replaced with whitespace so locations stay the same
+    fn reparse_expression(
+        pair: &Pair<Rule>,
+        end: usize,
+        local_variables: &LocalVariables,
+    ) -> Option<Result<InstructionWithStr, Error>> {
+        let input = pair.get_input();
+        let start = pair.as_span().start();
+        let mut source: String = input[..start]
+            .chars()
+            .map(|c| if c == '\n' || c == '\r' { c } else { ' ' })
+            .collect();
+        source.push_str(&input[start..end]);
+        let pair = SimpleSLParser::parse(Rule::single_expr, &source)
+            .ok()?
+            .next()
+            .unwrap();
+        Some(InstructionWithStr::new_expression(pair, local_variables))
+    }
+}
+
+impl Compile for Mut {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.instruction);
+        compiler.emit(Op::Mut(self.var_type.clone()));
     }
 }
 
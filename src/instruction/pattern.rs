@@ -0,0 +1,541 @@
+use super::local_variable::{LocalVariable, LocalVariables, Slot};
+use crate::{
+    Error,
+    interpreter::Interpreter,
+    variable::{Type, Typed, Variable},
+};
+use pest::iterators::Pair;
+use simplesl_parser::{Rule, unexpected};
+use std::{iter::zip, sync::Arc};
+
+/// Pattern used to destruct values in match arms, `:=` and for loops
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Wildcard,
+    Bind {
+        ident: Arc<str>,
+        var_type: Type,
+        filter: Option<Type>,
+        slot: Slot,
+    },
+    Literal(Variable),
+    Range {
+        start: Variable,
+        end: Variable,
+        inclusive: bool,
+    },
+    Tuple(Arc<[Pattern]>),
+    Struct(Arc<[(Arc<str>, Pattern)]>),
+    Array {
+        head: Arc<[Pattern]>,
+        rest: Option<Option<(Arc<str>, Type, Slot)>>,
+    },
+}
+
+impl Pattern {
+    /// Creates pattern matching values of var_type.
+    /// Returns error if pattern can never match value of var_type
+    pub fn create(
+        pair: Pair<Rule>,
+        var_type: &Type,
+        local_variables: &LocalVariables,
+    ) -> Result<Self, Error> {
+        let str: Arc<str> = pair.as_str().into();
+        let never_matches = || Error::PatternNeverMatches(str.clone(), var_type.clone());
+        match pair.as_rule() {
+            Rule::pattern_wildcard => Ok(Self::Wildcard),
+            Rule::pattern_bind => {
+                let mut inner = pair.into_inner();
+                let ident = inner.next().unwrap().as_str().into();
+                let filter = inner
+                    .next()
+                    .map(|pair| local_variables.create_type(pair))
+                    .transpose()?;
+                Self::new_bind(ident, var_type, filter).ok_or_else(never_matches)
+            }
+            Rule::pattern_literal => {
+                let value = literal(pair)?;
+                if !may_match(var_type, &value.as_type()) {
+                    return Err(never_matches());
+                }
+                Ok(Self::Literal(value))
+            }
+            Rule::pattern_range => {
+                let mut inner = pair.into_inner();
+                let start = literal(inner.next().unwrap())?;
+                let inclusive = inner.next().unwrap().as_str() == "..=";
+                let end = literal(inner.next().unwrap())?;
+                if !may_match(var_type, &start.as_type()) {
+                    return Err(never_matches());
+                }
+                Ok(Self::Range {
+                    start,
+                    end,
+                    inclusive,
+                })
+            }
+            Rule::pattern_tuple => {
+                let inner = pair.into_inner();
+                let len = inner.len();
+                let tuples = members(var_type)
+                    .filter(|member| {
+                        **member == Type::Any || member.tuple_len().is_some_and(|l| l == len)
+                    })
+                    .collect::<Box<[_]>>();
+                if tuples.is_empty() {
+                    return Err(never_matches());
+                }
+                let patterns = inner
+                    .enumerate()
+                    .map(|(index, pair)| {
+                        let element_type = join_types(
+                            tuples
+                                .iter()
+                                .map(|member| member.tuple_element_at(index).unwrap_or(Type::Any)),
+                        );
+                        Self::create(pair, &element_type, local_variables)
+                    })
+                    .collect::<Result<_, Error>>()?;
+                Ok(Self::Tuple(patterns))
+            }
+            Rule::pattern_struct => {
+                let fields = pair.into_inner().collect::<Box<[_]>>();
+                let idents = fields
+                    .iter()
+                    .map(|pair| pair.clone().into_inner().next().unwrap().as_str())
+                    .collect::<Box<[_]>>();
+                let structs = members(var_type)
+                    .filter(|member| {
+                        **member == Type::Any
+                            || member.is_struct()
+                                && idents.iter().all(|ident| member.has_field(ident))
+                    })
+                    .collect::<Box<[_]>>();
+                if structs.is_empty() {
+                    return Err(never_matches());
+                }
+                let fields = fields
+                    .into_iter()
+                    .map(|pair| {
+                        let str = pair.as_str();
+                        let mut inner = pair.into_inner();
+                        let ident: Arc<str> = inner.next().unwrap().as_str().into();
+                        let field_type = join_types(
+                            structs
+                                .iter()
+                                .map(|member| member.field_type(&ident).unwrap_or(Type::Any)),
+                        );
+                        let filter = match inner.next() {
+                            Some(pair) if is_pattern(pair.as_rule()) => {
+                                let pattern = Self::create(pair, &field_type, local_variables)?;
+                                return Ok((ident, pattern));
+                            }
+                            Some(pair) => Some(local_variables.create_type(pair)?),
+                            None => None,
+                        };
+                        let pattern = Self::new_bind(ident.clone(), &field_type, filter)
+                            .ok_or_else(|| Error::PatternNeverMatches(str.into(), field_type))?;
+                        Ok((ident, pattern))
+                    })
+                    .collect::<Result<_, Error>>()?;
+                Ok(Self::Struct(fields))
+            }
+            Rule::pattern_array => {
+                let arrays = members(var_type)
+                    .filter(|member| matches!(member, Type::Any | Type::Array(_)))
+                    .collect::<Box<[_]>>();
+                if arrays.is_empty() {
+                    return Err(never_matches());
+                }
+                let element_type = join_types(
+                    arrays
+                        .iter()
+                        .map(|member| member.index_result().unwrap_or(Type::Any)),
+                );
+                let mut head = Vec::new();
+                let mut rest = None;
+                for pair in pair.into_inner() {
+                    if pair.as_rule() == Rule::pattern_rest {
+                        let rest_type = Type::Array(element_type.clone().into());
+                        rest = Some(
+                            pair.into_inner()
+                                .next()
+                                .map(|pair| (pair.as_str().into(), rest_type, 0)),
+                        );
+                        continue;
+                    }
+                    head.push(Self::create(pair, &element_type, local_variables)?);
+                }
+                Ok(Self::Array {
+                    head: head.into(),
+                    rest,
+                })
+            }
+            rule => unexpected!(rule),
+        }
+    }
+
+    /// Creates pattern binding ident, returns None if filter can never match value of var_type
+    fn new_bind(ident: Arc<str>, var_type: &Type, filter: Option<Type>) -> Option<Self> {
+        let Some(filter) = filter else {
+            return Some(Self::Bind {
+                ident,
+                var_type: var_type.clone(),
+                filter: None,
+                slot: 0,
+            });
+        };
+        if !may_match(var_type, &filter) {
+            return None;
+        }
+        let var_type = if var_type.matches(&filter) {
+            var_type.clone()
+        } else {
+            filter.clone()
+        };
+        Some(Self::Bind {
+            ident,
+            var_type,
+            filter: Some(filter),
+            slot: 0,
+        })
+    }
+
+    /// Returns true if pattern matches every value of var_type
+    pub fn is_covering_type(&self, var_type: &Type) -> bool {
+        if let Type::Multi(types) = var_type {
+            return types.iter().all(|var_type| self.is_covering_type(var_type));
+        }
+        match self {
+            Self::Wildcard | Self::Bind { filter: None, .. } => true,
+            Self::Bind {
+                filter: Some(filter),
+                ..
+            } => var_type.matches(filter),
+            Self::Literal(Variable::Void) => *var_type == Type::Void,
+            Self::Literal(_) | Self::Range { .. } => false,
+            Self::Tuple(patterns) => {
+                let Type::Tuple(types) = var_type else {
+                    return false;
+                };
+                types.len() == patterns.len()
+                    && zip(patterns.iter(), types.iter())
+                        .all(|(pattern, var_type)| pattern.is_covering_type(var_type))
+            }
+            Self::Struct(fields) => {
+                var_type.is_struct()
+                    && fields.iter().all(|(ident, pattern)| {
+                        var_type
+                            .field_type(ident)
+                            .is_some_and(|field_type| pattern.is_covering_type(&field_type))
+                    })
+            }
+            Self::Array { head, rest } => {
+                head.is_empty() && rest.is_some() && matches!(var_type, Type::Array(_))
+            }
+        }
+    }
+
+    /// Returns true if variable matches pattern
+    pub fn matches(&self, variable: &Variable) -> bool {
+        match (self, variable) {
+            (Self::Wildcard | Self::Bind { filter: None, .. }, _) => true,
+            (
+                Self::Bind {
+                    filter: Some(filter),
+                    ..
+                },
+                variable,
+            ) => variable.as_type().matches(filter),
+            (Self::Literal(value), variable) => value.equals(variable),
+            (
+                Self::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                variable,
+            ) => {
+                let (start, end) = match (start, end, variable) {
+                    (Variable::Int(start), Variable::Int(end), Variable::Int(value)) => {
+                        (start.cmp(value), end.cmp(value))
+                    }
+                    (Variable::String(start), Variable::String(end), Variable::String(value)) => {
+                        (start.cmp(value), end.cmp(value))
+                    }
+                    _ => return false,
+                };
+                start.is_le() && (end.is_gt() || *inclusive && end.is_eq())
+            }
+            (Self::Tuple(patterns), Variable::Tuple(elements)) => {
+                patterns.len() == elements.len()
+                    && zip(patterns.iter(), elements.iter())
+                        .all(|(pattern, element)| pattern.matches(element))
+            }
+            (Self::Struct(fields), Variable::Struct(map)) => fields
+                .iter()
+                .all(|(ident, pattern)| map.get(ident).is_some_and(|field| pattern.matches(field))),
+            (Self::Array { head, rest }, Variable::Array(array)) => {
+                let len_matches = if rest.is_some() {
+                    array.len() >= head.len()
+                } else {
+                    array.len() == head.len()
+                };
+                len_matches
+                    && zip(head.iter(), array.iter())
+                        .all(|(pattern, element)| pattern.matches(element))
+            }
+            _ => false,
+        }
+    }
+
+    /// Inserts values bound by pattern into interpreter.
+    /// Variable must match the pattern
+    pub fn bind(&self, variable: &Variable, interpreter: &mut Interpreter) {
+        match (self, variable) {
+            (Self::Bind { slot, .. }, variable) => {
+                interpreter.set_local(*slot, variable.clone());
+            }
+            (Self::Tuple(patterns), Variable::Tuple(elements)) => {
+                for (pattern, element) in zip(patterns.iter(), elements.iter()) {
+                    pattern.bind(element, interpreter);
+                }
+            }
+            (Self::Struct(fields), Variable::Struct(map)) => {
+                for (ident, pattern) in fields.iter() {
+                    if let Some(field) = map.get(ident) {
+                        pattern.bind(field, interpreter);
+                    }
+                }
+            }
+            (Self::Array { head, rest }, Variable::Array(array)) => {
+                for (pattern, element) in zip(head.iter(), array.iter()) {
+                    pattern.bind(element, interpreter);
+                }
+                if let Some(Some((_, _, slot))) = rest {
+                    let rest = array.iter().skip(head.len()).cloned().collect::<Vec<_>>();
+                    interpreter.set_local(*slot, rest.into());
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Inserts idents bound by pattern with their types into local_variables.
+    /// Returns pattern binding values to slots of the idents
+    #[must_use]
+    pub fn insert_local_variables(&self, local_variables: &mut LocalVariables) -> Self {
+        match self {
+            Self::Bind {
+                ident,
+                var_type,
+                filter,
+                slot: _,
+            } => Self::Bind {
+                ident: ident.clone(),
+                var_type: var_type.clone(),
+                filter: filter.clone(),
+                slot: local_variables.insert(ident.clone(), LocalVariable::Other(var_type.clone())),
+            },
+            Self::Tuple(patterns) => Self::Tuple(
+                patterns
+                    .iter()
+                    .map(|pattern| pattern.insert_local_variables(local_variables))
+                    .collect(),
+            ),
+            Self::Struct(fields) => Self::Struct(
+                fields
+                    .iter()
+                    .map(|(ident, pattern)| {
+                        (
+                            ident.clone(),
+                            pattern.insert_local_variables(local_variables),
+                        )
+                    })
+                    .collect(),
+            ),
+            Self::Array { head, rest } => {
+                let head = head
+                    .iter()
+                    .map(|pattern| pattern.insert_local_variables(local_variables))
+                    .collect();
+                let rest = rest.as_ref().map(|rest| {
+                    rest.as_ref().map(|(ident, var_type, _)| {
+                        let slot = local_variables
+                            .insert(ident.clone(), LocalVariable::Other(var_type.clone()));
+                        (ident.clone(), var_type.clone(), slot)
+                    })
+                });
+                Self::Array { head, rest }
+            }
+            Self::Wildcard | Self::Literal(_) | Self::Range { .. } => self.clone(),
+        }
+    }
+}
+
+fn is_pattern(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::pattern_wildcard
+            | Rule::pattern_bind
+            | Rule::pattern_literal
+            | Rule::pattern_range
+            | Rule::pattern_tuple
+            | Rule::pattern_struct
+            | Rule::pattern_array
+    )
+}
+
+/// Returns value of literal pattern or range bound
+fn literal(pair: Pair<Rule>) -> Result<Variable, Error> {
+    if pair.as_rule() == Rule::string {
+        return Variable::try_from(pair);
+    }
+    let mut inner = pair.into_inner();
+    let mut pair = inner.next().unwrap();
+    let minus = pair.as_rule() == Rule::pattern_minus;
+    if minus {
+        pair = inner.next().unwrap();
+    }
+    Ok(match (Variable::try_from(pair)?, minus) {
+        (Variable::Int(value), true) => Variable::Int(value.wrapping_neg()),
+        (Variable::Float(value), true) => Variable::Float(-value),
+        (value, _) => value,
+    })
+}
+
+fn members(var_type: &Type) -> Box<dyn Iterator<Item = &Type> + '_> {
+    match var_type {
+        Type::Multi(types) => Box::new(types.iter()),
+        var_type => Box::new(std::iter::once(var_type)),
+    }
+}
+
+fn join_types(types: impl Iterator<Item = Type>) -> Type {
+    types.reduce(Type::concat).unwrap_or(Type::Never)
+}
+
+/// Returns true if some value can be both of var_type and pattern_type
+fn may_match(var_type: &Type, pattern_type: &Type) -> bool {
+    members(var_type).any(|member| {
+        members(pattern_type)
+            .any(|pattern_type| member.matches(pattern_type) || pattern_type.matches(member))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as simplesl, Code, Error, Interpreter, variable::Variable};
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn destruct() {
+        assert_eq!(
+            parse_and_exec("(a, (b, _), c) := (1, (2.5, \"x\"), true);\n(a, b, c)"),
+            Ok(var!((1, 2.5, true)))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "struct{x, y: int, p := (a, b)} := struct{x := 1.5, y := 2, p := (3, 4)};\n(x, y, a + b)"
+            ),
+            Ok(var!((1.5, 2, 7)))
+        );
+        assert_eq!(
+            parse_and_exec("[..rest] := [1, 2, 3]\nrest"),
+            Ok(var!([1, 2, 3]))
+        );
+        assert_eq!(
+            parse_and_exec("(a, b) := (1, 2, 3)"),
+            Err(Error::PatternNeverMatches(
+                "(a, b)".into(),
+                var_type!((int, int, int))
+            ))
+        );
+        assert_eq!(
+            parse_and_exec("[a, ..rest] := [1, 2, 3]"),
+            Err(Error::PatternNotCovering(
+                "[a, ..rest]".into(),
+                var_type!([int])
+            ))
+        );
+        assert_eq!(
+            parse_and_exec("(a: int, b) := (1.5, 2)"),
+            Err(Error::PatternNeverMatches(
+                "a: int".into(),
+                var_type!(float)
+            ))
+        );
+    }
+
+    #[test]
+    fn match_patterns() {
+        let script = "f := (value: (int, int) | [int] | struct{x: int, y: int}) -> int {
+            return match value {
+                (0, y) => y,
+                (x, _) => x,
+                [head, ..rest] => head + rest~$+,
+                [..] => 0,
+                struct{x := 0} => -1,
+                struct{x, y} => x * y,
+            }
+        }";
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{script}
+                [f((0, 5)), f((3, 5)), f([]), f([7, 1, 1]), f(struct{{x := 0, y := 2}}), f(struct{{x := 3, y := 2}})]"
+            )),
+            Ok(var!([5, 3, 0, 9, -1, 6]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match (1, 2) {
+                    (0, y) => y,
+                }"
+            ),
+            Err(Error::MatchNotCovered(var_type!((int, int))))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match (1, 2) {
+                    (a, \"x\") => a,
+                    => 0,
+                }"
+            ),
+            Err(Error::PatternNeverMatches("\"x\"".into(), var_type!(int)))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "match 5 {
+                    _ => true,
+                }"
+            ),
+            Ok(var!(true))
+        );
+    }
+
+    #[test]
+    fn for_patterns() {
+        assert_eq!(
+            parse_and_exec(
+                "sum := mut 0
+                for (a, b) in [(1, 2), (3, 4)]~ {
+                    sum += a * b
+                }
+                *sum"
+            ),
+            Ok(var!(14))
+        );
+        assert_eq!(
+            parse_and_exec("for (a, 1) in [(1, 2)]~ {}"),
+            Err(Error::PatternNotCovering(
+                "(a, 1)".into(),
+                var_type!((int, int))
+            ))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
@@ -1,4 +1,4 @@
-use super::InstructionWithStr;
+use super::{InstructionWithStr, local_variable::LocalVariables};
 use crate as simplesl;
 use crate::Error;
 use crate::variable::Type;
@@ -8,8 +8,13 @@ use simplesl_macros::var_type;
 use simplesl_parser::{Rule, unexpected};
 
 impl InstructionWithStr {
-    pub fn create_prefix(op: Pair<'_, Rule>, rhs: Self) -> Result<Self, Error> {
+    pub fn create_prefix(
+        op: Pair<'_, Rule>,
+        rhs: Self,
+        local_variables: &LocalVariables<'_>,
+    ) -> Result<Self, Error> {
         let str = rhs.str.clone();
+        let location = Some(local_variables.location(&op));
         let instruction = match op.as_rule() {
             Rule::not => not::create_instruction(rhs),
             Rule::unary_minus => unary_minus::create_instruction(rhs),
@@ -17,7 +22,11 @@ impl InstructionWithStr {
             rule => unexpected!(rule),
         }?;
         let str = format!("{} {}", op.as_str(), str).into();
-        Ok(Self { instruction, str })
+        Ok(Self {
+            instruction,
+            str,
+            location,
+        })
     }
 }
 
@@ -0,0 +1,201 @@
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
+use crate::{
+    self as simplesl, BinOperator, Error, ExecError,
+    function::Function,
+    variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
+};
+use simplesl_macros::var_type;
+use std::iter;
+
+#[derive(Debug)]
+pub struct Range {
+    start: Instruction,
+    end: Instruction,
+    step: Option<Instruction>,
+    inclusive: bool,
+}
+
+impl Range {
+    /// Creates `lhs..rhs` or `lhs..=rhs`
+    pub fn create_instruction(
+        lhs: InstructionWithStr,
+        op: BinOperator,
+        rhs: InstructionWithStr,
+    ) -> Result<Instruction, Error> {
+        let (lhs, rhs) = (lhs.instruction, rhs.instruction);
+        let lhs_type = lhs.return_type();
+        let rhs_type = rhs.return_type();
+        if lhs_type != Type::Int || rhs_type != Type::Int {
+            return Err(Error::CannotDo2(lhs_type, op, rhs_type));
+        }
+        Ok(Self {
+            start: lhs,
+            end: rhs,
+            step: None,
+            inclusive: op == BinOperator::RangeInclusive,
+        }
+        .into())
+    }
+
+    /// Sets step of range created from `lhs`, `lhs` is range without step
+    pub fn create_with_step(
+        lhs: InstructionWithStr,
+        step: InstructionWithStr,
+    ) -> Result<Instruction, Error> {
+        let Instruction::Range(range) = lhs.instruction else {
+            unreachable!("step of range that isn't range literal")
+        };
+        let step_type = step.return_type();
+        if step_type != Type::Int {
+            return Err(Error::CannotDo2(
+                range.return_type(),
+                BinOperator::Range,
+                step_type,
+            ));
+        }
+        Ok(Self {
+            start: range.start.clone(),
+            end: range.end.clone(),
+            step: Some(step.instruction),
+            inclusive: range.inclusive,
+        }
+        .into())
+    }
+}
+
+impl Compile for Range {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.start.compile(compiler);
+        self.end.compile(compiler);
+        match &self.step {
+            Some(step) => step.compile(compiler),
+            None => {
+                compiler.emit(Op::Constant(Variable::Int(1)));
+            }
+        }
+        compiler.emit(Op::Range {
+            inclusive: self.inclusive,
+        });
+    }
+}
+
+impl Recreate for Range {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let start = self.start.recreate(local_variables)?;
+        let end = self.end.recreate(local_variables)?;
+        let step = self
+            .step
+            .as_ref()
+            .map(|step| step.recreate(local_variables))
+            .transpose()?;
+        Ok(Self {
+            start,
+            end,
+            step,
+            inclusive: self.inclusive,
+        }
+        .into())
+    }
+}
+
+impl ReturnType for Range {
+    fn return_type(&self) -> Type {
+        var_type!(() -> (bool, int))
+    }
+}
+
+/// Returns iterator function over ints from start to end changing by step
+pub(crate) fn int_range(
+    start: i64,
+    end: i64,
+    step: i64,
+    inclusive: bool,
+) -> Result<Function, ExecError> {
+    if step == 0 {
+        return Err(ExecError::ZeroStep);
+    }
+    let elements = iter::successors(Some(start), move |i| i.checked_add(step))
+        .take_while(move |i| match (step > 0, inclusive) {
+            (true, true) => *i <= end,
+            (true, false) => *i < end,
+            (false, true) => *i >= end,
+            (false, false) => *i > end,
+        })
+        .map(|i| Ok(i.into()));
+    Ok(Function::native_iter(elements, Type::Int))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as simplesl, Code, Error, Interpreter, variable::Variable};
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn range_literals() {
+        assert_eq!(parse_and_exec("(0..5)$]"), Ok(var!([0, 1, 2, 3, 4])));
+        assert_eq!(parse_and_exec("(0..=5)$]"), Ok(var!([0, 1, 2, 3, 4, 5])));
+        assert_eq!(parse_and_exec("(0..10..3)$]"), Ok(var!([0, 3, 6, 9])));
+        assert_eq!(parse_and_exec("(10..=0..-5)$]"), Ok(var!([10, 5, 0])));
+        assert_eq!(parse_and_exec("(5..0)$]"), Ok(var!([])));
+        assert_eq!(parse_and_exec("n := 3; (n-1..n+2)$]"), Ok(var!([2, 3, 4])));
+        assert_eq!(parse_and_exec("(0..5)$+"), Ok(var!(10)));
+        assert_eq!(
+            parse_and_exec("(1..=3) @ (x: int) -> int { return x * x } $]"),
+            Ok(var!([1, 4, 9]))
+        );
+        assert_eq!(
+            parse_and_exec("(0..10) ? (x: int) -> bool { return x % 4 == 0 } $]"),
+            Ok(var!([0, 4, 8]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "sum := mut 0;
+                for i in 0..4 { sum += i }
+                *sum"
+            ),
+            Ok(var!(6))
+        );
+        assert_eq!(parse_and_exec("0..1..0"), Err(Error::ZeroStep));
+        assert_eq!(
+            parse_and_exec("(0..10)..2"),
+            Err(Error::CannotDo2(
+                var_type!(() -> (bool, int)),
+                simplesl::BinOperator::Range,
+                var_type!(int)
+            ))
+        );
+        assert_eq!(parse_and_exec("(0..10..1 + 2)$]"), Ok(var!([0, 3, 6, 9])));
+        assert_eq!(
+            parse_and_exec("0..1.5"),
+            Err(Error::CannotDo2(
+                var_type!(int),
+                simplesl::BinOperator::Range,
+                var_type!(float)
+            ))
+        );
+    }
+
+    #[test]
+    fn range_indexing() {
+        assert_eq!(
+            parse_and_exec("[10, 11, 12, 13, 14][1..4]"),
+            Ok(var!([11, 12, 13]))
+        );
+        assert_eq!(
+            parse_and_exec("a := [10, 11, 12, 13, 14]; a[0..5..2]"),
+            Ok(var!([10, 12, 14]))
+        );
+        assert_eq!(
+            parse_and_exec("a := [10, 11, 12]; n := 2; r := -n..0; a[r]"),
+            Ok(var!([11, 12]))
+        );
+        assert_eq!(parse_and_exec("\"simple\"[1..=3]"), Ok(var!("imp")));
+        assert_eq!(parse_and_exec("[1, 2][0..3]"), Err(Error::IndexOutOfBounds));
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
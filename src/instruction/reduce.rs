@@ -6,10 +6,11 @@ pub mod sum;
 use crate::{
     self as simplesl, Error, ExecError,
     instruction::{
-        Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+        Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
     },
-    interpreter::Interpreter,
+    interpreter::CallDepth,
     variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_macros::var_type;
@@ -70,23 +71,33 @@ impl Recreate for Reduce {
     }
 }
 
-impl Exec for Reduce {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let iter = self.iter.exec(interpreter)?;
-        let initial_value = self.initial_value.exec(interpreter)?;
-        let function = self.function.exec(interpreter)?;
-        let (Variable::Function(iter), Variable::Function(function)) = (&iter, &function) else {
-            unreachable!("Tried to do {iter} ${initial_value} {function}")
+impl Compile for Reduce {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.iter);
+        compiler.compile(&self.initial_value);
+        compiler.compile(&self.function);
+        compiler.emit(Op::Reduce);
+    }
+}
+
+/// Returns result of applying function to accumulator and elements of iterator
+pub(crate) fn exec(
+    iter: Variable,
+    initial_value: Variable,
+    function: Variable,
+    call_depth: CallDepth,
+) -> Result<Variable, ExecError> {
+    let (Variable::Function(iter), Variable::Function(function)) = (&iter, &function) else {
+        unreachable!("Tried to do {iter} ${initial_value} {function}")
+    };
+    let mut result = initial_value;
+    while let Variable::Tuple(tuple) = iter.exec_with_args(&[], call_depth)? {
+        if tuple[0] == Variable::Bool(false) {
+            break;
         };
-        let mut result = initial_value;
-        while let Variable::Tuple(tuple) = iter.exec(interpreter)? {
-            if tuple[0] == Variable::Bool(false) {
-                break;
-            };
-            result = function.exec_with_args(&[result, tuple[1].clone()])?;
-        }
-        Ok(result)
+        result = function.exec_with_args(&[result, tuple[1].clone()], call_depth)?;
     }
+    Ok(result)
 }
 
 impl ReturnType for Reduce {
@@ -1,8 +1,9 @@
 use crate::{
-    self as simplesl, Error, Interpreter,
+    self as simplesl, Error,
     instruction::{ExecResult, Instruction, InstructionWithStr, unary_operation::UnaryOperation},
+    interpreter::CallDepth,
     unary_operator::UnaryOperator,
-    variable::{ReturnType, Type, Variable},
+    variable::{ReturnType, Set, Type, Typed, Variable},
 };
 use lazy_static::lazy_static;
 use simplesl_macros::var_type;
@@ -11,8 +12,8 @@ lazy_static! {
     pub static ref ACCEPTED_TYPE: Type = var_type!(() -> (bool, any));
 }
 
-pub(crate) fn create(lhs: InstructionWithStr) -> Result<Instruction, Error> {
-    let op = UnaryOperator::Collect;
+/// Creates `$]` collecting elements into array or `$}` collecting them into set
+pub(crate) fn create(lhs: InstructionWithStr, op: UnaryOperator) -> Result<Instruction, Error> {
     let return_type = lhs.return_type();
     if !can_be_used(&return_type) {
         return Err(Error::IncorectUnaryOperatorOperand {
@@ -22,6 +23,12 @@ pub(crate) fn create(lhs: InstructionWithStr) -> Result<Instruction, Error> {
             given: return_type,
         });
     }
+    if op == UnaryOperator::CollectSet {
+        let element_type = return_type.iter_element().unwrap();
+        if !element_type.is_hashable() {
+            return Err(Error::CannotBeKey(element_type));
+        }
+    }
     Ok(UnaryOperation {
         instruction: lhs.instruction,
         op,
@@ -33,10 +40,10 @@ pub fn can_be_used(lhs: &Type) -> bool {
     lhs.matches(&ACCEPTED_TYPE)
 }
 
-pub(crate) fn exec(var: Variable, interpreter: &mut Interpreter) -> ExecResult {
+pub(crate) fn exec(var: Variable, call_depth: CallDepth) -> ExecResult {
     let iter = var.into_function().unwrap();
     let mut vec = Vec::new();
-    while let Variable::Tuple(tuple) = iter.exec(interpreter)? {
+    while let Variable::Tuple(tuple) = iter.exec_with_args(&[], call_depth)? {
         if tuple[0] == Variable::Bool(false) {
             break;
         };
@@ -45,11 +52,29 @@ pub(crate) fn exec(var: Variable, interpreter: &mut Interpreter) -> ExecResult {
     Ok(vec.into())
 }
 
+pub(crate) fn exec_set(var: Variable, call_depth: CallDepth) -> ExecResult {
+    let iter = var.into_function().unwrap();
+    let element_type = iter.as_type().iter_element().unwrap();
+    let mut set = Set::new_with_type(element_type);
+    while let Variable::Tuple(tuple) = iter.exec_with_args(&[], call_depth)? {
+        if tuple[0] == Variable::Bool(false) {
+            break;
+        };
+        set.insert(tuple[1].clone());
+    }
+    Ok(set.into())
+}
+
 pub(crate) fn return_type(lhs: Type) -> Type {
     let element = lhs.iter_element().unwrap();
     var_type!([element])
 }
 
+pub(crate) fn set_return_type(lhs: Type) -> Type {
+    let element = lhs.iter_element().unwrap();
+    Type::Set(element.into())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
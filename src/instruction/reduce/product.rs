@@ -1,6 +1,7 @@
 use crate::{
     self as simplesl, Error,
     instruction::{ExecResult, Instruction, InstructionWithStr, unary_operation::UnaryOperation},
+    interpreter::CallDepth,
     stdlib::operators::{FLOAT_PRODUCT, INT_PRODUCT},
     unary_operator::UnaryOperator,
     variable::{ReturnType, Type, Typed, Variable},
@@ -30,18 +31,18 @@ pub fn create(array: InstructionWithStr) -> Result<Instruction, Error> {
     .into())
 }
 
-pub fn exec(var: Variable) -> ExecResult {
+pub fn exec(var: Variable, call_depth: CallDepth) -> ExecResult {
     let return_type = var.as_type();
     if return_type.matches(&var_type!(() -> (bool, int))) {
         return Ok(Variable::from(INT_PRODUCT)
             .as_function()
             .unwrap()
-            .exec_with_args(&[var])?);
+            .exec_with_args(&[var], call_depth)?);
     }
     Ok(Variable::from(FLOAT_PRODUCT)
         .as_function()
         .unwrap()
-        .exec_with_args(&[var])?)
+        .exec_with_args(&[var], call_depth)?)
 }
 
 #[cfg(test)]
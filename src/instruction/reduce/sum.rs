@@ -1,6 +1,7 @@
 use crate::{
     self as simplesl, Error,
     instruction::{ExecResult, Instruction, InstructionWithStr, unary_operation::UnaryOperation},
+    interpreter::CallDepth,
     stdlib::operators::{FLOAT_SUM, INT_SUM, STRING_SUM},
     unary_operator::UnaryOperator,
     variable::{ReturnType, Type, Typed, Variable},
@@ -31,23 +32,23 @@ pub fn create(array: InstructionWithStr) -> Result<Instruction, Error> {
     .into())
 }
 
-pub fn exec(var: Variable) -> ExecResult {
+pub fn exec(var: Variable, call_depth: CallDepth) -> ExecResult {
     let return_type = var.as_type();
     if return_type.matches(&var_type!(() -> (bool, int))) {
         Ok(Variable::from(INT_SUM)
             .as_function()
             .unwrap()
-            .exec_with_args(&[var])?)
+            .exec_with_args(&[var], call_depth)?)
     } else if return_type.matches(&var_type!(() -> (bool, float))) {
         Ok(Variable::from(FLOAT_SUM)
             .as_function()
             .unwrap()
-            .exec_with_args(&[var])?)
+            .exec_with_args(&[var], call_depth)?)
     } else {
         Ok(Variable::from(STRING_SUM)
             .as_function()
             .unwrap()
-            .exec_with_args(&[var])?)
+            .exec_with_args(&[var], call_depth)?)
     }
 }
 
@@ -1,10 +1,11 @@
 use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    Compile, Instruction, InstructionWithStr, Recreate,
+    local_variable::{LocalVariables, Slot},
 };
 use crate::{
-    Error, ExecError,
-    interpreter::Interpreter,
+    Error, ExecError, OptLevel,
     variable::{ReturnType, Type},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -14,6 +15,7 @@ use std::sync::Arc;
 pub struct Set {
     pub ident: Arc<str>,
     pub instruction: InstructionWithStr,
+    pub slot: Slot,
 }
 
 impl Set {
@@ -22,8 +24,17 @@ impl Set {
         instruction: InstructionWithStr,
         local_variables: &mut LocalVariables,
     ) -> Self {
-        local_variables.insert(ident.clone(), (&instruction.instruction).into());
-        Self { ident, instruction }
+        let slot = local_variables.insert(ident.clone(), (&instruction.instruction).into());
+        if let Instruction::AnonymousFunction(function) = &instruction.instruction
+            && local_variables.opt_level == OptLevel::Full
+        {
+            local_variables.insert_literal(ident.clone(), function.clone());
+        }
+        Self {
+            ident,
+            instruction,
+            slot,
+        }
     }
 
     pub fn create_instruction(
@@ -38,23 +49,17 @@ impl Set {
     }
 }
 
-impl Exec for Set {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let result = self.instruction.exec(interpreter)?;
-        interpreter.insert(self.ident.clone(), result.clone());
-        Ok(result)
+impl Compile for Set {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.instruction);
+        compiler.emit(Op::Store(self.slot));
     }
 }
 
 impl Recreate for Set {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
         let instruction = self.instruction.recreate(local_variables)?;
-        local_variables.insert(self.ident.clone(), (&instruction.instruction).into());
-        Ok(Self {
-            ident: self.ident.clone(),
-            instruction,
-        }
-        .into())
+        Ok(Self::new(self.ident.clone(), instruction, local_variables).into())
     }
 }
 
@@ -0,0 +1,217 @@
+use super::{
+    Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    recreate_instructions,
+};
+use crate::{
+    Error, ExecError,
+    variable::{ReturnType, Set, Type, Variable},
+    vm::{Compiler, Op},
+};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct SetLiteral {
+    pub instructions: Arc<[InstructionWithStr]>,
+    pub element_type: Type,
+}
+
+impl SetLiteral {
+    pub fn create_instruction(
+        pair: Pair<Rule>,
+        local_variables: &LocalVariables,
+    ) -> Result<Instruction, Error> {
+        let instructions = pair
+            .into_inner()
+            .map(|arg| InstructionWithStr::new_expression(arg, local_variables))
+            .collect::<Result<Arc<_>, Error>>()?;
+        let element_type = instructions
+            .iter()
+            .map(ReturnType::return_type)
+            .reduce(Type::concat)
+            .unwrap_or(Type::Never);
+        if !element_type.is_hashable() {
+            return Err(Error::CannotBeKey(element_type));
+        }
+        Ok(Self {
+            instructions,
+            element_type,
+        }
+        .into())
+    }
+}
+
+impl Compile for SetLiteral {
+    fn compile(&self, compiler: &mut Compiler) {
+        for instruction in self.instructions.iter() {
+            compiler.compile(instruction);
+        }
+        compiler.emit(Op::Set {
+            element_type: self.element_type.clone(),
+            len: self.instructions.len(),
+        });
+    }
+}
+
+/// Returns set of given elements
+pub(crate) fn exec(element_type: &Type, elements: Vec<Variable>) -> Variable {
+    let mut set = Set::new_with_type(element_type.clone());
+    for element in elements {
+        set.insert(element);
+    }
+    set.into()
+}
+
+impl Recreate for SetLiteral {
+    fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
+        let instructions = recreate_instructions(&self.instructions, local_variables)?;
+        let mut set = Set::new_with_type(self.element_type.clone());
+        for instruction in &*instructions {
+            let InstructionWithStr {
+                instruction: Instruction::Variable(variable),
+                ..
+            } = instruction
+            else {
+                return Ok(Self {
+                    instructions,
+                    element_type: self.element_type.clone(),
+                }
+                .into());
+            };
+            set.insert(variable.clone());
+        }
+        Ok(Instruction::Variable(set.into()))
+    }
+}
+
+impl ReturnType for SetLiteral {
+    fn return_type(&self) -> Type {
+        Type::Set(self.element_type.clone().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, BinOperator, Code, Error, Interpreter,
+        variable::{Set, Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+    use std::str::FromStr;
+
+    #[test]
+    fn set_literals() {
+        assert_eq!(
+            parse_and_exec("{1, 2, 3, 2}"),
+            Ok(Set::from_iter([var!(1), var!(2), var!(3)]).into())
+        );
+        assert_eq!(
+            parse_and_exec(r#"x := {1, "a"}; x"#).map(|set| set.as_type()),
+            Ok(var_type!({ int | string }))
+        );
+        assert_eq!(
+            parse_and_exec("x := 5; {x,}"),
+            Ok(Set::from_iter([var!(5)]).into())
+        );
+        assert_eq!(
+            parse_and_exec("{(1, true), (1, true)}"),
+            Ok(Set::from_iter([var!((1, true))]).into())
+        );
+        assert_eq!(parse_and_exec("{1, 2} == {2, 1}"), Ok(var!(true)));
+        assert_eq!(parse_and_exec("{1, 2} == {1, 3}"), Ok(var!(false)));
+        assert_eq!(
+            parse_and_exec("{1.5, 2.5}"),
+            Err(Error::CannotBeKey(var_type!(float)))
+        );
+        let set = Set::from_iter([var!(1), var!("a"), var!((2, "b"))]);
+        assert_eq!(Variable::from_str(&set.to_string()), Ok(set.into()));
+        assert_eq!(parse_and_exec("x := {,}; x"), Ok(Set::from_iter([]).into()));
+        assert_eq!(
+            parse_and_exec("x := mut {int} {,}; *x"),
+            Ok(Set::from_iter([]).into())
+        );
+        assert_eq!(Variable::from_str("{,}"), Ok(Set::from_iter([]).into()));
+    }
+
+    #[test]
+    fn set_membership() {
+        assert_eq!(parse_and_exec("{1, 2, 3}[2]"), Ok(var!(true)));
+        assert_eq!(parse_and_exec("{1, 2, 3}[5]"), Ok(var!(false)));
+        assert_eq!(
+            parse_and_exec(r#"{1, 2, 3}["a"]"#),
+            Err(Error::CannotIndexWith(r#""a""#.into()))
+        );
+    }
+
+    #[test]
+    fn set_operators() {
+        assert_eq!(
+            parse_and_exec("{1, 2, 3} | {3, 4}"),
+            Ok(Set::from_iter([var!(1), var!(2), var!(3), var!(4)]).into())
+        );
+        assert_eq!(
+            parse_and_exec("{1, 2, 3} & {3, 4}"),
+            Ok(Set::from_iter([var!(3)]).into())
+        );
+        assert_eq!(
+            parse_and_exec("{1, 2, 3} ^ {3, 4}"),
+            Ok(Set::from_iter([var!(1), var!(2), var!(4)]).into())
+        );
+        assert_eq!(
+            parse_and_exec("{1, 2, 3} - {3, 4}"),
+            Ok(Set::from_iter([var!(1), var!(2)]).into())
+        );
+        assert_eq!(
+            parse_and_exec(r#"{1, 2} | {"a",}"#).map(|set| set.as_type()),
+            Ok(var_type!({ int | string }))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "x := mut {int} {1, 2};
+                x |= {5,};
+                x -= {1,};
+                *x"
+            ),
+            Ok(Set::from_iter([var!(2), var!(5)]).into())
+        );
+        assert_eq!(
+            parse_and_exec(r#"x := mut {1, 2}; x |= {"a",}"#),
+            Err(Error::CannotDo2(
+                var_type!(mut {int}),
+                BinOperator::AssignBitwiseOr,
+                var_type!({ string })
+            ))
+        );
+        assert_eq!(
+            parse_and_exec("{1, 2} | 5"),
+            Err(Error::CannotDo2(
+                var_type!({ int }),
+                BinOperator::BitwiseOr,
+                var_type!(int)
+            ))
+        );
+    }
+
+    #[test]
+    fn set_iteration() {
+        assert_eq!(parse_and_exec("{3, 1, 2}~$]"), Ok(var!([3, 1, 2])));
+        assert_eq!(
+            parse_and_exec("[1, 2, 1, 3, 2]~$}"),
+            Ok(Set::from_iter([var!(1), var!(2), var!(3)]).into())
+        );
+        assert_eq!(
+            parse_and_exec("[1, 2, 1]~$}").map(|set| set.as_type()),
+            Ok(var_type!({ int }))
+        );
+        assert_eq!(
+            parse_and_exec("[1.5]~$}"),
+            Err(Error::CannotBeKey(var_type!(float)))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
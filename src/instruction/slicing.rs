@@ -1,11 +1,10 @@
 use std::sync::Arc;
 
-use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
-};
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
 use crate::{
-    Error, ExecError, Interpreter,
+    Error, ExecError,
     variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -86,44 +85,45 @@ impl Slicing {
         }
         .into())
     }
+}
 
-    fn exec_index(
-        index: &Option<InstructionWithStr>,
-        interpreter: &mut Interpreter,
-    ) -> Result<Option<isize>, super::ExecStop> {
-        let exec = |ins: &InstructionWithStr| ins.exec(interpreter);
-        let start = index
-            .as_ref()
-            .map(exec)
-            .transpose()?
-            .map(Variable::into_int)
-            .transpose()
-            .unwrap()
-            .map(|i| i as isize);
-        Ok(start)
+impl Compile for Slicing {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.lhs);
+        for index in [&self.start, &self.stop, &self.step].into_iter().flatten() {
+            compiler.compile(index);
+        }
+        compiler.emit(Op::Slice {
+            start: self.start.is_some(),
+            stop: self.stop.is_some(),
+            step: self.step.is_some(),
+        });
     }
 }
 
-impl Exec for Slicing {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let lhs = self.lhs.exec(interpreter)?;
-
-        let start = Slicing::exec_index(&self.start, interpreter)?.into();
-        let end = Slicing::exec_index(&self.stop, interpreter)?.into();
-        let step = Slicing::exec_index(&self.step, interpreter)?;
+/// Returns slice of string or array, missing indexes are None
+pub(crate) fn exec(
+    lhs: Variable,
+    start: Option<Variable>,
+    stop: Option<Variable>,
+    step: Option<Variable>,
+) -> Variable {
+    let index = |index: Option<Variable>| index.map(|index| index.into_int().unwrap() as isize);
+    let s = slyce::Slice {
+        start: index(start).into(),
+        end: index(stop).into(),
+        step: index(step),
+    };
 
-        let s = slyce::Slice { start, end, step };
-
-        if let Variable::String(lhs) = lhs {
-            let chars: Box<[char]> = lhs.chars().collect();
-            let result: String = s.apply(&chars).cloned().collect();
-            return Ok(result.into());
-        }
-
-        let array = lhs.into_array().unwrap();
-        let result: Arc<[Variable]> = s.apply(array.as_ref()).cloned().collect();
-        Ok(result.into())
+    if let Variable::String(lhs) = lhs {
+        let chars: Box<[char]> = lhs.chars().collect();
+        let result: String = s.apply(&chars).cloned().collect();
+        return result.into();
     }
+
+    let array = lhs.into_array().unwrap();
+    let result: Arc<[Variable]> = s.apply(array.as_ref()).cloned().collect();
+    result.into()
 }
 
 impl Recreate for Slicing {
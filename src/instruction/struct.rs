@@ -1,11 +1,8 @@
-use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
-};
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
 use crate::{
     Error, ExecError,
-    instruction::ExecStop,
-    interpreter::{Interpreter, VariableMap},
-    variable::{ReturnType, StructType, Type, Variable},
+    variable::{ReturnType, StructType, Type},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -27,7 +24,7 @@ impl Struct {
             .map(|pair| {
                 if pair.as_rule() == Rule::ident {
                     let ident: Arc<str> = pair.as_str().into();
-                    let value = InstructionWithStr::new_ident(ident.clone(), local_variables)?;
+                    let value = InstructionWithStr::new_ident(pair, local_variables)?;
                     return Ok((ident, value));
                 }
                 let mut inner = pair.into_inner();
@@ -45,19 +42,12 @@ impl Struct {
     }
 }
 
-impl Exec for Struct {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let vm = self
-            .idents
-            .iter()
-            .zip(self.values.iter())
-            .map(|(ident, value)| {
-                let value = value.exec(interpreter)?;
-                let ident = ident.clone();
-                Ok((ident, value))
-            })
-            .collect::<Result<VariableMap, ExecStop>>()?;
-        Ok(Variable::Struct(vm.into()))
+impl Compile for Struct {
+    fn compile(&self, compiler: &mut Compiler) {
+        for value in self.values.iter() {
+            compiler.compile(value);
+        }
+        compiler.emit(Op::Struct(self.idents.clone()));
     }
 }
 
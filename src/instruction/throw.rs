@@ -0,0 +1,20 @@
+use super::{
+    Instruction, InstructionWithStr, local_variable::LocalVariables,
+    unary_operation::UnaryOperation,
+};
+use crate::{Error, unary_operator::UnaryOperator};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+
+pub fn create(
+    pair: Pair<Rule>,
+    local_variables: &mut LocalVariables,
+) -> Result<Instruction, Error> {
+    let pair = pair.into_inner().next().unwrap();
+    let instruction = InstructionWithStr::new_expression(pair, local_variables)?.instruction;
+    Ok(UnaryOperation {
+        instruction,
+        op: UnaryOperator::Throw,
+    }
+    .into())
+}
@@ -1,11 +1,11 @@
 use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
+    Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
     recreate_instructions,
 };
 use crate::{
     Error, ExecError,
-    interpreter::Interpreter,
     variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
@@ -44,10 +44,12 @@ impl Tuple {
     }
 }
 
-impl Exec for Tuple {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let elements = interpreter.exec(&self.elements)?;
-        Ok(Variable::Tuple(elements))
+impl Compile for Tuple {
+    fn compile(&self, compiler: &mut Compiler) {
+        for element in self.elements.iter() {
+            compiler.compile(element);
+        }
+        compiler.emit(Op::Tuple(self.elements.len()));
     }
 }
 
@@ -1,17 +1,16 @@
-use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
-};
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
 use crate::{
-    Error, ExecError, Interpreter,
+    Error, ExecError,
     variable::{ReturnType, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::Rule;
 
 #[derive(Debug, Clone)]
 pub struct TupleAccess {
-    tuple: InstructionWithStr,
-    index: usize,
+    pub tuple: InstructionWithStr,
+    pub index: usize,
 }
 
 impl TupleAccess {
@@ -53,9 +52,9 @@ impl Recreate for TupleAccess {
     }
 }
 
-impl Exec for TupleAccess {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let tuple = self.tuple.exec(interpreter)?.into_tuple().unwrap();
-        Ok(tuple[self.index].clone())
+impl Compile for TupleAccess {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.tuple);
+        compiler.emit(Op::TupleAccess(self.index));
     }
 }
@@ -0,0 +1,130 @@
+use super::{Instruction, local_variable::LocalVariables};
+use crate::{Error, variable::Variable};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+use std::sync::Arc;
+
+pub fn create(pair: Pair<Rule>, local_variables: &LocalVariables) -> Result<Instruction, Error> {
+    let mut inner = pair.into_inner();
+    let ident: Arc<str> = inner.next().unwrap().as_str().into();
+    let var_type = local_variables.create_type(inner.next().unwrap())?;
+    local_variables.insert_type(ident, var_type);
+    Ok(Variable::Void.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter,
+        variable::{Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn type_declaration() {
+        assert_eq!(
+            parse_and_exec(
+                "type Point = struct{x: int, y: int}
+                type Points = [Point]
+                sum_x := (points: Points) -> int {
+                    return points~ @ (point: Point) -> int { return point.x } $+
+                }
+                sum_x([struct{x := 1, y := 2}, struct{x := 5, y := 0}])"
+            ),
+            Ok(var!(6))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "type Result = int | struct{error_code: int, msg: string}
+                f := (a: Result) -> int {
+                    if x: int = a return x
+                    return -1
+                }
+                (f(5), f(struct{error_code := 3, msg := \"error\"}))"
+            ),
+            Ok(var!((5, -1)))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "m := mod {
+                    type Number = int | float
+                    zero := 0
+                }
+                f := (n: Number) -> Number { return n }
+                f(2.5)"
+            ),
+            Ok(var!(2.5))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "{
+                    type Number = int | float
+                }
+                f := (n: Number) -> Number { return n }"
+            ),
+            Err(Error::TypeDoesntExist("Number".into()))
+        );
+        assert_eq!(
+            parse_and_exec("type Integer = int\nx := mut Integer 5;\n*x"),
+            Ok(var!(5))
+        );
+        assert_eq!(
+            parse_and_exec("type Integer = int\nx := mut Integer \"5\""),
+            Err(Error::WrongInitialization {
+                declared: var_type!(int),
+                given: "\"5\"".into(),
+                given_type: var_type!(string)
+            })
+        );
+        assert_eq!(parse_and_exec("x := 5\ny := mut x - 1;\n*y"), Ok(var!(4)));
+        assert_eq!(
+            parse_and_exec("x := 1\nt := mut (x, x)\ny := *t\ny"),
+            Ok(var!((1, 1)))
+        );
+        assert_eq!(
+            parse_and_exec("x := 1\nt := mut [x]\ny := *t\ny"),
+            Ok(var!([1]))
+        );
+        assert_eq!(
+            parse_and_exec("type Integer = int\n[5, \"a\", 5.5]~ ? Integer $]"),
+            Ok(var!([5]))
+        );
+        assert_eq!(
+            parse_and_exec("is_int := (x: int) -> bool { return x == 5 }\n[5, 6]~ ? is_int $]"),
+            Ok(var!([5]))
+        );
+        assert_eq!(
+            parse_and_exec("type Integer = int\nx := mut [Integer] [5, 6];\n*x"),
+            Ok(var!([5, 6]))
+        );
+        assert_eq!(
+            parse_and_exec("type Integer = int\n[5, \"a\", 5.5]~ ? float | Integer $]"),
+            Ok(var!([5, 5.5]))
+        );
+    }
+
+    #[test]
+    fn type_registered_in_interpreter() {
+        let mut interpreter = Interpreter::without_stdlib();
+        interpreter.insert_type("Point".into(), var_type!(struct{x: float, y: float}));
+        let code = Code::parse(
+            &interpreter,
+            "(point: Point | ()) -> float {
+                match point {
+                    point: Point => return point.x + point.y,
+                    => return 0.0,
+                }
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            code.exec().unwrap().as_type(),
+            var_type!(interpreter => (Point | ()) -> float)
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
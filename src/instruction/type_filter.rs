@@ -1,10 +1,10 @@
-use super::{
-    Exec, ExecResult, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables,
-};
+use super::{Compile, Instruction, InstructionWithStr, Recreate, local_variable::LocalVariables};
 use crate::{
-    self as simplesl, BinOperator, Code, Error, ExecError,
-    interpreter::Interpreter,
-    variable::{ReturnType, Type, Variable},
+    self as simplesl, BinOperator, Error, ExecError,
+    function::Function,
+    interpreter::CallDepth,
+    variable::{ReturnType, Type, Typed, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_macros::var_type;
@@ -20,9 +20,10 @@ impl TypeFilter {
     pub fn create_instruction(
         iterator: InstructionWithStr,
         var_type: Pair<Rule>,
+        local_variables: &LocalVariables,
     ) -> Result<Instruction, Error> {
         let array_type = iterator.return_type();
-        let var_type = Type::from(var_type);
+        let var_type = local_variables.create_type(var_type)?;
         if !array_type.is_iterator() {
             return Err(Error::CannotDo2(array_type, BinOperator::Filter, var_type));
         }
@@ -30,33 +31,26 @@ impl TypeFilter {
     }
 }
 
-impl Exec for TypeFilter {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let iterator = self.iterator.exec(interpreter)?;
-        let mut interpreter = interpreter.create_layer();
-        interpreter.insert("iterator".into(), iterator);
-        let default_value = Variable::of_type(&self.var_type).unwrap();
-        interpreter.insert("default".into(), default_value);
-        Ok(Code::parse(
-            &interpreter,
-            &format!(
-                "() -> (bool, {}) {{
-                    loop {{
-                        res := iterator();
-                        (con, value) := res;
-                        if !con return (false, default);
-                        if value:{0} = value return (true, value);
-                    }}
-                    return (false, default);
-                }}",
-                self.var_type
-            ),
-        )
-        .unwrap()
-        .exec()?)
+impl Compile for TypeFilter {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile(&self.iterator);
+        compiler.emit(Op::TypeFilter(self.var_type.clone()));
     }
 }
 
+/// Returns iterator over elements of iterator matching var_type
+pub(crate) fn exec(iterator: Variable, var_type: &Type, call_depth: CallDepth) -> Variable {
+    let iterator = iterator.into_function().unwrap().iter(call_depth);
+    let call_depth = iterator.call_depth();
+    let filtered = var_type.clone();
+    let elements = iterator.filter(move |element| {
+        element
+            .as_ref()
+            .map_or(true, |element| element.as_type().matches(&filtered))
+    });
+    Function::native_adaptor(elements, var_type.clone(), call_depth).into()
+}
+
 impl Recreate for TypeFilter {
     fn recreate(&self, local_variables: &mut LocalVariables) -> Result<Instruction, ExecError> {
         let iterator = self.iterator.recreate(local_variables)?;
@@ -1,7 +1,7 @@
 mod iter;
 use super::{
-    Exec, ExecResult, ExecStop, Instruction, InstructionWithStr, Recreate, at,
-    function::call,
+    BinOperation, Compile, ExecResult, ExecStop, Instruction, InstructionWithStr, Recreate, at,
+    function::{Propagate, call},
     local_variable::LocalVariables,
     prefix_op::{indirection, not, unary_minus},
     reduce::{self, bool_reduce, collect, product, sum},
@@ -10,10 +10,12 @@ use super::{
     type_filter::TypeFilter,
 };
 use crate::{
-    Error, Interpreter,
+    BinOperator, Error, ExecError,
     instruction::field_access::FieldAccess,
+    interpreter::CallDepth,
     unary_operator::UnaryOperator,
-    variable::{ReturnType, Type},
+    variable::{ReturnType, Type, Variable},
+    vm::{Compiler, Op},
 };
 use pest::iterators::Pair;
 use simplesl_parser::{Rule, unexpected};
@@ -25,26 +27,47 @@ impl InstructionWithStr {
         local_variables: &LocalVariables<'_>,
     ) -> Result<Self, Error> {
         let str = format!("{} {}", lhs.str, op.as_str()).into();
+        let location = lhs.location.clone();
         let instruction = match op.as_rule() {
             Rule::at => at::create(lhs, op, local_variables),
             Rule::type_filter => {
-                TypeFilter::create_instruction(lhs, op.into_inner().next().unwrap())
+                let var_type = op.into_inner().next().unwrap();
+                // alias which isn't a type is a predicate of filter
+                if var_type.as_rule() == Rule::type_alias
+                    && local_variables.get_type(var_type.as_str()).is_none()
+                    && let Ok(predicate) = Self::new_ident(var_type.clone(), local_variables)
+                {
+                    BinOperation::create_instruction(
+                        lhs,
+                        BinOperator::Filter,
+                        predicate,
+                        local_variables,
+                    )
+                } else {
+                    TypeFilter::create_instruction(lhs, var_type, local_variables)
+                }
             }
             Rule::function_call => call::create_instruction(lhs, op, local_variables),
+            Rule::propagate => Propagate::create_instruction(lhs, local_variables),
             Rule::tuple_access => TupleAccess::create_instruction(lhs, op),
-            Rule::field_access => FieldAccess::create_instruction(lhs, op),
+            Rule::field_access => FieldAccess::create_instruction(lhs, op, local_variables),
             Rule::sum => sum::create(lhs),
             Rule::product => product::create(lhs),
             Rule::all => bool_reduce::create(lhs, UnaryOperator::All),
             Rule::reduce_any => bool_reduce::create(lhs, UnaryOperator::Any),
             Rule::bitand_reduce => reduce::bit::create(lhs, UnaryOperator::BitAnd),
             Rule::bitor_reduce => reduce::bit::create(lhs, UnaryOperator::BitOr),
-            Rule::collect => collect::create(lhs),
+            Rule::collect => collect::create(lhs, UnaryOperator::Collect),
+            Rule::collect_set => collect::create(lhs, UnaryOperator::CollectSet),
             Rule::iter => iter::create(lhs),
             Rule::slicing => Slicing::create(lhs, op, local_variables),
             rule => unexpected!(rule),
         }?;
-        Ok(Self { instruction, str })
+        Ok(Self {
+            instruction,
+            str,
+            location,
+        })
     }
 }
 
@@ -54,24 +77,57 @@ pub struct UnaryOperation {
     pub op: UnaryOperator,
 }
 
-impl Exec for UnaryOperation {
-    fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
-        let var = self.instruction.exec(interpreter)?;
-        Ok(match self.op {
-            UnaryOperator::Sum => sum::exec(var)?,
-            UnaryOperator::Product => product::exec(var)?,
-            UnaryOperator::Not => not::exec(var),
-            UnaryOperator::UnaryMinus => unary_minus::exec(var),
-            UnaryOperator::Return => return Err(ExecStop::Return(var)),
-            UnaryOperator::Indirection => indirection::exec(var),
-            UnaryOperator::FunctionCall => var.into_function().unwrap().exec(interpreter)?,
-            UnaryOperator::Collect => collect::exec(var, interpreter)?,
-            UnaryOperator::Iter => iter::exec(var),
-            UnaryOperator::All
-            | UnaryOperator::Any
-            | UnaryOperator::BitAnd
-            | UnaryOperator::BitOr => unreachable!(),
-        })
+/// Applies op to value of operand
+pub fn exec(var: Variable, op: UnaryOperator, call_depth: CallDepth) -> ExecResult {
+    Ok(match op {
+        UnaryOperator::Sum => sum::exec(var, call_depth)?,
+        UnaryOperator::Product => product::exec(var, call_depth)?,
+        UnaryOperator::Not => not::exec(var),
+        UnaryOperator::UnaryMinus => unary_minus::exec(var),
+        UnaryOperator::Return => return Err(ExecStop::Return(var)),
+        UnaryOperator::Throw => return Err(ExecStop::Error(ExecError::User(var))),
+        UnaryOperator::Indirection => indirection::exec(var),
+        UnaryOperator::FunctionCall => var
+            .into_function()
+            .unwrap()
+            .exec_with_args(&[], call_depth)?,
+        UnaryOperator::Collect => collect::exec(var, call_depth)?,
+        UnaryOperator::CollectSet => collect::exec_set(var, call_depth)?,
+        UnaryOperator::Iter => iter::exec(var),
+        UnaryOperator::All
+        | UnaryOperator::Any
+        | UnaryOperator::BitAnd
+        | UnaryOperator::BitOr
+        | UnaryOperator::Yield => {
+            unreachable!()
+        }
+    })
+}
+
+impl Compile for UnaryOperation {
+    fn compile(&self, compiler: &mut Compiler) {
+        match (self.op, &self.instruction) {
+            (UnaryOperator::Return, Instruction::BinOperation(call))
+                if compiler.tail_calls() && call.compile_call(compiler, Op::TailCall) => {}
+            (UnaryOperator::Return, Instruction::UnaryOperation(call))
+                if compiler.tail_calls() && call.op == UnaryOperator::FunctionCall =>
+            {
+                call.instruction.compile(compiler);
+                compiler.emit(Op::TailCall(0));
+            }
+            (UnaryOperator::FunctionCall, instruction) => {
+                instruction.compile(compiler);
+                compiler.emit(Op::Call(0));
+            }
+            (UnaryOperator::Yield, instruction) => {
+                instruction.compile(compiler);
+                compiler.emit(Op::Yield);
+            }
+            (op, instruction) => {
+                instruction.compile(compiler);
+                compiler.emit(Op::Unary(op));
+            }
+        }
     }
 }
 
@@ -98,12 +154,15 @@ impl ReturnType for UnaryOperation {
             UnaryOperator::Indirection => indirection::return_type(return_type),
             UnaryOperator::FunctionCall => return_type.return_type().unwrap(),
             UnaryOperator::Collect => collect::return_type(return_type),
+            UnaryOperator::CollectSet => collect::set_return_type(return_type),
             UnaryOperator::Iter => iter::return_type(return_type),
+            UnaryOperator::Yield => Type::Void,
             UnaryOperator::All
             | UnaryOperator::Any
             | UnaryOperator::BitAnd
             | UnaryOperator::BitOr
-            | UnaryOperator::Return => Type::Never,
+            | UnaryOperator::Return
+            | UnaryOperator::Throw => Type::Never,
         }
     }
 }
@@ -1,63 +1,67 @@
 use super::UnaryOperation;
 use crate::{
-    self as simplesl, Code, Error, Interpreter,
+    self as simplesl, Error,
     function::Function,
     instruction::{Instruction, InstructionWithStr},
     unary_operator::UnaryOperator,
-    variable::{ReturnType, Type, Typed, Variable},
+    variable::{ReturnType, Type, Variable},
 };
 use lazy_static::lazy_static;
 use simplesl_macros::var_type;
-use std::sync::Arc;
 
 lazy_static! {
-    static ref ITER: Arc<Function> = Code::parse(
-        &Interpreter::with_stdlib(),
-        "(array: [int], default: int) -> () -> (bool, int) {
-            i := mut -1;
-            len := std.len(array)
-            return () -> (bool, int) {
-                i+=1;
-                if *i < len {
-                    return (true, array[*i])
-                }
-                return (false, default)
-            }
-        } "
-    )
-    .unwrap()
-    .exec()
-    .unwrap()
-    .into_function()
-    .unwrap();
+    static ref ACCEPTED_TYPE: Type = var_type!([any] | {any: any} | {any});
 }
 
 pub(crate) fn exec(var: Variable) -> Variable {
-    let element_type = var.as_type().element_type().unwrap();
-    let default = Variable::of_type(&element_type).unwrap_or(Variable::Void);
-    let result = ITER
-        .exec_with_args(&[var, default])
-        .unwrap()
-        .into_function()
-        .unwrap();
-    let mut result = Arc::unwrap_or_clone(result);
-    result.return_type = var_type!((bool, element_type));
-    result.into()
+    if let Variable::Map(map) = var {
+        let key_type = map.key_type().clone();
+        let value_type = map.value_type().clone();
+        let entries = (0..map.len()).map(move |i| {
+            let (key, value) = map.get_index(i).unwrap();
+            Ok((key.clone(), value.clone()).into())
+        });
+        return Function::native_iter(entries, var_type!((key_type, value_type))).into();
+    }
+    if let Variable::Set(set) = var {
+        let element_type = set.element_type().clone();
+        let elements = (0..set.len()).map(move |i| Ok(set.get_index(i).unwrap().clone()));
+        return Function::native_iter(elements, element_type).into();
+    }
+    let array = var.into_array().unwrap();
+    let element_type = array.element_type.clone();
+    let elements = array.elements.clone();
+    let elements = (0..elements.len()).map(move |i| Ok(elements[i].clone()));
+    Function::native_iter(elements, element_type).into()
 }
 
 pub(crate) fn return_type(lhs: Type) -> Type {
-    let element_type = lhs.element_type().unwrap();
+    let element_type = element_type(&lhs);
     var_type!(() -> (bool, element_type))
 }
 
+/// Returns type of elements returned by iterator over array, map or set of type var_type
+fn element_type(var_type: &Type) -> Type {
+    match var_type {
+        Type::Array(element_type) | Type::Set(element_type) => element_type.as_ref().clone(),
+        Type::Map(map_type) => {
+            let key_type = map_type.key.clone();
+            let value_type = map_type.value.clone();
+            var_type!((key_type, value_type))
+        }
+        Type::Multi(multi) => multi.iter().map(element_type).reduce(Type::concat).unwrap(),
+        _ => Type::Never,
+    }
+}
+
 pub(crate) fn create(lhs: InstructionWithStr) -> Result<Instruction, Error> {
     let op = UnaryOperator::Iter;
     let lhs_type = lhs.return_type();
-    if !lhs_type.matches(&var_type!([any])) {
+    if !lhs_type.matches(&ACCEPTED_TYPE) {
         return Err(Error::IncorectUnaryOperatorOperand {
             ins: lhs.str,
             op,
-            expected: var_type!([any]),
+            expected: ACCEPTED_TYPE.clone(),
             given: lhs_type,
         });
     }
@@ -85,7 +89,7 @@ mod tests {
             Err(Error::IncorectUnaryOperatorOperand {
                 ins: "45".into(),
                 op: OP,
-                expected: var_type!([any]),
+                expected: var_type!([any] | {any: any} | {any}),
                 given: var_type!(int)
             })
         );
@@ -94,7 +98,7 @@ mod tests {
             Err(Error::IncorectUnaryOperatorOperand {
                 ins: r#""abc""#.into(),
                 op: OP,
-                expected: var_type!([any]),
+                expected: var_type!([any] | {any: any} | {any}),
                 given: var_type!(string)
             })
         );
@@ -108,7 +112,7 @@ mod tests {
             Err(Error::IncorectUnaryOperatorOperand {
                 ins: "x ()".into(),
                 op: OP,
-                expected: var_type!([any]),
+                expected: var_type!([any] | {any: any} | {any}),
                 given: var_type!([int] | string)
             })
         );
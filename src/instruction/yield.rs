@@ -0,0 +1,231 @@
+use super::{
+    Instruction, InstructionWithStr, local_variable::LocalVariables,
+    unary_operation::UnaryOperation,
+};
+use crate::{Error, unary_operator::UnaryOperator, variable::ReturnType};
+use pest::iterators::Pair;
+use simplesl_parser::Rule;
+
+pub fn create(
+    pair: Pair<Rule>,
+    local_variables: &mut LocalVariables,
+) -> Result<Instruction, Error> {
+    if local_variables.function().is_none() {
+        return Err(Error::YieldOutsideFunction);
+    }
+    let pair = pair.into_inner().next().unwrap();
+    let instruction = InstructionWithStr::new_expression(pair, local_variables)?.instruction;
+    local_variables
+        .function()
+        .unwrap()
+        .check_yield(instruction.return_type())?;
+    Ok(UnaryOperation {
+        instruction,
+        op: UnaryOperator::Yield,
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, ExecError, Interpreter,
+        variable::{Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn generators() {
+        let countdown = "countdown := (n: int) {
+            i := mut n;
+            while *i > 0 {
+                yield *i;
+                i -= 1;
+            }
+        }";
+        assert_eq!(
+            parse_and_exec(&format!("{countdown} countdown(3)$]")),
+            Ok(var!([3, 2, 1]))
+        );
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{countdown}
+                countdown(4) @ (x: int) -> int {{ return x * 10 }} $]"
+            )),
+            Ok(var!([40, 30, 20, 10]))
+        );
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{countdown}
+                sum := mut 0;
+                for x in countdown(4) {{ sum += x }}
+                *sum"
+            )),
+            Ok(var!(10))
+        );
+        let pairs = "pairs := (n: int) -> () -> (bool, (int, int)) {
+            for i in 0..n {
+                for j in 0..n {
+                    if j > i { break }
+                    {
+                        yield (i, j)
+                    }
+                }
+            }
+        }";
+        assert_eq!(
+            parse_and_exec(&format!(
+                "{pairs}
+                pairs(3) @ (p: (int, int)) -> int {{ return p.0 * 10 + p.1 }} $]"
+            )),
+            Ok(var!([0, 10, 11, 20, 21, 22]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "naturals := () {
+                    i := mut 0;
+                    loop {
+                        yield *i;
+                        i += 1;
+                    }
+                }
+                squares := mut [int] [];
+                for x in naturals() {
+                    if x == 4 { break }
+                    squares = *squares + [x * x];
+                }
+                *squares"
+            ),
+            Ok(var!([0, 1, 4, 9]))
+        );
+    }
+
+    #[test]
+    fn generator_types() {
+        let interpreter = Interpreter::without_stdlib();
+        let code = Code::parse(
+            &interpreter,
+            "gen := (flag: bool) {
+                yield 1;
+                if flag { return }
+                yield \"a\"
+            }
+            gen",
+        )
+        .unwrap();
+        assert_eq!(
+            code.exec().map(|function| function.as_type()),
+            Ok(var_type!((bool) -> () -> (bool, int | string)))
+        );
+        assert_eq!(
+            parse_and_exec("gen := () -> () -> (bool, int) { yield \"a\" }"),
+            Err(Error::WrongYield {
+                function_name: Some("gen".into()),
+                element_type: var_type!(int),
+                yielded: var_type!(string)
+            })
+        );
+        assert_eq!(
+            Code::parse(
+                &interpreter,
+                "gen := () -> () -> (bool, int) { yield \"a\" }"
+            )
+            .unwrap_err()
+            .to_string(),
+            "Cannot yield string from generator gen\nGenerator gen declared to yield int"
+        );
+        assert_eq!(
+            parse_and_exec("gen := () -> int { yield 5 }"),
+            Err(Error::WrongGeneratorType(var_type!(int)))
+        );
+        assert_eq!(
+            parse_and_exec("gen := () { yield 5; return 5 }"),
+            Err(Error::WrongReturn {
+                function_name: Some("gen".into()),
+                function_return_type: var_type!(()),
+                returned: var_type!(int)
+            })
+        );
+        assert_eq!(parse_and_exec("yield 5"), Err(Error::YieldOutsideFunction));
+    }
+
+    #[test]
+    fn generator_errors() {
+        assert_eq!(
+            parse_and_exec(
+                "gen := () {
+                    yield 1;
+                    throw \"stop\"
+                }
+                gen()$]"
+            ),
+            Err(Error::User(var!("stop")))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "gen := () {
+                    yield 1;
+                    yield 2;
+                }
+                it := gen();
+                (it(), it(), it(), it())"
+            ),
+            Ok(var!(((true, 1), (true, 2), (false, 0), (false, 0))))
+        );
+    }
+
+    #[test]
+    fn recursive_generators() {
+        let nested = "nested := (n: int) -> () -> (bool, int) {
+            if n == 0 {
+                yield 0;
+                return
+            }
+            for x in nested(n - 1) {
+                yield x + 1
+            }
+        }";
+        assert_eq!(
+            parse_and_exec(&format!("{nested} nested(400)$]")),
+            Ok(var!([400]))
+        );
+        let error = Code::parse(
+            &Interpreter::without_stdlib(),
+            &format!("{nested} nested(1200)$]"),
+        )
+        .unwrap()
+        .exec()
+        .unwrap_err();
+        assert_eq!(error.root(), &ExecError::StackOverflow("nested".into()));
+        assert_eq!(
+            parse_and_exec(
+                "f := (n: int) -> int {
+                    return match n {
+                        0 => 0,
+                        x: int => 1 + f(x - 1),
+                    }
+                }
+                gen := () { yield f(998) }
+                gen()$]"
+            ),
+            Ok(var!([998]))
+        );
+    }
+
+    #[test]
+    fn many_generators() {
+        assert_eq!(
+            parse_and_exec(
+                "gen := (n: int) -> () -> (bool, int) { yield n }
+                gens := (0..20000) @ gen $]
+                gens~ @ (g: () -> (bool, int)) -> int { return g().1 } $+"
+            ),
+            Ok(var!(199990000))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
@@ -1,4 +1,5 @@
-use crate::instruction::{Exec, ExecStop, InstructionWithStr};
+use crate::function::Function;
+use crate::instruction::local_variable::Slot;
 use crate::stdlib::Std;
 use crate::variable::*;
 use std::collections::HashMap;
@@ -8,12 +9,65 @@ use std::sync::Arc;
 #[must_use]
 pub struct Interpreter<'a> {
     variables: VariableMap,
+    /// Local variables of the function being executed indexed by their slots
+    frame: Vec<Variable>,
+    /// Function being executed, its recursive calls in tail position reuse the frame
+    function: Option<Arc<Function>>,
+    /// Nesting of function call executed with this interpreter
+    call_depth: CallDepth,
+    types: HashMap<Arc<str>, Type>,
     lower_layer: Option<&'a Self>,
 }
 
 pub type VariableMap = HashMap<Arc<str>, Variable>;
 
+/// Number of nested function calls passed from caller to callee
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CallDepth {
+    depth: usize,
+    /// Maximal number of nested function calls
+    limit: usize,
+}
+
+impl CallDepth {
+    /// Returns depth of call made at this depth, None if it would exceed the limit
+    pub fn enter(self) -> Option<Self> {
+        (self.depth < self.limit).then_some(Self {
+            depth: self.depth + 1,
+            limit: self.limit,
+        })
+    }
+
+    /// Returns depth one level deeper without checking the limit
+    pub fn nested(self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..self
+        }
+    }
+
+    /// Returns the greater of depths keeping limit of this one
+    pub fn deeper(self, other: Self) -> Self {
+        Self {
+            depth: self.depth.max(other.depth),
+            ..self
+        }
+    }
+}
+
+impl Default for CallDepth {
+    fn default() -> Self {
+        Self {
+            depth: 0,
+            limit: Interpreter::DEFAULT_RECURSION_LIMIT,
+        }
+    }
+}
+
 impl<'a> Interpreter<'a> {
+    /// Maximal number of nested function calls used unless set otherwise
+    pub const DEFAULT_RECURSION_LIMIT: usize = 1000;
+
     /// Constructs a new Interpreter with simplesl stdlib
     pub fn with_stdlib() -> Self {
         let mut interpreter = Self::without_stdlib();
@@ -25,20 +79,14 @@ impl<'a> Interpreter<'a> {
     pub fn without_stdlib() -> Self {
         Self {
             variables: VariableMap::new(),
+            frame: Vec::new(),
+            function: None,
+            call_depth: CallDepth::default(),
+            types: HashMap::new(),
             lower_layer: None,
         }
     }
 
-    pub(crate) fn exec(
-        &mut self,
-        instructions: &[InstructionWithStr],
-    ) -> Result<Arc<[Variable]>, ExecStop> {
-        instructions
-            .iter()
-            .map(|instruction| instruction.exec(self))
-            .collect()
-    }
-
     pub fn get_variable(&self, name: &str) -> Option<&Variable> {
         self.variables
             .get(name)
@@ -48,9 +96,77 @@ impl<'a> Interpreter<'a> {
         self.variables.insert(name, variable);
     }
 
+    /// Returns value of local variable stored in given slot of frame
+    pub(crate) fn local(&self, slot: Slot) -> &Variable {
+        self.frame
+            .get(slot)
+            .unwrap_or_else(|| panic!("Tried to get local variable from empty slot {slot}"))
+    }
+
+    /// Stores value of local variable in given slot of frame
+    pub(crate) fn set_local(&mut self, slot: Slot, variable: Variable) {
+        if slot >= self.frame.len() {
+            self.frame.resize(slot + 1, Variable::Void);
+        }
+        self.frame[slot] = variable;
+    }
+
+    /// Replaces frame returning the previous one
+    pub(crate) fn replace_frame(&mut self, frame: Vec<Variable>) -> Vec<Variable> {
+        std::mem::replace(&mut self.frame, frame)
+    }
+
+    /// Sets function executed by the interpreter
+    pub(crate) fn set_function(&mut self, function: Arc<Function>) {
+        self.function = Some(function);
+    }
+
+    /// Returns true if the interpreter executes given function
+    pub(crate) fn is_executing(&self, function: &Arc<Function>) -> bool {
+        self.function
+            .as_ref()
+            .is_some_and(|executed| Arc::ptr_eq(executed, function))
+    }
+
+    /// Sets maximal number of nested function calls in code executed with this interpreter.
+    /// Exceeding it raises `ExecError::StackOverflow`
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.call_depth.limit = limit;
+    }
+
+    #[must_use]
+    pub fn recursion_limit(&self) -> usize {
+        self.call_depth.limit
+    }
+
+    /// Returns nesting of function call executed with this interpreter
+    pub(crate) fn call_depth(&self) -> CallDepth {
+        self.call_depth
+    }
+
+    /// Sets nesting of function call executed with this interpreter
+    pub(crate) fn set_call_depth(&mut self, call_depth: CallDepth) {
+        self.call_depth = call_depth;
+    }
+
+    /// Returns type registered under given alias
+    pub fn get_type(&self, name: &str) -> Option<&Type> {
+        self.types
+            .get(name)
+            .or_else(|| self.lower_layer?.get_type(name))
+    }
+    /// Registers type alias that can be used in scripts parsed with this interpreter
+    pub fn insert_type(&mut self, name: Arc<str>, var_type: Type) {
+        self.types.insert(name, var_type);
+    }
+
     pub fn create_layer(&'a self) -> Self {
         Self {
             variables: VariableMap::new(),
+            frame: Vec::new(),
+            function: None,
+            call_depth: self.call_depth,
+            types: HashMap::new(),
             lower_layer: Some(self),
         }
     }
@@ -4,15 +4,21 @@ mod errors;
 pub mod function;
 mod instruction;
 mod interpreter;
+mod optimizer;
 pub mod stdlib;
 mod to_result;
 mod unary_operator;
 pub mod variable;
+mod vm;
 pub use simplesl_macros::{var, var_type};
 use std::fmt::{Debug, Display};
 pub use {
-    bin_operator::BinOperator, code::Code, errors::Error, errors::ExecError,
-    interpreter::Interpreter, to_result::ToResult,
+    bin_operator::BinOperator,
+    code::Code,
+    errors::{Error, ExecError, Frame, Location, StackTrace, Warning},
+    interpreter::Interpreter,
+    optimizer::OptLevel,
+    to_result::ToResult,
 };
 
 pub fn join<'a, T, I>(items: I, separator: &str) -> String
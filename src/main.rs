@@ -1,6 +1,6 @@
 use rustyline::{DefaultEditor, error::ReadlineError};
-use simplesl::{Code, Error, Interpreter};
-use std::{env, fs, process::ExitCode};
+use simplesl::{Code, Interpreter};
+use std::{env, process::ExitCode};
 
 fn main() -> ExitCode {
     let mut args = env::args();
@@ -37,18 +37,29 @@ fn run_shell() -> Result<(), ReadlineError> {
             Err(err) => return Err(err),
         };
         rl.add_history_entry(&line)?;
-        match Code::parse(&interpreter, &line)
-            .and_then(|code| code.exec_unscoped(&mut interpreter).map_err(Error::from))
-        {
-            Ok(result) => println!("{result:?}"),
+        match Code::parse(&interpreter, &line) {
+            Ok(code) => {
+                print_warnings(&code);
+                match code.exec_unscoped(&mut interpreter) {
+                    Ok(result) => println!("{result:?}"),
+                    Err(error) => eprintln!("{error}"),
+                }
+            }
             Err(error) => eprintln!("{error}"),
         }
     }
 }
 
-fn run_from_file(path: &str) -> Result<(), Error> {
-    let script = fs::read_to_string(path)?;
+fn run_from_file(path: &str) -> Result<(), Box<dyn std::error::Error>> {
     let interpreter = Interpreter::with_stdlib();
-    Code::parse(&interpreter, &script)?.exec()?;
+    let code = Code::parse_file(&interpreter, path)?;
+    print_warnings(&code);
+    code.exec()?;
     Ok(())
 }
+
+fn print_warnings(code: &Code) {
+    for warning in code.warnings() {
+        eprintln!("Warning: {warning}");
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{
+    ExecError,
+    function::{Param, Params},
+    instruction::{
+        Instruction, InstructionWithStr, Recreate, block::Block, function::AnonymousFunction,
+        local_variable::LocalVariables, set::Set, tuple::Tuple,
+    },
+    unary_operator::UnaryOperator,
+    variable::Variable,
+};
+use std::iter::zip;
+
+/// How thoroughly code is optimized while it is parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptLevel {
+    /// Instructions are executed as they were parsed
+    None,
+    /// Operations on constants are folded and constants are propagated through `:=`.
+    /// Ifs with constant condition are replaced with the taken branch
+    /// and code unreachable after `return`, `break`, `continue` or `throw` is removed
+    #[default]
+    Basic,
+    /// Calls of small function literals are also replaced with their bodies
+    Full,
+}
+
+/// Maximal number of instructions in expression returned by inlined function
+const INLINE_LIMIT: usize = 16;
+
+/// Returns true if execution of instruction never continues with the next one
+pub(crate) fn always_stops(instruction: &Instruction) -> bool {
+    match instruction {
+        Instruction::Break | Instruction::Continue => true,
+        Instruction::UnaryOperation(operation) => {
+            matches!(operation.op, UnaryOperator::Return | UnaryOperator::Throw)
+        }
+        Instruction::Block(Block { instructions }) => instructions
+            .last()
+            .is_some_and(|last| always_stops(&last.instruction)),
+        Instruction::IfElse(if_else) => {
+            always_stops(&if_else.if_true.instruction)
+                && always_stops(&if_else.if_false.instruction)
+        }
+        _ => false,
+    }
+}
+
+/// Returns body of function literal with params bound to args if it is small enough to be inlined.
+/// Only functions which body returns expression using nothing but constants and params are inlined
+pub(crate) fn inline(
+    function: &AnonymousFunction,
+    args: &Instruction,
+    local_variables: &LocalVariables,
+) -> Result<Option<Instruction>, ExecError> {
+    let Some(expression) = function.returned_expression() else {
+        return Ok(None);
+    };
+    if size(expression, &function.params).is_none_or(|size| size > INLINE_LIMIT) {
+        return Ok(None);
+    }
+    let args: Vec<InstructionWithStr> = match args {
+        Instruction::Tuple(Tuple { elements }) => elements.to_vec(),
+        Instruction::Variable(Variable::Tuple(values)) => values
+            .iter()
+            .cloned()
+            .map(InstructionWithStr::from)
+            .collect(),
+        _ => return Ok(None),
+    };
+    let mut local_variables = local_variables.create_layer();
+    let mut instructions = Vec::new();
+    for (Param { name, .. }, arg) in zip(function.params.iter(), args) {
+        // Constant args are propagated into the expression so binding them isn't needed
+        let constant = matches!(arg.instruction, Instruction::Variable(_));
+        let str = format!("{name} := {}", arg.str).into();
+        let set = Set::new(name.clone(), arg, &mut local_variables);
+        if !constant {
+            instructions.push(InstructionWithStr {
+                instruction: set.into(),
+                str,
+                location: None,
+            });
+        }
+    }
+    let expression = expression.recreate(&mut local_variables)?;
+    if instructions.is_empty() {
+        return Ok(Some(expression));
+    }
+    instructions.push(InstructionWithStr {
+        instruction: expression,
+        str: "return".into(),
+        location: None,
+    });
+    Ok(Some(
+        Block {
+            instructions: instructions.into(),
+        }
+        .into(),
+    ))
+}
+
+/// Returns number of instructions in expression or None if it uses anything but constants and params
+fn size(instruction: &Instruction, params: &Params) -> Option<usize> {
+    let size_of_all = |instructions: &[InstructionWithStr]| {
+        instructions
+            .iter()
+            .map(|instruction| size(&instruction.instruction, params))
+            .sum::<Option<usize>>()
+    };
+    let inner = match instruction {
+        Instruction::Variable(_) => 0,
+        Instruction::LocalVariable(ident, ..)
+            if params.iter().any(|param| param.name == *ident) =>
+        {
+            0
+        }
+        Instruction::BinOperation(operation) => {
+            size(&operation.lhs, params)? + size(&operation.rhs, params)?
+        }
+        Instruction::UnaryOperation(operation)
+            if !matches!(operation.op, UnaryOperator::Return | UnaryOperator::Yield) =>
+        {
+            size(&operation.instruction, params)?
+        }
+        Instruction::Tuple(Tuple { elements }) => size_of_all(elements)?,
+        Instruction::Array(array) => size_of_all(&array.instructions)?,
+        Instruction::TupleAccess(access) => size(&access.tuple.instruction, params)?,
+        Instruction::FieldAccess(access) => size(&access.var.instruction, params)?,
+        _ => return None,
+    };
+    Some(inner + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter, OptLevel,
+        instruction::{Instruction, block::Block, r#loop::Loop},
+        variable::Variable,
+    };
+    use simplesl_macros::var;
+
+    #[test]
+    fn constant_propagation() {
+        let code = parse("x := 2\ny := x * 3\ny + 1", OptLevel::Basic).unwrap();
+        assert!(matches!(
+            code.instructions.last().unwrap().instruction,
+            Instruction::Variable(Variable::Int(7))
+        ));
+        let code = parse("x := 2\ny := x * 3\ny + 1", OptLevel::None).unwrap();
+        assert!(matches!(
+            code.instructions.last().unwrap().instruction,
+            Instruction::BinOperation(_)
+        ));
+    }
+
+    #[test]
+    fn dead_code_elimination() {
+        let code = parse("loop { break; x := 5; x + 1 }", OptLevel::Basic).unwrap();
+        let Instruction::Loop(ref l) = code.instructions[0].instruction else {
+            panic!("expected loop")
+        };
+        let Loop(body) = &**l;
+        let Instruction::Block(Block { ref instructions }) = body.instruction else {
+            panic!("expected block")
+        };
+        assert_eq!(instructions.len(), 1);
+        let code = parse("throw 1\nx := 5\nx", OptLevel::Basic).unwrap();
+        assert_eq!(code.instructions.len(), 1);
+        assert_eq!(
+            parse("throw 1\ny := x", OptLevel::Basic).unwrap_err(),
+            Error::VariableDoesntExist("x".into())
+        );
+    }
+
+    #[test]
+    fn inlining() {
+        let script = "double := (x: int) -> int { return x * 2 }\ndouble(21)";
+        let code = parse(script, OptLevel::Full).unwrap();
+        assert!(matches!(
+            code.instructions.last().unwrap().instruction,
+            Instruction::Variable(Variable::Int(42))
+        ));
+        let code = parse(script, OptLevel::Basic).unwrap();
+        assert!(matches!(
+            code.instructions.last().unwrap().instruction,
+            Instruction::BinOperation(_)
+        ));
+        let code = parse(
+            "add := (a: int, b: int) -> int { return a + b }
+            f := (n: int) -> int { return add(n, n * 2) }
+            f(3)",
+            OptLevel::Full,
+        )
+        .unwrap();
+        assert_eq!(code.exec(), Ok(var!(9)));
+        // functions using variables other than params aren't inlined
+        let code = parse(
+            "y := mut 1
+            f := (x: int) -> int { return x + *y }
+            f(1)",
+            OptLevel::Full,
+        )
+        .unwrap();
+        assert!(matches!(
+            code.instructions.last().unwrap().instruction,
+            Instruction::BinOperation(_)
+        ));
+    }
+
+    #[test]
+    fn levels_give_same_results() {
+        let script = "fib := (n: int) -> int {
+                if n < 2 return n
+                return fib(n - 1) + fib(n - 2)
+            }
+            square := (x: int) -> int { return x * x }
+            sum := mut 0
+            for i in 0..10 {
+                if i == 7 { break }
+                if true { sum += square(i) } else { sum += 1000 }
+            }
+            (fib(10), *sum, ((a: int, b: int) -> (int, int) { return (b, a) })(1, 2))";
+        for level in [OptLevel::None, OptLevel::Basic, OptLevel::Full] {
+            assert_eq!(
+                parse(script, level).unwrap().exec(),
+                Ok(var!((55, 91, (2, 1))))
+            );
+        }
+    }
+
+    fn parse(script: &str, opt_level: OptLevel) -> Result<Code, Error> {
+        Code::parse_with_opt_level(&Interpreter::without_stdlib(), script, opt_level)
+    }
+}
@@ -1,11 +1,12 @@
 mod convert;
 mod fs;
 mod io;
+mod iter;
 mod math;
 pub(crate) mod operators;
 mod string;
 pub use self::{
-    convert::Convert, fs::FS, io::IO, math::Math, operators::Operators, string::String,
+    convert::Convert, fs::FS, io::IO, iter::Iter, math::Math, operators::Operators, string::String,
 };
 use crate as simplesl;
 use crate::variable::Variable;
@@ -16,6 +17,7 @@ decls! {
         convert:=Convert,
         fs:=FS,
         io:=IO,
+        iter:=Iter,
         math:=Math,
         operators:=Operators,
         string:=String,
@@ -24,9 +26,11 @@ decls! {
 }
 
 #[export(Len)]
-pub fn len(#[var_type([any]|string)] variable: &Variable) -> usize {
+pub fn len(#[var_type([any]|string|{any: any}|{any})] variable: &Variable) -> usize {
     match variable {
         Variable::Array(var) => var.len(),
+        Variable::Map(map) => map.len(),
+        Variable::Set(set) => set.len(),
         Variable::String(string) => string.chars().count(),
         _ => unreachable!(),
     }
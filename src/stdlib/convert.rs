@@ -4,6 +4,10 @@ use simplesl_macros::export;
 #[export(Convert)]
 mod inner {
     pub use crate::variable::Variable;
+    use crate::{
+        ExecError,
+        variable::{Array, Set},
+    };
 
     pub fn to_float(#[var_type(int|float)] value: &Variable) -> f64 {
         match value {
@@ -32,4 +36,67 @@ mod inner {
     pub fn to_string(variable: &Variable) -> String {
         variable.to_string()
     }
+
+    #[generics(T)]
+    #[return_type({T})]
+    pub fn to_set(#[var_type([T])] array: &Array) -> Result<Variable, ExecError> {
+        if !array.element_type().is_hashable() {
+            return Err(ExecError::CannotBeKey(array.element_type().clone()));
+        }
+        let mut set = Set::new_with_type(array.element_type().clone());
+        for value in array.iter() {
+            set.insert(value.clone());
+        }
+        Ok(set.into())
+    }
+
+    #[generics(T)]
+    #[return_type([T])]
+    pub fn set_to_array(#[var_type({T})] set: &Variable) -> Variable {
+        let set = set.as_set().unwrap();
+        let elements = set.iter().cloned().collect();
+        Array::new_with_type(set.element_type().clone(), elements).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as simplesl, Code, Error, Interpreter,
+        variable::{Set, Typed, Variable},
+    };
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn set_conversions() {
+        assert_eq!(
+            parse_and_exec("std.convert.to_set([3, 1, 3])"),
+            Ok(Set::from_iter([var!(3), var!(1)]).into())
+        );
+        assert_eq!(
+            parse_and_exec("std.convert.to_set([1, 2])").map(|set| set.as_type()),
+            Ok(var_type!({ int }))
+        );
+        assert_eq!(
+            parse_and_exec("std.convert.to_set([1.5, 1.5, [1]])"),
+            Err(Error::CannotBeKey(var_type!(float | [int])))
+        );
+        assert_eq!(
+            parse_and_exec("std.convert.to_set([[1], [1]])"),
+            Err(Error::CannotBeKey(var_type!([int])))
+        );
+        assert_eq!(
+            parse_and_exec(r#"std.convert.set_to_array({"b", "a", "b"})"#),
+            Ok(var!(["b", "a"]))
+        );
+        assert_eq!(
+            parse_and_exec("x := std.convert.set_to_array({1, 2}); x").map(|x| x.as_type()),
+            Ok(var_type!([int]))
+        );
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::with_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
 }
@@ -0,0 +1,487 @@
+use crate::{
+    self as simplesl, ExecError,
+    function::{Function, FunctionIter},
+    interpreter::CallDepth,
+    variable::{Type, Typed, Variable},
+};
+use simplesl_macros::export;
+use std::{
+    cmp::Ordering,
+    iter::Peekable,
+    sync::{Arc, Mutex},
+};
+
+#[export(Iter)]
+mod inner {
+    use super::{Peek, PeekNext, compare, element_type, test};
+    use crate::{
+        ExecError,
+        function::Function,
+        instruction::range::int_range,
+        interpreter::{CallDepth, VariableMap},
+        variable::{Type, Typed, Variable},
+    };
+    use std::{
+        iter,
+        sync::{Arc, Mutex},
+    };
+
+    #[return_type(() -> (bool, int))]
+    pub fn range(start: i64, end: i64) -> Result<Variable, ExecError> {
+        int_range(start, end, 1, false).map(Variable::from)
+    }
+
+    #[return_type(() -> (bool, int))]
+    pub fn range_step(start: i64, end: i64, step: i64) -> Result<Variable, ExecError> {
+        int_range(start, end, step, false).map(Variable::from)
+    }
+
+    #[generics(T)]
+    #[return_type(() -> (bool, T))]
+    pub fn repeat(#[var_type(T)] value: Variable) -> Variable {
+        let element_type = value.as_type();
+        Function::native_iter(iter::repeat(value).map(Ok), element_type).into()
+    }
+
+    #[generics(A, B)]
+    #[return_type(() -> (bool, (A, B)))]
+    pub fn zip(
+        #[var_type(() -> (bool, A))] a: Arc<Function>,
+        #[var_type(() -> (bool, B))] b: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = Type::Tuple([element_type(&a), element_type(&b)].into());
+        let (a, b) = (a.iter(call_depth), b.iter(call_depth));
+        let call_depth = a.call_depth().deeper(b.call_depth());
+        let elements = a.zip(b).map(|(a, b)| Ok(Variable::Tuple([a?, b?].into())));
+        Function::native_adaptor(elements, element_type, call_depth).into()
+    }
+
+    #[generics(T)]
+    #[return_type(() -> (bool, (int, T)))]
+    pub fn enumerate(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = Type::Tuple([Type::Int, element_type(&iter)].into());
+        let iter = iter.iter(call_depth);
+        let call_depth = iter.call_depth();
+        let elements = iter
+            .enumerate()
+            .map(|(i, element)| Ok(Variable::Tuple([(i as i64).into(), element?].into())));
+        Function::native_adaptor(elements, element_type, call_depth).into()
+    }
+
+    #[generics(T)]
+    #[return_type(() -> (bool, T))]
+    pub fn chain(
+        #[var_type(() -> (bool, T))] a: Arc<Function>,
+        #[var_type(() -> (bool, T))] b: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = element_type(&a) | element_type(&b);
+        let (a, b) = (a.iter(call_depth), b.iter(call_depth));
+        let call_depth = a.call_depth().deeper(b.call_depth());
+        Function::native_adaptor(a.chain(b), element_type, call_depth).into()
+    }
+
+    #[generics(T)]
+    #[return_type(() -> (bool, T))]
+    pub fn take(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        n: i64,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = element_type(&iter);
+        let n = usize::try_from(n).unwrap_or(0);
+        let iter = iter.iter(call_depth);
+        let call_depth = iter.call_depth();
+        Function::native_adaptor(iter.take(n), element_type, call_depth).into()
+    }
+
+    #[generics(T)]
+    #[return_type(() -> (bool, T))]
+    pub fn skip(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        n: i64,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = element_type(&iter);
+        let n = usize::try_from(n).unwrap_or(0);
+        let iter = iter.iter(call_depth);
+        let call_depth = iter.call_depth();
+        Function::native_adaptor(iter.skip(n), element_type, call_depth).into()
+    }
+
+    #[generics(T)]
+    #[return_type(() -> (bool, T))]
+    pub fn take_while(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        #[var_type((T) -> bool)] predicate: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = element_type(&iter);
+        let iter = iter.iter(call_depth);
+        let call_depth = iter.call_depth();
+        let elements = iter
+            .map_while(move |element| match test(&predicate, element, call_depth) {
+                Ok((true, element)) => Some(Ok(element)),
+                Ok((false, _)) => None,
+                Err(error) => Some(Err(error)),
+            })
+            .fuse();
+        Function::native_adaptor(elements, element_type, call_depth).into()
+    }
+
+    #[generics(T)]
+    #[return_type(() -> (bool, T))]
+    pub fn skip_while(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        #[var_type((T) -> bool)] predicate: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = element_type(&iter);
+        let mut skipping = true;
+        let iter = iter.iter(call_depth);
+        let call_depth = iter.call_depth();
+        let elements = iter.filter_map(move |element| {
+            if !skipping {
+                return Some(element);
+            }
+            match test(&predicate, element, call_depth) {
+                Ok((true, _)) => None,
+                Ok((false, element)) => {
+                    skipping = false;
+                    Some(Ok(element))
+                }
+                Err(error) => Some(Err(error)),
+            }
+        });
+        Function::native_adaptor(elements, element_type, call_depth).into()
+    }
+
+    #[generics(T, U)]
+    #[return_type(() -> (bool, U))]
+    pub fn flat_map(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        #[var_type((T) -> () -> (bool, U))] function: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = function.return_type.iter_element().unwrap();
+        let iter = iter.iter(call_depth);
+        let call_depth = iter.call_depth();
+        let elements = iter.flat_map(move |element| {
+            let inner: Box<dyn Iterator<Item = Result<Variable, ExecError>> + Send> =
+                match element.and_then(|element| function.exec_with_args(&[element], call_depth)) {
+                    Ok(inner) => Box::new(inner.into_function().unwrap().iter(call_depth)),
+                    Err(error) => Box::new(iter::once(Err(error))),
+                };
+            inner
+        });
+        Function::native_adaptor(elements, element_type, call_depth).into()
+    }
+
+    #[generics(T)]
+    #[return_type(struct{next: () -> (bool, T), peek: () -> (bool, T)})]
+    pub fn peekable(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Variable {
+        let element_type = element_type(&iter);
+        let iter = iter.iter(call_depth);
+        let call_depth = iter.call_depth();
+        let elements = Arc::new(Mutex::new(Some(iter.peekable())));
+        let next =
+            Function::native_adaptor(PeekNext(elements.clone()), element_type.clone(), call_depth);
+        let peek = Function::native_adaptor(Peek(elements), element_type, call_depth);
+        let fields =
+            VariableMap::from([("next".into(), next.into()), ("peek".into(), peek.into())]);
+        Variable::Struct(fields.into())
+    }
+
+    #[generics(T)]
+    pub fn count(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Result<i64, ExecError> {
+        iter.iter(call_depth)
+            .try_fold(0, |count, element| element.map(|_| count + 1))
+    }
+
+    #[generics(T)]
+    #[return_type(T | ())]
+    pub fn min(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Result<Variable, ExecError> {
+        let mut min = None;
+        for element in iter.iter(call_depth) {
+            let element = element?;
+            min = match min {
+                Some(min) if compare(&element, &min)?.is_ge() => Some(min),
+                _ => Some(element),
+            };
+        }
+        Ok(min.unwrap_or(Variable::Void))
+    }
+
+    #[generics(T)]
+    #[return_type(T | ())]
+    pub fn max(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Result<Variable, ExecError> {
+        let mut max = None;
+        for element in iter.iter(call_depth) {
+            let element = element?;
+            max = match max {
+                Some(max) if compare(&element, &max)?.is_le() => Some(max),
+                _ => Some(element),
+            };
+        }
+        Ok(max.unwrap_or(Variable::Void))
+    }
+
+    #[generics(T)]
+    #[return_type(T | ())]
+    pub fn find(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        #[var_type((T) -> bool)] predicate: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Result<Variable, ExecError> {
+        for element in iter.iter(call_depth) {
+            if let (true, element) = test(&predicate, element, call_depth)? {
+                return Ok(element);
+            }
+        }
+        Ok(Variable::Void)
+    }
+
+    #[generics(T)]
+    #[return_type(int | ())]
+    pub fn position(
+        #[var_type(() -> (bool, T))] iter: Arc<Function>,
+        #[var_type((T) -> bool)] predicate: Arc<Function>,
+        call_depth: CallDepth,
+    ) -> Result<Variable, ExecError> {
+        for (i, element) in iter.iter(call_depth).enumerate() {
+            if test(&predicate, element, call_depth)?.0 {
+                return Ok((i as i64).into());
+            }
+        }
+        Ok(Variable::Void)
+    }
+}
+
+/// Returns type of elements returned by iterator function
+fn element_type(iter: &Function) -> Type {
+    iter.as_type().iter_element().unwrap()
+}
+
+/// Returns result of calling predicate with element together with the element
+fn test(
+    predicate: &Arc<Function>,
+    element: Result<Variable, ExecError>,
+    call_depth: CallDepth,
+) -> Result<(bool, Variable), ExecError> {
+    let element = element?;
+    let result = predicate.exec_with_args(std::slice::from_ref(&element), call_depth)?;
+    Ok((result == Variable::Bool(true), element))
+}
+
+fn compare(lhs: &Variable, rhs: &Variable) -> Result<Ordering, ExecError> {
+    let ordering = match (lhs, rhs) {
+        (Variable::Int(lhs), Variable::Int(rhs)) => Some(lhs.cmp(rhs)),
+        (Variable::Float(lhs), Variable::Float(rhs)) => lhs.partial_cmp(rhs),
+        (Variable::Int(lhs), Variable::Float(rhs)) => (*lhs as f64).partial_cmp(rhs),
+        (Variable::Float(lhs), Variable::Int(rhs)) => lhs.partial_cmp(&(*rhs as f64)),
+        (Variable::String(_), Variable::String(_))
+        | (Variable::Array(_), Variable::Array(_))
+        | (Variable::Tuple(_), Variable::Tuple(_)) => lhs.compare(rhs),
+        _ => None,
+    };
+    ordering.ok_or_else(|| ExecError::CannotCompare(lhs.clone(), rhs.clone()))
+}
+
+/// Iterator shared by next and peek, None while it is advanced
+type Shared = Arc<Mutex<Option<Peekable<FunctionIter>>>>;
+
+/// Advances shared iterator taken out of the lock, so it can be used by callbacks it calls
+fn advance<T>(shared: &Shared, f: impl FnOnce(&mut Peekable<FunctionIter>) -> T) -> Option<T> {
+    let mut elements = shared.lock().unwrap().take()?;
+    let result = f(&mut elements);
+    *shared.lock().unwrap() = Some(elements);
+    Some(result)
+}
+
+/// Returns elements of peekable iterator
+struct PeekNext(Shared);
+
+impl Iterator for PeekNext {
+    type Item = Result<Variable, ExecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        advance(&self.0, Iterator::next)?
+    }
+}
+
+/// Returns next element of peekable iterator without consuming it
+struct Peek(Shared);
+
+impl Iterator for Peek {
+    type Item = Result<Variable, ExecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        advance(&self.0, |elements| match elements.peek()? {
+            Ok(element) => Some(Ok(element.clone())),
+            Err(_) => elements.next(),
+        })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as simplesl, Code, Error, Interpreter, variable::Variable};
+    use simplesl_macros::var;
+
+    #[test]
+    fn constructors() {
+        assert_eq!(
+            parse_and_exec("std.iter.range(1, 5)$]"),
+            Ok(var!([1, 2, 3, 4]))
+        );
+        assert_eq!(
+            parse_and_exec("std.iter.range_step(10, 0, -3)$]"),
+            Ok(var!([10, 7, 4, 1]))
+        );
+        assert_eq!(
+            parse_and_exec("std.iter.range_step(0, 10, 0)"),
+            Err(Error::ZeroStep)
+        );
+        assert_eq!(
+            parse_and_exec("std.iter.take(std.iter.repeat(\"a\"), 3)$]"),
+            Ok(var!(["a", "a", "a"]))
+        );
+    }
+
+    #[test]
+    fn adaptors() {
+        assert_eq!(
+            parse_and_exec(
+                "iter := std.iter;
+                iter.zip(iter.range(0, 5), [\"a\", \"b\"]~)$]"
+            ),
+            Ok(Variable::from([var!((0, "a")), var!((1, "b"))]))
+        );
+        assert_eq!(
+            parse_and_exec("std.iter.enumerate([\"a\", \"b\"]~)$]"),
+            Ok(Variable::from([var!((0, "a")), var!((1, "b"))]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "iter := std.iter;
+                iter.chain(iter.skip(iter.range(0, 5), 3), iter.range(7, 9))$]"
+            ),
+            Ok(var!([3, 4, 7, 8]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "iter := std.iter;
+                iter.take_while(iter.range(0, 10), (x: int) -> bool { return x < 3 })$]"
+            ),
+            Ok(var!([0, 1, 2]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "iter := std.iter;
+                iter.skip_while([1, 2, 5, 1]~, (x: int) -> bool { return x < 3 })$]"
+            ),
+            Ok(var!([5, 1]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "iter := std.iter;
+                iter.flat_map(iter.range(1, 4), (n: int) -> () -> (bool, int) {
+                    return iter.range(0, n)
+                })$]"
+            ),
+            Ok(var!([0, 0, 1, 0, 1, 2]))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "p := std.iter.peekable([1, 2]~);
+                (p.peek(), p.peek(), p.next(), p.next(), p.peek(), p.next())"
+            ),
+            Ok(var!((
+                (true, 1),
+                (true, 1),
+                (true, 1),
+                (true, 2),
+                (false, 0),
+                (false, 0)
+            )))
+        );
+    }
+
+    #[test]
+    fn consumers() {
+        assert_eq!(
+            parse_and_exec("std.iter.count(std.iter.range(0, 7))"),
+            Ok(var!(7))
+        );
+        assert_eq!(
+            parse_and_exec("iter := std.iter; [iter.min([3, 1, 2]~), iter.max([3, 1, 2]~)]"),
+            Ok(var!([1, 3]))
+        );
+        assert_eq!(
+            parse_and_exec("std.iter.max([\"a\", \"c\", \"b\"]~)"),
+            Ok(var!("c"))
+        );
+        assert_eq!(
+            parse_and_exec(r#"std.iter.max([(1, "b"), (2, "a"), (1, "c")]~)"#),
+            Ok(var!((2, "a")))
+        );
+        assert_eq!(parse_and_exec("std.iter.min([]~)"), Ok(Variable::Void));
+        assert_eq!(
+            parse_and_exec(
+                "iter := std.iter;
+                [
+                    iter.find([1, 4, 6]~, (x: int) -> bool { return x % 2 == 0 }),
+                    iter.position([1, 4, 6]~, (x: int) -> bool { return x > 4 }),
+                    iter.position([1, 4, 6]~, (x: int) -> bool { return x > 6 })
+                ]"
+            ),
+            Ok(var!([4, 2, ()]))
+        );
+        assert_eq!(
+            parse_and_exec("std.iter.max([1, \"a\"]~)"),
+            Err(Error::CannotCompare(var!("a"), var!(1)))
+        );
+    }
+
+    #[test]
+    fn precise_types() {
+        let check = |script: &str| Code::parse(&Interpreter::with_stdlib(), script).is_ok();
+        assert!(check(
+            "f := (iter: () -> (bool, (int, string))) {};
+            f(std.iter.enumerate([\"a\"]~))"
+        ));
+        assert!(check(
+            "f := (value: int | ()) {};
+            f(std.iter.min(std.iter.range(0, 3)))"
+        ));
+        assert!(!check(
+            "f := (iter: () -> (bool, int)) {};
+            f(std.iter.zip([1]~, [\"a\"]~))"
+        ));
+        assert!(!check(
+            "f := (iter: () -> (bool, string)) {};
+            f(std.iter.take(std.iter.range(0, 3), 2))"
+        ));
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::with_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
@@ -20,12 +20,18 @@ pub enum UnaryOperator {
     UnaryMinus,
     #[display("return")]
     Return,
+    #[display("throw")]
+    Throw,
+    #[display("yield")]
+    Yield,
     #[display("*")]
     Indirection,
     #[display("()")]
     FunctionCall,
     #[display("$]")]
     Collect,
+    #[display("$}}")]
+    CollectSet,
     #[display("~")]
     Iter,
 }
@@ -34,7 +40,12 @@ impl UnaryOperator {
     pub fn is_prefix(&self) -> bool {
         matches!(
             self,
-            Self::Not | Self::UnaryMinus | Self::Return | Self::Indirection
+            Self::Not
+                | Self::UnaryMinus
+                | Self::Return
+                | Self::Throw
+                | Self::Yield
+                | Self::Indirection
         )
     }
 }
@@ -1,7 +1,13 @@
 mod array;
+mod r#enum;
+mod enum_type;
 mod function_type;
+mod generics;
+mod map;
+mod map_type;
 mod multi_type;
 mod r#mut;
+mod set;
 mod struct_type;
 mod try_from;
 mod r#type;
@@ -9,17 +15,38 @@ mod type_of;
 use crate::{self as simplesl, Error, function::Function, interpreter::VariableMap};
 use derive_more::{Display, From};
 use enum_as_inner::EnumAsInner;
+pub(crate) use generics::infer_generics;
 use itertools::Itertools;
 use match_any::match_any;
 use pest::{Parser, iterators::Pair};
 use simplesl_macros::var;
 use simplesl_parser::{Rule, SimpleSLParser, unexpected};
-use std::{collections::HashMap, fmt, io, str::FromStr, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    iter::zip,
+    mem,
+    str::FromStr,
+    sync::Arc,
+};
 pub use r#type::{ReturnType, Type, Typed};
 use typle::typle;
 pub use {
-    array::Array, function_type::FunctionType, multi_type::MultiType, r#mut::Mut,
-    struct_type::StructType, type_of::TypeOf,
+    array::Array,
+    r#enum::Enum,
+    enum_type::{EnumType, VariantType},
+    function_type::FunctionType,
+    generics::Generics,
+    map::Map,
+    map_type::MapType,
+    multi_type::MultiType,
+    r#mut::Mut,
+    set::Set,
+    struct_type::StructType,
+    type_of::TypeOf,
 };
 
 #[derive(Clone, Display, EnumAsInner, From)]
@@ -41,6 +68,12 @@ pub enum Variable {
     #[from(Mut, Arc<Mut>)]
     Mut(Arc<Mut>),
     Struct(Arc<VariableMap>),
+    #[from(Enum, Arc<Enum>)]
+    Enum(Arc<Enum>),
+    #[from(Map, Arc<Map>)]
+    Map(Arc<Map>),
+    #[from(Set, Arc<Set>)]
+    Set(Arc<Set>),
     #[from]
     Void,
 }
@@ -58,6 +91,7 @@ impl Variable {
             | Variable::Function(value) => format!("{value}"),
             Variable::Array(value) => value.string(depth),
             Variable::Mut(value) => value.string(depth+1),
+            Variable::Enum(value) | Variable::Map(value) | Variable::Set(value) => value.string(depth),
             Variable::Tuple(elements) => format!("({})", elements.iter().map(|v| v.debug(depth+1)).collect::<Box<[_]>>().join(", ")),
             Variable::Struct(vm) => {
                 let elements = vm.iter().map(|(key, value)| format!("{}={}", key, value.debug(depth))).join(", ");
@@ -67,6 +101,87 @@ impl Variable {
         }
     }
 
+    /// Returns position of variant in declaration of Variable
+    fn kind(&self) -> u8 {
+        match self {
+            Variable::Bool(_) => 0,
+            Variable::Int(_) => 1,
+            Variable::Float(_) => 2,
+            Variable::String(_) => 3,
+            Variable::Function(_) => 4,
+            Variable::Array(_) => 5,
+            Variable::Tuple(_) => 6,
+            Variable::Mut(_) => 7,
+            Variable::Struct(_) => 8,
+            Variable::Enum(_) => 9,
+            Variable::Map(_) => 10,
+            Variable::Set(_) => 11,
+            Variable::Void => 12,
+        }
+    }
+
+    /// Equality used by `==` and `!=` operators. Floats are compared like in IEEE 754,
+    /// so -0.0 is equal to 0.0 and NaN isn't equal to anything
+    pub fn equals(&self, other: &Self) -> bool {
+        fn all_equal(lhs: &[Variable], rhs: &[Variable]) -> bool {
+            lhs.len() == rhs.len() && zip(lhs, rhs).all(|(lhs, rhs)| lhs.equals(rhs))
+        }
+        match (self, other) {
+            (Variable::Float(value1), Variable::Float(value2)) => value1 == value2,
+            (Variable::Array(value1), Variable::Array(value2)) => all_equal(value1, value2),
+            (Variable::Tuple(value1), Variable::Tuple(value2)) => all_equal(value1, value2),
+            (Variable::Struct(value1), Variable::Struct(value2)) => {
+                value1.len() == value2.len()
+                    && value1.iter().all(|(key, value)| {
+                        value2.get(key).is_some_and(|other| value.equals(other))
+                    })
+            }
+            (Variable::Enum(value1), Variable::Enum(value2)) => {
+                value1.enum_type == value2.enum_type
+                    && value1.variant == value2.variant
+                    && all_equal(&value1.values, &value2.values)
+            }
+            (Variable::Map(value1), Variable::Map(value2)) => {
+                value1.len() == value2.len()
+                    && value1.iter().all(|(key, value)| {
+                        value2.get(key).is_some_and(|other| value.equals(other))
+                    })
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Ordering used by comparison operators. Floats are compared like in IEEE 754,
+    /// so -0.0 is equal to 0.0 and arrays or tuples containing NaN can't be ordered
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        fn lexicographic<'a>(
+            mut lhs: impl Iterator<Item = &'a Variable>,
+            mut rhs: impl Iterator<Item = &'a Variable>,
+        ) -> Option<Ordering> {
+            loop {
+                match (lhs.next(), rhs.next()) {
+                    (Some(lhs), Some(rhs)) => match lhs.compare(rhs)? {
+                        Ordering::Equal => continue,
+                        ordering => return Some(ordering),
+                    },
+                    (Some(_), None) => return Some(Ordering::Greater),
+                    (None, Some(_)) => return Some(Ordering::Less),
+                    (None, None) => return Some(Ordering::Equal),
+                }
+            }
+        }
+        match (self, other) {
+            (Variable::Float(value1), Variable::Float(value2)) => value1.partial_cmp(value2),
+            (Variable::Array(value1), Variable::Array(value2)) => {
+                lexicographic(value1.iter(), value2.iter())
+            }
+            (Variable::Tuple(value1), Variable::Tuple(value2)) => {
+                lexicographic(value1.iter(), value2.iter())
+            }
+            _ => Some(self.cmp(other)),
+        }
+    }
+
     fn debug(&self, depth: u8) -> String {
         match_any! { self,
             Self::Int(value)
@@ -113,8 +228,35 @@ impl Variable {
                         .collect();
                 Some(Variable::Struct(vm?.into()))
             }
+            Type::Enum(enum_type) => {
+                enum_type
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .find_map(|(variant, variant_type)| {
+                        let values = variant_type
+                            .payload
+                            .iter()
+                            .map(Self::of_type)
+                            .collect::<Option<_>>()?;
+                        Some(
+                            Enum {
+                                enum_type: enum_type.clone(),
+                                variant,
+                                values,
+                            }
+                            .into(),
+                        )
+                    })
+            }
+            Type::Map(map_type) => {
+                Some(Map::new_with_type(map_type.key.clone(), map_type.value.clone()).into())
+            }
+            Type::Set(element_type) => {
+                Some(Set::new_with_type(element_type.as_ref().clone()).into())
+            }
             Type::Any => Some(Variable::Void),
-            Type::Never => None,
+            Type::Generic(_) | Type::Never => None,
         }
     }
 }
@@ -126,7 +268,8 @@ impl Typed for Variable {
             Variable::Int(_) => Type::Int,
             Variable::Float(_) => Type::Float,
             Variable::String(_) => Type::String,
-            Variable::Function(var) | Variable::Array(var) | Variable::Mut(var) => var.as_type(),
+            Variable::Function(var) | Variable::Array(var) | Variable::Mut(var)
+            | Variable::Enum(var) | Variable::Map(var) | Variable::Set(var) => var.as_type(),
             Variable::Tuple(elements) => {
                 let types = elements.iter().map(Variable::as_type).collect();
                 Type::Tuple(types)
@@ -165,12 +308,15 @@ impl PartialEq for Variable {
             (Variable::Array(value1), Variable::Array(value2))
             | (Variable::Bool(value1), Variable::Bool(value2))
             | (Variable::Int(value1), Variable::Int(value2))
-            | (Variable::Float(value1), Variable::Float(value2))
             | (Variable::String(value1), Variable::String(value2))
             | (Variable::Tuple(value1), Variable::Tuple(value2))
-            | (Variable::Struct(value1), Variable::Struct(value2)) => value1 == value2,
+            | (Variable::Struct(value1), Variable::Struct(value2))
+            | (Variable::Enum(value1), Variable::Enum(value2))
+            | (Variable::Map(value1), Variable::Map(value2))
+            | (Variable::Set(value1), Variable::Set(value2)) => value1 == value2,
             (Variable::Function(value1), Variable::Function(value2))
             | (Variable::Mut(value1), Variable::Mut(value2)) => Arc::ptr_eq(value1, value2),
+            (Variable::Float(value1), Variable::Float(value2)) => value1.total_cmp(value2).is_eq(),
             (Variable::Void, Variable::Void) => true,
             _ => false
         }
@@ -179,6 +325,68 @@ impl PartialEq for Variable {
 
 impl Eq for Variable {}
 
+impl PartialOrd for Variable {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Total ordering of variables used for sorting. Strings, arrays and tuples are ordered lexicographically.
+/// Floats are ordered with [`f64::total_cmp`] like in [`PartialEq`], so -0.0 is less than 0.0
+/// and NaN is equal to itself. Variables of different kinds are ordered by kind.
+/// Comparison operators use [`Variable::compare`] and [`Variable::equals`]
+impl Ord for Variable {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Variable::Bool(value1), Variable::Bool(value2)) => value1.cmp(value2),
+            (Variable::Int(value1), Variable::Int(value2)) => value1.cmp(value2),
+            (Variable::Float(value1), Variable::Float(value2)) => value1.total_cmp(value2),
+            (Variable::String(value1), Variable::String(value2)) => value1.cmp(value2),
+            (Variable::Array(value1), Variable::Array(value2)) => value1.iter().cmp(value2.iter()),
+            (Variable::Tuple(value1), Variable::Tuple(value2)) => value1.cmp(value2),
+            (Variable::Struct(value1), Variable::Struct(value2)) => {
+                value1.iter().sorted().cmp(value2.iter().sorted())
+            }
+            (Variable::Enum(value1), Variable::Enum(value2)) => (
+                &value1.enum_type.name,
+                value1.variant,
+                &value1.values,
+            )
+                .cmp(&(&value2.enum_type.name, value2.variant, &value2.values)),
+            (Variable::Map(value1), Variable::Map(value2)) => {
+                value1.iter().sorted().cmp(value2.iter().sorted())
+            }
+            (Variable::Set(value1), Variable::Set(value2)) => {
+                value1.iter().sorted().cmp(value2.iter().sorted())
+            }
+            (Variable::Function(value1), Variable::Function(value2)) => {
+                Arc::as_ptr(value1).cmp(&Arc::as_ptr(value2))
+            }
+            (Variable::Mut(value1), Variable::Mut(value2)) => {
+                Arc::as_ptr(value1).cmp(&Arc::as_ptr(value2))
+            }
+            (Variable::Void, Variable::Void) => Ordering::Equal,
+            (value1, value2) => value1.kind().cmp(&value2.kind()),
+        }
+    }
+}
+
+impl Hash for Variable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        // Only variants allowed as map keys are hashed by value, they can't be mutated so
+        // interior mutability can't change hash of a key. For the others hash of discriminant
+        // is consistent with PartialEq
+        match self {
+            Variable::Bool(value) => value.hash(state),
+            Variable::Int(value) => value.hash(state),
+            Variable::String(value) => value.hash(state),
+            Variable::Tuple(elements) => elements.hash(state),
+            _ => (),
+        }
+    }
+}
+
 #[doc(hidden)]
 impl TryFrom<Pair<'_, Rule>> for Variable {
     type Error = Error;
@@ -251,6 +459,13 @@ impl TryFrom<Pair<'_, Rule>> for Variable {
                 let len = parse_int(len_pair)?;
                 Ok(Array::new_repeat(value, len as usize).into())
             }
+            Rule::tuple_from_str => {
+                let elements = pair
+                    .into_inner()
+                    .map(Self::try_from)
+                    .collect::<Result<Arc<[Variable]>, Error>>()?;
+                Ok(Variable::Tuple(elements))
+            }
             Rule::struct_from_str => {
                 let vm = pair
                     .into_inner()
@@ -263,6 +478,27 @@ impl TryFrom<Pair<'_, Rule>> for Variable {
                     .collect::<Result<VariableMap, Error>>()?;
                 Ok(Variable::Struct(vm.into()))
             }
+            Rule::map_from_str => {
+                let map = pair
+                    .into_inner()
+                    .tuples()
+                    .map(|(key, value)| Ok((Variable::try_from(key)?, Variable::try_from(value)?)))
+                    .collect::<Result<Map, Error>>()?;
+                if !map.key_type.is_hashable() {
+                    return Err(Error::CannotBeKey(map.key_type));
+                }
+                Ok(map.into())
+            }
+            Rule::set_from_str => {
+                let set = pair
+                    .into_inner()
+                    .map(Self::try_from)
+                    .collect::<Result<Set, Error>>()?;
+                if !set.element_type().is_hashable() {
+                    return Err(Error::CannotBeKey(set.element_type().clone()));
+                }
+                Ok(set.into())
+            }
             Rule::void => Ok(Variable::Void),
             _ => Err(Error::CannotBeParsed(pair.as_str().into())),
         }
@@ -343,8 +579,12 @@ pub fn is_correct_variable_name(name: &str) -> bool {
 mod tests {
     use std::{collections::HashMap, str::FromStr};
 
-    use crate::variable::{Array, Variable};
+    use crate::{
+        self as simplesl, Error,
+        variable::{Array, Variable},
+    };
     use proptest::prelude::*;
+    use simplesl_macros::var_type;
 
     #[test]
     fn test_send() {
@@ -358,6 +598,62 @@ mod tests {
         assert_sync::<Variable>();
     }
     #[test]
+    fn ordering() {
+        use crate as simplesl;
+        use simplesl_macros::var;
+        use std::cmp::Ordering;
+        let mut variables = [
+            var!((2, "b")),
+            var!("b"),
+            var!([1, 2]),
+            var!(1),
+            var!("abc"),
+            var!([1]),
+            var!((1, "z")),
+            var!([0, 5, 5]),
+            var!(-3),
+            var!((2, "a")),
+        ];
+        variables.sort();
+        assert_eq!(
+            variables,
+            [
+                var!(-3),
+                var!(1),
+                var!("abc"),
+                var!("b"),
+                var!([0, 5, 5]),
+                var!([1]),
+                var!([1, 2]),
+                var!((1, "z")),
+                var!((2, "a")),
+                var!((2, "b")),
+            ]
+        );
+        assert!(var!(1.5) < var!(2.5));
+        assert!(var!(true) > var!(false));
+        assert_eq!(var!(-0.0).cmp(&var!(0.0)), Ordering::Less);
+        assert_eq!(var!([-0.0]).cmp(&var!([0.0])), Ordering::Less);
+        assert!(var!(-0.0).equals(&var!(0.0)));
+        let nan = Variable::Float(f64::NAN);
+        assert_eq!(nan.cmp(&nan), Ordering::Equal);
+        assert_eq!(nan, nan);
+        assert!(!nan.equals(&nan));
+        assert_eq!(nan.compare(&nan), None);
+        assert_eq!(
+            Variable::from([nan.clone()]).compare(&[nan.clone()].into()),
+            None
+        );
+        assert_eq!(
+            var!([-0.0, 1]).compare(&var!([0.0, 2])),
+            Some(Ordering::Less)
+        );
+        let mut floats = [nan.clone(), var!(1.5), var!(-2.0)];
+        floats.sort();
+        assert_eq!(floats[..2], [var!(-2.0), var!(1.5)]);
+        assert!(matches!(floats[2], Variable::Float(value) if value.is_nan()));
+    }
+    #[test]
     fn check_is_correct_variable_name() {
         use crate::variable::is_correct_variable_name;
         assert!(is_correct_variable_name("aDd"));
@@ -433,7 +729,23 @@ mod tests {
                 ])
                 .into()
             ))
-        )
+        );
+        assert_eq!(
+            Variable::from_str("{1: 2.5}").map(|map| map.to_string()),
+            Ok("{1: 2.5}".into())
+        );
+        assert_eq!(
+            Variable::from_str("{1.5: 2}"),
+            Err(Error::CannotBeKey(var_type!(float)))
+        );
+        assert_eq!(
+            Variable::from_str("{[1]: 2}"),
+            Err(Error::CannotBeKey(var_type!([int])))
+        );
+        assert_eq!(
+            Variable::from_str("{1.5, 2.5}"),
+            Err(Error::CannotBeKey(var_type!(float)))
+        );
     }
 
     proptest! {
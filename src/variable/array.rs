@@ -4,7 +4,7 @@ use derive_more::Display;
 use simplesl_macros::var_type;
 use std::{ops::Deref, sync::Arc};
 
-#[derive(Display, PartialEq)]
+#[derive(Clone, Display, PartialEq)]
 #[display("{}", self.string(0))]
 pub struct Array {
     pub(crate) element_type: Type,
@@ -0,0 +1,36 @@
+use super::{EnumType, Type, Typed, Variable};
+use std::sync::Arc;
+
+/// Value of enum type
+#[derive(PartialEq)]
+pub struct Enum {
+    pub enum_type: Arc<EnumType>,
+    pub variant: usize,
+    pub values: Arc<[Variable]>,
+}
+
+impl Enum {
+    pub fn variant_name(&self) -> &Arc<str> {
+        &self.enum_type.variants[self.variant].name
+    }
+
+    pub(crate) fn string(&self, depth: u8) -> String {
+        let name = format!("{}.{}", self.enum_type.name, self.variant_name());
+        if self.values.is_empty() {
+            return name;
+        }
+        let values = self
+            .values
+            .iter()
+            .map(|value| value.debug(depth + 1))
+            .collect::<Box<[_]>>()
+            .join(", ");
+        format!("{name}({values})")
+    }
+}
+
+impl Typed for Enum {
+    fn as_type(&self) -> Type {
+        Type::Enum(self.enum_type.clone())
+    }
+}
@@ -0,0 +1,30 @@
+use super::Type;
+use crate::join;
+use derive_more::Display;
+use std::sync::Arc;
+
+/// Type of tagged union declared with enum statement.
+/// Enums are compared by name and variants so two enums with the same shape are different types
+#[derive(Clone, Debug, Display, Hash, PartialEq, Eq)]
+#[display("{name}")]
+pub struct EnumType {
+    pub name: Arc<str>,
+    pub variants: Arc<[VariantType]>,
+}
+
+impl EnumType {
+    /// Returns index and type of the variant with given name
+    pub fn variant(&self, name: &str) -> Option<(usize, &VariantType)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name.as_ref() == name)
+    }
+}
+
+#[derive(Clone, Debug, Display, Hash, PartialEq, Eq)]
+#[display("{name}({})", join(payload.as_ref(), ", "))]
+pub struct VariantType {
+    pub name: Arc<str>,
+    pub payload: Arc<[Type]>,
+}
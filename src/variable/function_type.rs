@@ -1,8 +1,6 @@
 use super::{ReturnType, Type};
 use crate::join;
 use derive_more::Display;
-use pest::iterators::Pair;
-use simplesl_parser::Rule;
 use std::{iter::zip, ops::BitOr, sync::Arc};
 
 #[derive(Clone, Debug, Display, Hash, PartialEq, Eq)]
@@ -49,19 +47,6 @@ impl<T: Into<Type>> BitOr<T> for FunctionType {
     }
 }
 
-#[doc(hidden)]
-impl From<Pair<'_, Rule>> for FunctionType {
-    fn from(pair: Pair<'_, Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        let params: Arc<[Type]> = pairs.next().unwrap().into_inner().map(Type::from).collect();
-        let return_type = Type::from(pairs.next().unwrap());
-        Self {
-            params,
-            return_type,
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use simplesl_macros::var_type;
@@ -109,5 +94,15 @@ mod tests {
         assert!(!function_type3.matches(&function_type));
         assert!(!function_type3.matches(&function_type2));
         assert!(!function_type2.matches(&function_type3));
+        let int_function_type = FunctionType {
+            params: [var_type!(int)].into(),
+            return_type: var_type!(int),
+        };
+        let void_function_type = FunctionType {
+            params: [var_type!(int)].into(),
+            return_type: var_type!(()),
+        };
+        assert!(!int_function_type.matches(&void_function_type));
+        assert!(!void_function_type.matches(&int_function_type));
     }
 }
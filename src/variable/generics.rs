@@ -0,0 +1,136 @@
+use super::{FunctionType, MapType, StructType, Type};
+use std::{collections::HashMap, iter::zip, sync::Arc};
+
+/// Types bound to type parameters of generic function
+pub type Generics = HashMap<Arc<str>, Type>;
+
+impl Type {
+    /// Returns true if type contains type parameter
+    #[must_use]
+    pub fn is_generic(&self) -> bool {
+        match self {
+            Self::Generic(_) => true,
+            Self::Function(function) => {
+                function.params.iter().any(Self::is_generic) || function.return_type.is_generic()
+            }
+            Self::Array(var_type) | Self::Mut(var_type) | Self::Set(var_type) => {
+                var_type.is_generic()
+            }
+            Self::Tuple(types) => types.iter().any(Self::is_generic),
+            Self::Multi(types) => types.iter().any(Self::is_generic),
+            Self::Struct(StructType(fields)) => fields.values().any(Self::is_generic),
+            Self::Map(map_type) => map_type.key.is_generic() || map_type.value.is_generic(),
+            _ => false,
+        }
+    }
+
+    /// Replaces type parameters with types returned by `get`.
+    /// Type parameters for which `get` returns None are left unchanged
+    #[must_use]
+    pub fn substitute(&self, get: &impl Fn(&str) -> Option<Type>) -> Type {
+        if !self.is_generic() {
+            return self.clone();
+        }
+        match self {
+            Self::Generic(name) => get(name).unwrap_or_else(|| self.clone()),
+            Self::Function(function) => FunctionType {
+                params: function
+                    .params
+                    .iter()
+                    .map(|var_type| var_type.substitute(get))
+                    .collect(),
+                return_type: function.return_type.substitute(get),
+            }
+            .into(),
+            Self::Array(var_type) => Self::Array(var_type.substitute(get).into()),
+            Self::Mut(var_type) => Self::Mut(var_type.substitute(get).into()),
+            Self::Set(var_type) => Self::Set(var_type.substitute(get).into()),
+            Self::Tuple(types) => Self::Tuple(
+                types
+                    .iter()
+                    .map(|var_type| var_type.substitute(get))
+                    .collect(),
+            ),
+            Self::Multi(types) => types
+                .iter()
+                .map(|var_type| var_type.substitute(get))
+                .reduce(Type::concat)
+                .unwrap(),
+            Self::Struct(StructType(fields)) => StructType::from(
+                fields
+                    .iter()
+                    .map(|(key, var_type)| (key.clone(), var_type.substitute(get)))
+                    .collect::<HashMap<_, _>>(),
+            )
+            .into(),
+            Self::Map(map_type) => MapType {
+                key: map_type.key.substitute(get),
+                value: map_type.value.substitute(get),
+            }
+            .into(),
+            var_type => var_type.clone(),
+        }
+    }
+
+    /// Infers types of type parameters used in self from type of value given in its place.
+    /// If type inferred for the same parameter more than once matches the other one,
+    /// the wider one is kept. Otherwise they are conjoined, unless they are disjoint
+    pub fn infer(&self, given: &Type, generics: &mut Generics) {
+        match (self, given) {
+            (Self::Generic(name), given) => {
+                let inferred = match generics.get(name) {
+                    Some(inferred) if given.matches(inferred) => return,
+                    Some(inferred) if inferred.matches(given) => given.clone(),
+                    Some(inferred) => match inferred.conjoin(given) {
+                        Type::Never => return,
+                        conjoined => conjoined,
+                    },
+                    None => given.clone(),
+                };
+                generics.insert(name.clone(), inferred);
+            }
+            (Self::Array(var_type), Self::Array(given))
+            | (Self::Mut(var_type), Self::Mut(given))
+            | (Self::Set(var_type), Self::Set(given)) => var_type.infer(given, generics),
+            (Self::Tuple(types), Self::Tuple(given)) if types.len() == given.len() => {
+                for (var_type, given) in zip(types.iter(), given.iter()) {
+                    var_type.infer(given, generics);
+                }
+            }
+            (Self::Function(function), Self::Function(given))
+                if function.params.len() == given.params.len() =>
+            {
+                for (var_type, given) in zip(function.params.iter(), given.params.iter()) {
+                    var_type.infer(given, generics);
+                }
+                function.return_type.infer(&given.return_type, generics);
+            }
+            (Self::Map(map_type), Self::Map(given)) => {
+                map_type.key.infer(&given.key, generics);
+                map_type.value.infer(&given.value, generics);
+            }
+            (Self::Struct(StructType(fields)), Self::Struct(StructType(given))) => {
+                for (key, var_type) in fields.iter() {
+                    if let Some(given) = given.get(key) {
+                        var_type.infer(given, generics);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Infers types of type parameters of function with params from types of args
+pub(crate) fn infer_generics<'a>(
+    params: impl IntoIterator<Item = &'a Type>,
+    args: impl IntoIterator<Item = Type>,
+) -> Generics {
+    let mut generics = Generics::new();
+    for (param, arg) in zip(params, args) {
+        if param.is_generic() {
+            param.infer(&arg, &mut generics);
+        }
+    }
+    generics
+}
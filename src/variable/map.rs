@@ -0,0 +1,158 @@
+use super::{MapType, Type, Typed, Variable};
+use std::{collections::HashMap, fmt};
+
+/// Map preserving insertion order of its keys
+#[derive(Clone)]
+pub struct Map {
+    pub(crate) key_type: Type,
+    pub(crate) value_type: Type,
+    entries: Vec<(Variable, Variable)>,
+    indexes: HashMap<Variable, usize>,
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.string(0))
+    }
+}
+
+impl Map {
+    pub fn new_with_type(key_type: Type, value_type: Type) -> Self {
+        Self {
+            key_type,
+            value_type,
+            entries: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+
+    /// Returns map containing entries of both maps.
+    /// Values from map2 replace values of the same keys from map1
+    pub fn merge(mut map1: Self, map2: &Self) -> Self {
+        map1.key_type |= map2.key_type.clone();
+        map1.value_type |= map2.value_type.clone();
+        for (key, value) in map2.iter() {
+            map1.insert(key.clone(), value.clone());
+        }
+        map1
+    }
+
+    /// Inserts value under key, replacing value previously stored under it
+    pub fn insert(&mut self, key: Variable, value: Variable) {
+        if let Some(&index) = self.indexes.get(&key) {
+            self.entries[index].1 = value;
+            return;
+        }
+        self.indexes.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+    }
+
+    pub fn get(&self, key: &Variable) -> Option<&Variable> {
+        self.indexes.get(key).map(|&index| &self.entries[index].1)
+    }
+
+    pub fn get_mut(&mut self, key: &Variable) -> Option<&mut Variable> {
+        self.indexes
+            .get(key)
+            .map(|&index| &mut self.entries[index].1)
+    }
+
+    /// Returns entry inserted as index-th
+    pub fn get_index(&self, index: usize) -> Option<(&Variable, &Variable)> {
+        self.entries.get(index).map(|(key, value)| (key, value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns iterator over entries in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&Variable, &Variable)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn key_type(&self) -> &Type {
+        &self.key_type
+    }
+
+    pub fn value_type(&self) -> &Type {
+        &self.value_type
+    }
+
+    pub(crate) fn string(&self, depth: u8) -> String {
+        if self.is_empty() {
+            return "{:}".into();
+        }
+        format!(
+            "{{{}}}",
+            self.iter()
+                .map(|(key, value)| format!("{}: {}", key.debug(depth + 1), value.debug(depth + 1)))
+                .collect::<Box<[_]>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Typed for Map {
+    fn as_type(&self) -> Type {
+        MapType {
+            key: self.key_type.clone(),
+            value: self.value_type.clone(),
+        }
+        .into()
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key).is_some_and(|other| value == other))
+    }
+}
+
+impl FromIterator<(Variable, Variable)> for Map {
+    fn from_iter<T: IntoIterator<Item = (Variable, Variable)>>(iter: T) -> Self {
+        let mut map = Self::new_with_type(Type::Never, Type::Never);
+        for (key, value) in iter {
+            map.key_type |= key.as_type();
+            map.value_type |= value.as_type();
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as simplesl;
+    use crate::variable::{Map, Typed, Variable};
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn map() {
+        let map = Map::from_iter([
+            (var!("a"), var!(1)),
+            (var!("b"), var!(2)),
+            (var!("a"), var!(3)),
+        ]);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&var!("a")), Some(&var!(3)));
+        assert_eq!(map.get(&var!("c")), None);
+        assert_eq!(map.as_type(), var_type!({string: int}));
+        assert_eq!(map.to_string(), r#"{"a": 3, "b": 2}"#);
+        let map2 = Map::from_iter([(var!("b"), var!(2)), (var!("a"), var!(3))]);
+        assert!(map == map2);
+        let merged = Map::merge(map, &Map::from_iter([(var!(5), var!(5.5))]));
+        assert_eq!(merged.as_type(), var_type!({string | int: int | float}));
+        assert_eq!(
+            Variable::from(merged).to_string(),
+            r#"{"a": 3, "b": 2, 5: 5.5}"#
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use super::Type;
+use derive_more::Display;
+
+#[derive(Clone, Debug, Display, Hash, PartialEq, Eq)]
+#[display("{{{key}: {value}}}")]
+pub struct MapType {
+    pub key: Type,
+    pub value: Type,
+}
+
+impl MapType {
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        self.key.matches(&other.key) && self.value.matches(&other.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as simplesl;
+    use crate::variable::{MapType, Type};
+    use simplesl_macros::var_type;
+    use std::str::FromStr;
+
+    #[test]
+    fn map_type() {
+        let map_type = MapType {
+            key: Type::String,
+            value: Type::Int,
+        };
+        assert_eq!(map_type.to_string(), "{string: int}");
+        assert_eq!(var_type!({string: int}), Type::from(map_type.clone()));
+        assert_eq!(Type::from_str("{string: int}"), Ok(map_type.clone().into()));
+        assert!(map_type.matches(&map_type));
+        assert!(var_type!({string: int}).matches(&var_type!({string: int | float})));
+        assert!(var_type!({string: int}).matches(&var_type!({any: any})));
+        assert!(!var_type!({string: int | float}).matches(&var_type!({string: int})));
+        assert!(!var_type!({string: int}).matches(&var_type!({int: int})));
+        assert!(!var_type!({string: int}).matches(&var_type!([(string, int)])));
+    }
+}
@@ -0,0 +1,163 @@
+use super::{Type, Typed, Variable};
+use std::{collections::HashMap, fmt};
+
+/// Set preserving insertion order of its elements
+#[derive(Clone)]
+pub struct Set {
+    pub(crate) element_type: Type,
+    elements: Vec<Variable>,
+    indexes: HashMap<Variable, usize>,
+}
+
+impl fmt::Display for Set {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.string(0))
+    }
+}
+
+impl Set {
+    pub fn new_with_type(element_type: Type) -> Self {
+        Self {
+            element_type,
+            elements: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+
+    /// Inserts value into set. Returns false if set already contained it
+    pub fn insert(&mut self, value: Variable) -> bool {
+        if self.indexes.contains_key(&value) {
+            return false;
+        }
+        self.indexes.insert(value.clone(), self.elements.len());
+        self.elements.push(value);
+        true
+    }
+
+    pub fn contains(&self, value: &Variable) -> bool {
+        self.indexes.contains_key(value)
+    }
+
+    /// Returns set containing elements of both sets
+    pub fn union(mut set1: Self, set2: &Self) -> Self {
+        set1.element_type |= set2.element_type.clone();
+        for value in set2.iter() {
+            set1.insert(value.clone());
+        }
+        set1
+    }
+
+    /// Returns set containing elements of set1 that are also in set2
+    pub fn intersection(set1: &Self, set2: &Self) -> Self {
+        set1.filter(|value| set2.contains(value))
+    }
+
+    /// Returns set containing elements of set1 that are not in set2
+    pub fn difference(set1: &Self, set2: &Self) -> Self {
+        set1.filter(|value| !set2.contains(value))
+    }
+
+    /// Returns set containing elements that are in exactly one of the sets
+    pub fn symmetric_difference(set1: &Self, set2: &Self) -> Self {
+        let mut result = Self::difference(set1, set2);
+        result.element_type |= set2.element_type.clone();
+        for value in set2.iter().filter(|value| !set1.contains(value)) {
+            result.insert(value.clone());
+        }
+        result
+    }
+
+    fn filter(&self, predicate: impl Fn(&Variable) -> bool) -> Self {
+        let mut result = Self::new_with_type(self.element_type.clone());
+        for value in self.iter().filter(|value| predicate(value)) {
+            result.insert(value.clone());
+        }
+        result
+    }
+
+    /// Returns element inserted as index-th
+    pub fn get_index(&self, index: usize) -> Option<&Variable> {
+        self.elements.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns iterator over elements in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = &Variable> {
+        self.elements.iter()
+    }
+
+    pub fn element_type(&self) -> &Type {
+        &self.element_type
+    }
+
+    pub(crate) fn string(&self, depth: u8) -> String {
+        if self.is_empty() {
+            return "{,}".into();
+        }
+        format!(
+            "{{{}}}",
+            self.iter()
+                .map(|value| value.debug(depth + 1))
+                .collect::<Box<[_]>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Typed for Set {
+    fn as_type(&self) -> Type {
+        Type::Set(self.element_type.clone().into())
+    }
+}
+
+impl PartialEq for Set {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|value| other.contains(value))
+    }
+}
+
+impl FromIterator<Variable> for Set {
+    fn from_iter<T: IntoIterator<Item = Variable>>(iter: T) -> Self {
+        let mut set = Self::new_with_type(Type::Never);
+        for value in iter {
+            set.element_type |= value.as_type();
+            set.insert(value);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as simplesl;
+    use crate::variable::{Set, Typed};
+    use simplesl_macros::{var, var_type};
+
+    #[test]
+    fn set() {
+        let set1 = Set::from_iter([var!(1), var!(2), var!(3), var!(2)]);
+        assert_eq!(set1.len(), 3);
+        assert!(set1.contains(&var!(2)));
+        assert!(!set1.contains(&var!(4)));
+        assert_eq!(set1.as_type(), var_type!({ int }));
+        assert_eq!(set1.to_string(), "{1, 2, 3}");
+        let set2 = Set::from_iter([var!(3), var!("a")]);
+        let union = Set::union(set1.clone(), &set2);
+        assert!(union == Set::from_iter([var!(1), var!(2), var!(3), var!("a")]));
+        assert_eq!(union.as_type(), var_type!({ int | string }));
+        assert!(Set::intersection(&set1, &set2) == Set::from_iter([var!(3)]));
+        assert!(Set::difference(&set1, &set2) == Set::from_iter([var!(1), var!(2)]));
+        assert!(
+            Set::symmetric_difference(&set1, &set2)
+                == Set::from_iter([var!(1), var!(2), var!("a")])
+        );
+        assert!(Set::from_iter([var!(2), var!(1)]) == Set::from_iter([var!(1), var!(2)]));
+    }
+}
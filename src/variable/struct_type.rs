@@ -1,7 +1,5 @@
 use derive_more::Display;
 use itertools::Itertools;
-use pest::iterators::Pair;
-use simplesl_parser::Rule;
 use std::{collections::HashMap, hash::Hash, sync::Arc};
 
 use crate::variable::Type;
@@ -42,18 +40,6 @@ impl<const N: usize> From<[(Arc<str>, Type); N]> for StructType {
     }
 }
 
-#[doc(hidden)]
-impl From<Pair<'_, Rule>> for StructType {
-    fn from(pair: Pair<'_, Rule>) -> Self {
-        let tm = pair
-            .into_inner()
-            .tuples()
-            .map(|(key, value)| (key.as_str().into(), Type::from(value)))
-            .collect();
-        Self(Arc::new(tm))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use crate as simplesl;
@@ -1,12 +1,19 @@
-use super::{function_type::FunctionType, multi_type::MultiType};
-use crate::{self as simplesl, errors::ParseTypeError, join, variable::struct_type::StructType};
+use super::{function_type::FunctionType, map_type::MapType, multi_type::MultiType};
+use crate::{
+    self as simplesl,
+    errors::ParseTypeError,
+    join,
+    variable::{EnumType, struct_type::StructType},
+};
 use derive_more::{Display, From};
+use itertools::Itertools;
 use lazy_static::lazy_static;
 use match_any::match_any;
 use pest::{Parser, iterators::Pair};
 use simplesl_macros::var_type;
 use simplesl_parser::{Rule, SimpleSLParser};
 use std::{
+    collections::HashMap,
     hash::Hash,
     iter::zip,
     ops::{BitOr, BitOrAssign},
@@ -37,6 +44,15 @@ pub enum Type {
     Mut(Arc<Type>),
     #[from]
     Struct(StructType),
+    #[from(EnumType, Arc<EnumType>)]
+    Enum(Arc<EnumType>),
+    #[from(MapType)]
+    Map(Arc<MapType>),
+    #[display("{{{_0}}}")]
+    Set(Arc<Type>),
+    /// Type parameter of generic function
+    #[display("{_0}")]
+    Generic(Arc<str>),
     #[display("any")]
     Any,
     #[display("!")]
@@ -58,7 +74,9 @@ impl Type {
             (Type::Never, _) => true,
             (Self::Function(var_type), Self::Function(var_type2))
             | (Self::Array(var_type), Self::Array(var_type2))
-            | (Self::Struct(var_type), Self::Struct(var_type2)) => {
+            | (Self::Set(var_type), Self::Set(var_type2))
+            | (Self::Struct(var_type), Self::Struct(var_type2))
+            | (Self::Map(var_type), Self::Map(var_type2)) => {
                 var_type.matches(var_type2)
             },
             (Self::Multi(types), other) => types.iter().all(|var_type| var_type.matches(other)),
@@ -95,6 +113,12 @@ impl Type {
             (first, second) if first == second => first.clone(),
             (other, Type::Any) | (Type::Any, other) => other.clone(),
             (Type::Array(elm1), Type::Array(elm2)) => Type::Array(elm1.conjoin(elm2).into()),
+            (Type::Set(elm1), Type::Set(elm2)) => Type::Set(elm1.conjoin(elm2).into()),
+            (Type::Map(map1), Type::Map(map2)) => MapType {
+                key: map1.key.conjoin(&map2.key),
+                value: map1.value.conjoin(&map2.value),
+            }
+            .into(),
             (Type::Tuple(types1), Type::Tuple(types2)) => {
                 if types1.len() != types2.len() {
                     return Type::Never;
@@ -213,6 +237,58 @@ impl Type {
         }
     }
 
+    /// Returns type of element of set
+    pub fn set_element_type(&self) -> Option<Type> {
+        match self {
+            Type::Set(element) => Some(element.as_ref().clone()),
+            Type::Multi(multi) => {
+                let mut iter = multi.iter();
+                let first = iter.next().unwrap().set_element_type()?;
+                iter.map(Self::set_element_type)
+                    .try_fold(first, |acc, curr| Some(acc | curr?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns type of keys of map
+    pub fn key_type(&self) -> Option<Type> {
+        match self {
+            Type::Map(map_type) => Some(map_type.key.clone()),
+            Type::Multi(multi) => {
+                let mut iter = multi.iter();
+                let first = iter.next().unwrap().key_type()?;
+                iter.map(Self::key_type)
+                    .try_fold(first, |acc, curr| Some(acc | curr?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns type of values of map
+    pub fn value_type(&self) -> Option<Type> {
+        match self {
+            Type::Map(map_type) => Some(map_type.value.clone()),
+            Type::Multi(multi) => {
+                let mut iter = multi.iter();
+                let first = iter.next().unwrap().value_type()?;
+                iter.map(Self::value_type)
+                    .try_fold(first, |acc, curr| Some(acc | curr?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if values of type can be used as keys of map
+    pub fn is_hashable(&self) -> bool {
+        match self {
+            Self::Bool | Self::Int | Self::String | Self::Never => true,
+            Self::Tuple(types) => types.iter().all(Self::is_hashable),
+            Self::Multi(multi) => multi.iter().all(Self::is_hashable),
+            _ => false,
+        }
+    }
+
     /// Returns type of element of mut
     pub fn mut_element_type(&self) -> Option<Type> {
         match self {
@@ -361,46 +437,87 @@ impl FromStr for Type {
         let Ok(mut pairs) = SimpleSLParser::parse(Rule::r#type, s) else {
             return Err(ParseTypeError);
         };
-        Ok(Type::from(pairs.next().unwrap()))
+        Type::from_pair(pairs.next().unwrap(), &|_| None).map_err(|_| ParseTypeError)
     }
 }
 
-#[doc(hidden)]
-impl From<Pair<'_, Rule>> for Type {
-    fn from(pair: Pair<'_, Rule>) -> Self {
-        match pair.as_rule() {
+impl Type {
+    /// Builds type from pair, resolving type aliases with `aliases`.
+    /// Returns name of the alias if it cannot be resolved
+    pub(crate) fn from_pair(
+        pair: Pair<'_, Rule>,
+        aliases: &impl Fn(&str) -> Option<Type>,
+    ) -> Result<Self, Arc<str>> {
+        let from_pair = |pair| Self::from_pair(pair, aliases);
+        Ok(match pair.as_rule() {
             Rule::bool_type => Self::Bool,
             Rule::int_type => Self::Int,
             Rule::float_type => Self::Float,
             Rule::string_type => Self::String,
             Rule::void => Self::Void,
-            Rule::function_type => FunctionType::from(pair).into(),
+            Rule::function_type => {
+                let mut pairs = pair.into_inner();
+                let params = pairs
+                    .next()
+                    .unwrap()
+                    .into_inner()
+                    .map(from_pair)
+                    .collect::<Result<_, _>>()?;
+                let return_type = from_pair(pairs.next().unwrap())?;
+                FunctionType {
+                    params,
+                    return_type,
+                }
+                .into()
+            }
             Rule::array_type => {
                 let element_type = pair
                     .into_inner()
                     .next()
-                    .map(Type::from)
+                    .map(from_pair)
+                    .transpose()?
                     .unwrap_or(Type::Never);
                 Self::Array(element_type.into())
             }
             Rule::tuple_type => {
-                let types = pair.into_inner().map(Type::from).collect();
+                let types = pair.into_inner().map(from_pair).collect::<Result<_, _>>()?;
                 Self::Tuple(types)
             }
             Rule::multi => pair
                 .into_inner()
-                .map(Type::from)
-                .reduce(Type::concat)
-                .unwrap(),
+                .map(from_pair)
+                .reduce(|acc, curr| Ok(acc?.concat(curr?)))
+                .unwrap()?,
             Rule::any => Self::Any,
             Rule::never => Self::Never,
             Rule::mut_type => {
-                let element_type = pair.into_inner().next().map(Type::from).unwrap();
+                let element_type = from_pair(pair.into_inner().next().unwrap())?;
                 Self::Mut(element_type.into())
             }
-            Rule::struct_type => StructType::from(pair).into(),
+            Rule::struct_type => {
+                let fields = pair
+                    .into_inner()
+                    .tuples()
+                    .map(|(key, value)| Ok::<_, Arc<str>>((key.as_str().into(), from_pair(value)?)))
+                    .collect::<Result<HashMap<_, _>, _>>()?;
+                StructType::from(fields).into()
+            }
+            Rule::map_type => {
+                let mut pairs = pair.into_inner();
+                let key = from_pair(pairs.next().unwrap())?;
+                let value = from_pair(pairs.next().unwrap())?;
+                MapType { key, value }.into()
+            }
+            Rule::set_type => {
+                let element_type = from_pair(pair.into_inner().next().unwrap())?;
+                Self::Set(element_type.into())
+            }
+            Rule::type_alias => {
+                let ident = pair.as_str();
+                aliases(ident).ok_or_else(|| Arc::from(ident))?
+            }
             rule => panic!("Type cannot be built from rule: {rule:?}"),
-        }
+        })
     }
 }
 
@@ -0,0 +1,599 @@
+mod compiler;
+mod op;
+pub(crate) use self::{compiler::Compiler, op::Op};
+use crate::{
+    ExecError, Interpreter, Location,
+    instruction::{
+        ExecResult, ExecStop, InstructionWithStr, array_repeat, assign_element, bin_op,
+        local_variable::Slot, map_literal, range, reduce, set_literal, slicing, type_filter,
+        unary_operation,
+    },
+    interpreter::VariableMap,
+    variable::{Enum, Mut, Type, Typed, Variable},
+};
+use std::sync::Arc;
+
+/// Bytecode of instructions executed by the vm
+#[derive(Debug, Clone)]
+pub(crate) struct Chunk {
+    ops: Arc<[Op]>,
+    /// Locations reported in errors of ops
+    locations: Arc<[Option<Location>]>,
+}
+
+/// Loop being executed, restored on break and continue
+struct LoopFrame {
+    start: usize,
+    end: usize,
+    stack: usize,
+}
+
+/// State of chunk execution, kept by generator while it is suspended
+#[derive(Default)]
+pub(crate) struct ExecState {
+    pc: usize,
+    stack: Vec<Variable>,
+    loops: Vec<LoopFrame>,
+    tries: Vec<TryFrame>,
+}
+
+/// Try block being executed, restored when it catches thrown value
+struct TryFrame {
+    var_type: Type,
+    slot: Slot,
+    catch: usize,
+    stack: usize,
+    /// Number of loops being executed when the block started
+    loops: usize,
+}
+
+impl Chunk {
+    /// Compiles instructions executed one after another
+    pub fn new(instructions: &[InstructionWithStr]) -> Self {
+        let mut compiler = Compiler::default();
+        compiler.compile_sequence(instructions);
+        compiler.finish()
+    }
+
+    /// Compiles body of function, recursive calls in tail position reuse its frame
+    pub fn function(instructions: &[InstructionWithStr]) -> Self {
+        let mut compiler = Compiler::function();
+        compiler.compile_sequence(instructions);
+        compiler.finish()
+    }
+
+    /// Executes chunk returning value of the last instruction
+    pub fn exec(&self, interpreter: &mut Interpreter) -> ExecResult {
+        self.resume(&mut ExecState::default(), interpreter)
+    }
+
+    /// Continues execution of chunk from given state.
+    /// Execution stopped by yield can be resumed with the same state
+    pub fn resume(&self, state: &mut ExecState, interpreter: &mut Interpreter) -> ExecResult {
+        let ExecState {
+            pc,
+            stack,
+            loops,
+            tries,
+        } = state;
+        while let Some(op) = self.ops.get(*pc) {
+            *pc += 1;
+            let Err(stop) = Self::exec_op(op, pc, stack, loops, tries, interpreter) else {
+                continue;
+            };
+            match (stop, loops.last()) {
+                (ExecStop::Break, Some(_)) => {
+                    let frame = loops.pop().unwrap();
+                    end_tries(tries, loops.len());
+                    stack.truncate(frame.stack);
+                    *pc = frame.end;
+                }
+                (ExecStop::Continue, Some(frame)) => {
+                    end_tries(tries, loops.len() - 1);
+                    stack.truncate(frame.stack);
+                    *pc = frame.start;
+                }
+                (ExecStop::Error(error), _) => {
+                    let error = match &self.locations[*pc - 1] {
+                        Some(location) => error.at(location),
+                        None => error,
+                    };
+                    let Some((frame, value)) = catch(tries, &error) else {
+                        return Err(ExecStop::Error(error));
+                    };
+                    interpreter.set_local(frame.slot, value);
+                    stack.truncate(frame.stack);
+                    loops.truncate(frame.loops);
+                    *pc = frame.catch;
+                }
+                (stop, _) => return Err(stop),
+            }
+        }
+        Ok(stack.pop().unwrap_or(Variable::Void))
+    }
+
+    fn exec_op(
+        op: &Op,
+        pc: &mut usize,
+        stack: &mut Vec<Variable>,
+        loops: &mut Vec<LoopFrame>,
+        tries: &mut Vec<TryFrame>,
+        interpreter: &mut Interpreter,
+    ) -> Result<(), ExecStop> {
+        match op {
+            Op::Constant(variable) => stack.push(variable.clone()),
+            Op::Load(slot) => stack.push(interpreter.local(*slot).clone()),
+            Op::Store(slot) => {
+                let variable = stack.last().unwrap().clone();
+                interpreter.set_local(*slot, variable);
+            }
+            Op::Pop => {
+                stack.pop();
+            }
+            Op::Jump(target) => *pc = *target,
+            Op::JumpIfFalse(target) => {
+                if !stack.pop().unwrap().into_bool().unwrap() {
+                    *pc = *target;
+                }
+            }
+            Op::JumpIfEqual(target) => {
+                let value = stack.pop().unwrap();
+                if stack.last().is_some_and(|last| last.equals(&value)) {
+                    *pc = *target;
+                }
+            }
+            Op::And(target) => {
+                if stack.last() == Some(&Variable::Bool(false)) {
+                    *pc = *target;
+                } else {
+                    stack.pop();
+                }
+            }
+            Op::Or(target) => {
+                if stack.last() == Some(&Variable::Bool(true)) {
+                    *pc = *target;
+                } else {
+                    stack.pop();
+                }
+            }
+            Op::Binary(op) => {
+                let rhs = stack.pop().unwrap();
+                let lhs = stack.pop().unwrap();
+                stack.push(bin_op::exec(lhs, rhs, *op, interpreter.call_depth())?);
+            }
+            Op::Unary(op) => {
+                let variable = stack.pop().unwrap();
+                stack.push(unary_operation::exec(
+                    variable,
+                    *op,
+                    interpreter.call_depth(),
+                )?);
+            }
+            Op::Call(len) => {
+                let args = stack.split_off(stack.len() - len);
+                let function = stack.pop().unwrap().into_function().unwrap();
+                stack.push(function.exec_with_args(&args, interpreter.call_depth())?);
+            }
+            Op::TailCall(len) => {
+                let args = stack.split_off(stack.len() - len);
+                let function = stack.pop().unwrap().into_function().unwrap();
+                if interpreter.is_executing(&function) {
+                    return Err(ExecStop::TailCall(args));
+                }
+                // Other functions are called normally so they remain in stack trace
+                return Err(ExecStop::Return(
+                    function.exec_with_args(&args, interpreter.call_depth())?,
+                ));
+            }
+            Op::Tuple(len) => {
+                let elements = stack.split_off(stack.len() - len);
+                stack.push(Variable::Tuple(elements.into()));
+            }
+            Op::Array(len) => {
+                let elements: Arc<[Variable]> = stack.split_off(stack.len() - len).into();
+                stack.push(elements.into());
+            }
+            Op::ArrayRepeat => {
+                let len = stack.pop().unwrap();
+                let value = stack.pop().unwrap();
+                stack.push(array_repeat::exec(value, len)?);
+            }
+            Op::TupleAccess(index) => {
+                let tuple = stack.pop().unwrap().into_tuple().unwrap();
+                stack.push(tuple[*index].clone());
+            }
+            Op::FieldAccess(ident) => {
+                let fields = stack.pop().unwrap().into_struct().unwrap();
+                stack.push(fields.get(ident).unwrap().clone());
+            }
+            Op::Struct(idents) => {
+                let values = stack.split_off(stack.len() - idents.len());
+                let fields: VariableMap = idents.iter().cloned().zip(values).collect();
+                stack.push(Variable::Struct(fields.into()));
+            }
+            Op::Map {
+                key_type,
+                value_type,
+                len,
+            } => {
+                let entries = stack.split_off(stack.len() - 2 * len);
+                stack.push(map_literal::exec(key_type, value_type, entries));
+            }
+            Op::Set { element_type, len } => {
+                let elements = stack.split_off(stack.len() - len);
+                stack.push(set_literal::exec(element_type, elements));
+            }
+            Op::EnumVariant {
+                enum_type,
+                variant,
+                len,
+            } => {
+                let values = stack.split_off(stack.len() - len);
+                let variant = Enum {
+                    enum_type: enum_type.clone(),
+                    variant: *variant,
+                    values: values.into(),
+                };
+                stack.push(variant.into());
+            }
+            Op::Mut(var_type) => {
+                let variable = stack.pop().unwrap().into();
+                let var_type = var_type.clone();
+                stack.push(Mut { var_type, variable }.into());
+            }
+            Op::Range { inclusive } => {
+                let step = stack.pop().unwrap().into_int().unwrap();
+                let end = stack.pop().unwrap().into_int().unwrap();
+                let start = stack.pop().unwrap().into_int().unwrap();
+                stack.push(range::int_range(start, end, step, *inclusive)?.into());
+            }
+            Op::Slice { start, stop, step } => {
+                let step = step.then(|| stack.pop().unwrap());
+                let stop = stop.then(|| stack.pop().unwrap());
+                let start = start.then(|| stack.pop().unwrap());
+                let lhs = stack.pop().unwrap();
+                stack.push(slicing::exec(lhs, start, stop, step));
+            }
+            Op::TypeFilter(var_type) => {
+                let iterator = stack.pop().unwrap();
+                stack.push(type_filter::exec(
+                    iterator,
+                    var_type,
+                    interpreter.call_depth(),
+                ));
+            }
+            Op::Reduce => {
+                let function = stack.pop().unwrap();
+                let initial_value = stack.pop().unwrap();
+                let iter = stack.pop().unwrap();
+                stack.push(reduce::exec(
+                    iter,
+                    initial_value,
+                    function,
+                    interpreter.call_depth(),
+                )?);
+            }
+            Op::AssignElement { path, op } => {
+                let operands = stack.split_off(stack.len() - assign_element::operands(path));
+                stack.push(assign_element::exec(operands, path, *op)?);
+            }
+            Op::Closure(closure) => {
+                let captured = stack.split_off(stack.len() - closure.captures);
+                stack.push(closure.create(captured, interpreter).into());
+            }
+            Op::Propagate(failure) => {
+                if stack.last().unwrap().as_type().matches(failure) {
+                    return Err(ExecStop::Return(stack.pop().unwrap()));
+                }
+            }
+            Op::Bind(pattern) => pattern.bind(stack.last().unwrap(), interpreter),
+            Op::MatchArm { arm, otherwise } => {
+                let variable = stack.last().unwrap();
+                if arm.matches(variable) {
+                    arm.bind(variable, interpreter);
+                } else {
+                    *pc = *otherwise;
+                }
+            }
+            Op::Try {
+                var_type,
+                slot,
+                catch,
+            } => tries.push(TryFrame {
+                var_type: var_type.clone(),
+                slot: *slot,
+                catch: *catch,
+                stack: stack.len(),
+                loops: loops.len(),
+            }),
+            Op::EndTry => {
+                tries.pop();
+            }
+            Op::Loop { end } => loops.push(LoopFrame {
+                start: *pc,
+                end: *end,
+                stack: stack.len(),
+            }),
+            Op::Yield => {
+                let value = stack.pop().unwrap();
+                stack.push(Variable::Void);
+                return Err(ExecStop::Yield(value));
+            }
+            Op::Break => return Err(ExecStop::Break),
+            Op::Continue => return Err(ExecStop::Continue),
+        }
+        Ok(())
+    }
+}
+
+/// Ends try blocks started inside loops left by break or continue
+fn end_tries(tries: &mut Vec<TryFrame>, loops: usize) {
+    while tries.last().is_some_and(|frame| frame.loops > loops) {
+        tries.pop();
+    }
+}
+
+/// Ends try blocks until one catching error and returns it with caught value
+fn catch(tries: &mut Vec<TryFrame>, error: &ExecError) -> Option<(TryFrame, Variable)> {
+    let ExecError::User(value) = error.root() else {
+        return None;
+    };
+    while let Some(frame) = tries.pop() {
+        if value.as_type().matches(&frame.var_type) {
+            return Some((frame, value.clone()));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as simplesl, Code, Error, ExecError, Interpreter, variable::Variable};
+    use simplesl_macros::var;
+
+    #[test]
+    fn scopes() {
+        assert_eq!(parse_and_exec("x := 1\n{ x := 2; y := x }\nx"), Ok(var!(1)));
+        assert_eq!(
+            parse_and_exec(
+                "sum := mut 0
+                for i in 0..4 {
+                    x := i * 2
+                    sum += x
+                }
+                *sum"
+            ),
+            Ok(var!(12))
+        );
+        let mut interpreter = Interpreter::without_stdlib();
+        Code::parse(&interpreter, "x := 5; { y := 1 }")
+            .unwrap()
+            .exec_unscoped(&mut interpreter)
+            .unwrap();
+        assert_eq!(interpreter.get_variable("x"), Some(&var!(5)));
+        assert_eq!(interpreter.get_variable("y"), None);
+        let result = Code::parse(&interpreter, "a := 1; z := mut 0; b := a / *z; c := 2")
+            .unwrap()
+            .exec_unscoped(&mut interpreter);
+        assert!(result.is_err());
+        assert_eq!(interpreter.get_variable("a"), Some(&var!(1)));
+        assert_eq!(interpreter.get_variable("b"), None);
+        assert_eq!(interpreter.get_variable("c"), None);
+    }
+
+    #[test]
+    fn slots() {
+        assert_eq!(
+            parse_and_exec(
+                "x := 1
+                { a := 10; b := a + x }
+                { c := 20; d := c * 2 }
+                y := 2;
+                (x, y)"
+            ),
+            Ok(var!((1, 2)))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "add := (a: int, b: int) -> () -> int {
+                    sum := a + b
+                    return () -> int { return sum * a }
+                }
+                f := add(2, 3)
+                g := add(1, 1);
+                (f(), g())"
+            ),
+            Ok(var!((10, 2)))
+        );
+    }
+
+    #[test]
+    fn closures() {
+        assert_eq!(
+            parse_and_exec(
+                "x := 2
+                make := (n: int) -> () -> () -> int {
+                    return () -> () -> int {
+                        return () -> int { return x * n }
+                    }
+                }
+                make(5)()()"
+            ),
+            Ok(var!(10))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "counter := mut 0
+                increment := () -> int {
+                    counter += 1
+                    return *counter
+                }
+                increment()
+                increment()"
+            ),
+            Ok(var!(2))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "double := (x: int) -> int { return 2 * x }
+                apply := (x: int) -> int { return double(x) + 1 }
+                apply(3)"
+            ),
+            Ok(var!(7))
+        );
+    }
+
+    #[test]
+    fn break_and_continue() {
+        assert_eq!(
+            parse_and_exec(
+                "i := mut 0
+                sum := mut 0
+                loop {
+                    i += 1
+                    match *i {
+                        2 => { continue },
+                        5 => { break },
+                        x: int => sum += x,
+                    }
+                }
+                *sum"
+            ),
+            Ok(var!(8))
+        );
+        assert_eq!(
+            parse_and_exec(
+                "count := mut 0
+                for i in 0..3 {
+                    for j in 0..3 {
+                        if j > i { break }
+                        count += 1
+                    }
+                }
+                *count"
+            ),
+            Ok(var!(6))
+        );
+    }
+
+    #[test]
+    fn tail_calls() {
+        assert_eq!(
+            parse_and_exec(
+                "sum := (n: int, acc: int) -> int {
+                    if n == 0 {
+                        return acc
+                    }
+                    return sum(n - 1, acc + n)
+                }
+                sum(100000, 0)"
+            ),
+            Ok(var!(5000050000))
+        );
+    }
+
+    #[test]
+    fn recursion_limit() {
+        let mut interpreter = Interpreter::without_stdlib();
+        interpreter.set_recursion_limit(100);
+        let script = "f := (n: int) -> int {
+            if n == 0 {
+                return 0
+            }
+            return 1 + f(n - 1)
+        }";
+        Code::parse(&interpreter, script)
+            .unwrap()
+            .exec_unscoped(&mut interpreter)
+            .unwrap();
+        let exec = |interpreter: &mut Interpreter, script: &str| {
+            Code::parse(interpreter, script)
+                .unwrap()
+                .exec_unscoped(interpreter)
+        };
+        assert_eq!(exec(&mut interpreter, "f(99)"), Ok(var!(99)));
+        let error = exec(&mut interpreter, "f(100)").unwrap_err();
+        assert_eq!(error.root(), &ExecError::StackOverflow("f".into()));
+        assert_eq!(exec(&mut interpreter, "f(50)"), Ok(var!(50)));
+        let error = exec(
+            &mut interpreter,
+            "g := (n: int) -> int { return g(n) + 1 }\ng(0)",
+        )
+        .unwrap_err();
+        assert_eq!(error.root(), &ExecError::StackOverflow("g".into()));
+        let error = exec(
+            &mut interpreter,
+            "h := (n: int) -> int { return ([n]~ @ h $])[0] }\nh(0)",
+        )
+        .unwrap_err();
+        assert!(matches!(error.root(), ExecError::StackOverflow(_)));
+    }
+
+    #[test]
+    fn iterator_chain_limit() {
+        let chain = |len: usize| {
+            parse_and_exec(&format!(
+                "it := mut [1]~
+                f := (x: int) -> int {{ return x }}
+                for i in 0..{len} {{ it = *it @ f }}
+                (*it)()"
+            ))
+        };
+        assert_eq!(chain(500), Ok(var!((true, 1))));
+        assert!(matches!(chain(200000), Err(Error::StackOverflow(_))));
+    }
+
+    #[test]
+    fn deep_recursion() {
+        assert_eq!(
+            parse_and_exec(
+                "f := (n: int) -> int {
+                    return match n {
+                        0 => 0,
+                        x: int => 1 + f(x - 1),
+                    }
+                }
+                f(999)"
+            ),
+            Ok(var!(999))
+        );
+        let mut interpreter = Interpreter::without_stdlib();
+        interpreter.set_recursion_limit(20000);
+        let script = "f := (n: int) -> int {
+            if n == 0 {
+                return 0
+            }
+            return 1 + f(n - 1)
+        }
+        f(19999)";
+        assert_eq!(
+            Code::parse(&interpreter, script)
+                .unwrap()
+                .exec_unscoped(&mut interpreter),
+            Ok(var!(19999))
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        let script = "x := mut 0
+            for i in 0..2 {
+                y := i / *x
+            }";
+        let error = Code::parse(&Interpreter::without_stdlib(), script)
+            .unwrap()
+            .exec()
+            .unwrap_err();
+        assert_eq!(error.root(), &ExecError::ZeroDivision);
+        let location = error.trace().unwrap().frames()[0]
+            .location
+            .as_ref()
+            .unwrap();
+        assert_eq!((location.line, location.column), (3, 22));
+    }
+
+    fn parse_and_exec(script: &str) -> Result<Variable, Error> {
+        Code::parse(&Interpreter::without_stdlib(), script)
+            .and_then(|code| code.exec().map_err(Error::from))
+    }
+}
@@ -0,0 +1,97 @@
+use super::{Chunk, Op};
+use crate::{
+    Location,
+    instruction::{Compile, InstructionWithStr},
+    variable::Variable,
+};
+
+/// Builds chunk of bytecode from instructions
+#[derive(Debug, Default)]
+pub(crate) struct Compiler {
+    ops: Vec<Op>,
+    locations: Vec<Option<Location>>,
+    /// Location of the innermost instruction being compiled that has one
+    location: Option<Location>,
+    /// Whether calls returned from the compiled code can reuse its frame
+    tail_calls: bool,
+}
+
+impl Compiler {
+    /// Creates compiler of function body, returned calls are compiled into tail calls
+    pub fn function() -> Self {
+        Self {
+            tail_calls: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn tail_calls(&self) -> bool {
+        self.tail_calls
+    }
+
+    /// Compiles code in which calls in tail position don't release the frame
+    pub fn without_tail_calls(&mut self, compile: impl FnOnce(&mut Self)) {
+        let tail_calls = std::mem::replace(&mut self.tail_calls, false);
+        compile(self);
+        self.tail_calls = tail_calls;
+    }
+
+    /// Appends op and returns its position
+    pub fn emit(&mut self, op: Op) -> usize {
+        self.ops.push(op);
+        self.locations.push(self.location.clone());
+        self.ops.len() - 1
+    }
+
+    /// Returns position of the next emitted op
+    pub fn position(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Sets target of jump at given position to the next emitted op
+    pub fn patch(&mut self, jump: usize) {
+        let target = self.position();
+        match &mut self.ops[jump] {
+            Op::Jump(to)
+            | Op::JumpIfFalse(to)
+            | Op::JumpIfEqual(to)
+            | Op::And(to)
+            | Op::Or(to)
+            | Op::MatchArm { otherwise: to, .. }
+            | Op::Try { catch: to, .. }
+            | Op::Loop { end: to } => *to = target,
+            op => unreachable!("Tried to patch {op:?}"),
+        }
+    }
+
+    /// Compiles instruction, errors of its ops are reported at its location
+    pub fn compile(&mut self, instruction: &InstructionWithStr) {
+        let Some(location) = &instruction.location else {
+            instruction.instruction.compile(self);
+            return;
+        };
+        let outer = self.location.replace(location.clone());
+        instruction.instruction.compile(self);
+        self.location = outer;
+    }
+
+    /// Compiles instructions executed one after another leaving value of the last one on the stack
+    pub fn compile_sequence(&mut self, instructions: &[InstructionWithStr]) {
+        let Some((last, instructions)) = instructions.split_last() else {
+            self.emit(Op::Constant(Variable::Void));
+            return;
+        };
+        for instruction in instructions {
+            self.compile(instruction);
+            self.emit(Op::Pop);
+        }
+        self.compile(last);
+    }
+
+    pub fn finish(self) -> Chunk {
+        Chunk {
+            ops: self.ops.into(),
+            locations: self.locations.into(),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    BinOperator,
+    function::Closure,
+    instruction::{
+        assign_element::Accessor, control_flow::MatchArmKind, local_variable::Slot,
+        pattern::Pattern,
+    },
+    unary_operator::UnaryOperator,
+    variable::{EnumType, Type, Variable},
+};
+use std::sync::Arc;
+
+/// Single instruction of the vm, operands are taken from the top of the stack
+#[derive(Debug, Clone)]
+pub(crate) enum Op {
+    /// Pushes the value
+    Constant(Variable),
+    /// Pushes value of local variable stored in given slot
+    Load(Slot),
+    /// Stores value on the top of the stack in given slot, the value is left on the stack
+    Store(Slot),
+    Pop,
+    Jump(usize),
+    /// Pops bool and jumps if it is false
+    JumpIfFalse(usize),
+    /// Pops value and jumps if it is equal to the new top value
+    JumpIfEqual(usize),
+    /// Jumps leaving false on the stack if the top value is false, otherwise pops it
+    And(usize),
+    /// Jumps leaving true on the stack if the top value is true, otherwise pops it
+    Or(usize),
+    Binary(BinOperator),
+    Unary(UnaryOperator),
+    /// Pops given number of arguments and the function and pushes value returned by it
+    Call(usize),
+    /// Pops given number of arguments and the function and returns value returned by it.
+    /// Recursive call is left to the caller, so the frame of the current function is released first
+    TailCall(usize),
+    /// Pops given number of values and pushes tuple of them
+    Tuple(usize),
+    /// Pops given number of values and pushes array of them
+    Array(usize),
+    /// Pops length and value and pushes array of value repeated length times
+    ArrayRepeat,
+    TupleAccess(usize),
+    FieldAccess(Arc<str>),
+    /// Pops values of fields with given idents and pushes struct of them
+    Struct(Arc<[Arc<str>]>),
+    /// Pops given number of keys each followed by its value and pushes map of them
+    Map {
+        key_type: Type,
+        value_type: Type,
+        len: usize,
+    },
+    /// Pops given number of values and pushes set of them
+    Set {
+        element_type: Type,
+        len: usize,
+    },
+    /// Pops payload of enum variant and pushes the variant
+    EnumVariant {
+        enum_type: Arc<EnumType>,
+        variant: usize,
+        len: usize,
+    },
+    /// Pops value and pushes mut holding it
+    Mut(Type),
+    /// Pops step, end and start and pushes iterator over ints between them
+    Range {
+        inclusive: bool,
+    },
+    /// Pops indexes given by flags and indexed value and pushes its slice
+    Slice {
+        start: bool,
+        stop: bool,
+        step: bool,
+    },
+    /// Pops iterator and pushes iterator over its elements matching type
+    TypeFilter(Type),
+    /// Pops function, initial value and iterator and pushes result of reducing the iterator
+    Reduce,
+    /// Pops value, keys of path and mut and assigns value to element of mut at path
+    AssignElement {
+        path: Arc<[Accessor]>,
+        op: BinOperator,
+    },
+    /// Pops captured values and pushes function created from closure
+    Closure(Arc<Closure>),
+    /// Returns the top value if it matches type
+    Propagate(Type),
+    /// Binds the top value to slots of pattern, the value is left on the stack
+    Bind(Pattern),
+    /// Binds the top value to match arm if it matches it, otherwise jumps. The value is left on the stack
+    MatchArm {
+        arm: MatchArmKind,
+        otherwise: usize,
+    },
+    /// Starts try block, values thrown by it that match type are stored in slot
+    /// and execution continues at catch
+    Try {
+        var_type: Type,
+        slot: Slot,
+        catch: usize,
+    },
+    /// Ends try block started by the innermost try
+    EndTry,
+    /// Starts loop which body begins at the next op, break jumps to end
+    Loop {
+        end: usize,
+    },
+    /// Pops value and suspends execution of generator passing the value to its consumer.
+    /// `()` is pushed as the value of yield
+    Yield,
+    Break,
+    Continue,
+}